@@ -0,0 +1,147 @@
+//! The symbols that stand for a cell's number when reading and writing boards
+//!
+//! Keeping them apart from the numbers means the solver never sees them, so letters work as well as digits,
+//! and a grid of any size gets as many symbols as it has numbers
+
+/// Which character stands for each number from 1 up to however many symbols there are
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alphabet {
+    /// Only the first `len` are used
+    symbols: [char; Alphabet::MAX_LEN],
+    len: usize,
+}
+
+impl Alphabet {
+    /// The most symbols an alphabet can have, which is as big as a grid's numbers get
+    pub const MAX_LEN: usize = 63;
+
+    /// `1` through `9`, the usual symbols
+    pub const DIGITS: Alphabet =
+        Alphabet::from_array(['1', '2', '3', '4', '5', '6', '7', '8', '9']);
+
+    /// `A` through `I`, for puzzles written with letters
+    pub const LETTERS: Alphabet =
+        Alphabet::from_array(['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I']);
+
+    /// `0` through `9` and then `A` through `F`, for 16x16 grids written in hex, so `0` stands for 1 and `F` for 16
+    pub const HEX: Alphabet = Alphabet::from_array([
+        '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'A', 'B', 'C', 'D', 'E', 'F',
+    ]);
+
+    /// `symbols` padded out to [`Alphabet::MAX_LEN`], for the constants, which are checked by hand
+    const fn from_array<const LEN: usize>(symbols: [char; LEN]) -> Alphabet {
+        let mut padded = [' '; Alphabet::MAX_LEN];
+        let mut idx = 0;
+        while idx < LEN {
+            padded[idx] = symbols[idx];
+            idx += 1;
+        }
+        Alphabet {
+            symbols: padded,
+            len: LEN,
+        }
+    }
+
+    /// An alphabet where `symbols[n - 1]` stands for `n`
+    ///
+    /// Returns `None` if there are no symbols or more than [`Alphabet::MAX_LEN`], or if a symbol repeats, is
+    /// whitespace, or is `x`, which always marks a blank cell. Symbols are matched ignoring ASCII case, so two that
+    /// only differ by case count as repeats
+    pub fn new(symbols: &[char]) -> Option<Alphabet> {
+        let unusable = |c: char| c.is_whitespace() || c.eq_ignore_ascii_case(&'x');
+        let repeats = (0..symbols.len())
+            .any(|i| (0..i).any(|j| symbols[i].eq_ignore_ascii_case(&symbols[j])));
+        if symbols.is_empty()
+            || symbols.len() > Alphabet::MAX_LEN
+            || symbols.iter().any(|&c| unusable(c))
+            || repeats
+        {
+            return None;
+        }
+        let mut padded = [' '; Alphabet::MAX_LEN];
+        padded[..symbols.len()].copy_from_slice(symbols);
+        Some(Alphabet {
+            symbols: padded,
+            len: symbols.len(),
+        })
+    }
+
+    /// The usual symbols for a grid `side` cells across: `1`-`9`, then `A` for 10, `B` for 11, and so on
+    ///
+    /// Returns `None` past 32, where the next letter would be `X`, which always marks a blank
+    pub fn for_side(side: usize) -> Option<Alphabet> {
+        let symbols = ('1'..='9').chain('A'..='Z').take(side).collect::<Vec<_>>();
+        match symbols.len() == side {
+            true => Alphabet::new(&symbols),
+            false => None,
+        }
+    }
+
+    /// How many symbols there are, which is the biggest number they can write
+    pub fn num_symbols(&self) -> usize {
+        self.len
+    }
+
+    /// The symbols in order, so `symbols()[n - 1]` stands for `n`
+    pub fn symbols(&self) -> &[char] {
+        &self.symbols[..self.len]
+    }
+
+    /// The character for `num`, which must be in `1..=self.num_symbols()`
+    #[track_caller]
+    pub fn symbol(&self, num: u8) -> char {
+        assert!(
+            (1..=self.len).contains(&(num as usize)),
+            "the alphabet has no symbol for {num}"
+        );
+        self.symbols[num as usize - 1]
+    }
+
+    /// The number `c` stands for, if it's in the alphabet
+    pub fn value(&self, c: char) -> Option<u8> {
+        self.symbols()
+            .iter()
+            .position(|symbol| symbol.eq_ignore_ascii_case(&c))
+            .map(|idx| idx as u8 + 1)
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Alphabet::DIGITS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_has_sixteen_symbols() {
+        assert_eq!(Alphabet::HEX.num_symbols(), 16);
+        assert_eq!(Alphabet::HEX.value('0'), Some(1));
+        assert_eq!(Alphabet::HEX.value('f'), Some(16));
+        assert_eq!(Alphabet::HEX.symbol(11), 'A');
+    }
+
+    #[test]
+    fn new_rejects_unusable_symbols() {
+        assert_eq!(Alphabet::new(&[]), None);
+        assert_eq!(Alphabet::new(&['a', 'A']), None);
+        assert_eq!(Alphabet::new(&['1', 'x']), None);
+        assert_eq!(Alphabet::new(&['1', ' ']), None);
+        assert_eq!(Alphabet::new(&['a'; Alphabet::MAX_LEN + 1]), None);
+        assert_eq!(
+            Alphabet::new(&['1', '2', '3', '4', '5', '6', '7', '8', '9']),
+            Some(Alphabet::DIGITS)
+        );
+    }
+
+    #[test]
+    fn for_side_stops_before_x() {
+        assert_eq!(Alphabet::for_side(9), Some(Alphabet::DIGITS));
+        assert_eq!(Alphabet::for_side(16).unwrap().symbol(16), 'G');
+        assert_eq!(Alphabet::for_side(32).unwrap().symbol(32), 'W');
+        assert_eq!(Alphabet::for_side(33), None);
+    }
+}
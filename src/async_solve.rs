@@ -0,0 +1,99 @@
+//! Solving without blocking a tokio runtime
+//!
+//! The solve runs on tokio's blocking pool through `spawn_blocking`, so the CPU-bound search never holds up the
+//! runtime's workers
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll},
+};
+
+use tokio::task::JoinHandle;
+
+use crate::{Board, Solver};
+
+/// Resolves to the solution of the board passed to [`solve_async`]
+///
+/// Dropping this before it resolves cancels the solve
+pub struct SolveFuture {
+    handle: JoinHandle<Option<Board>>,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Solves `board` with `spawn_blocking`, keeping the CPU-bound search off the async runtime's workers
+///
+/// Like `spawn_blocking` it panics if called outside of a tokio runtime. If the runtime shuts down before the
+/// solve gets going, the future resolves to `None`
+pub fn solve_async(board: Board) -> SolveFuture {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let worker = Arc::clone(&cancel);
+    let handle = tokio::task::spawn_blocking(move || Solver::new().solve_until(board, &worker));
+    SolveFuture { handle, cancel }
+}
+
+impl Future for SolveFuture {
+    type Output = Option<Board>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.handle)
+            .poll(cx)
+            .map(|result| match result {
+                Ok(solution) => solution,
+                Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                Err(_) => None,
+            })
+    }
+}
+
+impl Drop for SolveFuture {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn resolves_to_the_solution() {
+        for puzzle in [
+            include_str!("../examples/easy1.txt"),
+            include_str!("../examples/extreme1.txt"),
+        ] {
+            let board: Board = puzzle.parse().unwrap();
+            let expected = Solver::new().solve(board.clone());
+            let solution = runtime().block_on(async { solve_async(board).await });
+            assert!(solution.as_ref().is_some_and(Board::is_solved));
+            assert_eq!(solution, expected);
+        }
+    }
+
+    #[test]
+    fn resolves_to_none_without_a_solution() {
+        let board: Board = include_str!("../examples/fail3.txt").parse().unwrap();
+        assert_eq!(runtime().block_on(async { solve_async(board).await }), None);
+    }
+
+    #[test]
+    fn dropping_the_future_cancels_the_solve() {
+        let runtime = runtime();
+        let _guard = runtime.enter();
+        let future = solve_async(Board::new());
+        let cancel = Arc::clone(&future.cancel);
+        assert!(!cancel.load(Ordering::Relaxed));
+        drop(future);
+        assert!(cancel.load(Ordering::Relaxed));
+    }
+}
@@ -0,0 +1,759 @@
+//! Solving many puzzles in one go
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use rayon::prelude::*;
+
+use crate::{Board, Solver, constraint::split_constraints, parse::ParseError, solve};
+
+/// Extensions of the files [`solve_dir`] treats as puzzles
+const PUZZLE_EXTENSIONS: [&str; 2] = ["txt", "sdk"];
+
+/// Extension of the files [`solve_dir`] writes solutions to, next to their puzzle
+const SOLUTION_EXTENSION: &str = "solution";
+
+/// Totals across a batch of puzzles
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BatchStats {
+    pub solved: usize,
+    pub failed: usize,
+    pub elapsed: Duration,
+}
+
+/// How far along a batch is, reported each time a puzzle finishes
+#[derive(Debug, Clone, Copy)]
+pub struct BatchProgress {
+    /// How many puzzles have finished so far, including this one
+    pub done: usize,
+    pub total: usize,
+    /// Totals over the finished puzzles
+    pub stats: BatchStats,
+}
+
+impl std::fmt::Display for BatchStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} solved, {} failed, {} total in {:.2?}",
+            self.solved,
+            self.failed,
+            self.solved + self.failed,
+            self.elapsed
+        )
+    }
+}
+
+/// The files matching `pattern`, a path whose last part may use `*` for any run of characters and `?` for any one,
+/// sorted by name
+///
+/// This is for shells that pass wildcards through unexpanded. A pattern without wildcards, one whose directory can't
+/// be read, or one matching nothing is passed back as it is, so opening it reports the problem
+pub fn expand_glob(pattern: &str) -> Vec<String> {
+    let path = Path::new(pattern);
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return vec![pattern.to_owned()];
+    };
+    if !name.contains(['*', '?']) {
+        return vec![pattern.to_owned()];
+    }
+
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+    let Ok(entries) = fs::read_dir(dir.unwrap_or(Path::new("."))) else {
+        return vec![pattern.to_owned()];
+    };
+    let mut matches = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|file| wildcard_match(name.as_bytes(), file.as_bytes()))
+        .map(|file| match dir {
+            Some(dir) => dir.join(file).to_string_lossy().into_owned(),
+            None => file,
+        })
+        .collect::<Vec<_>>();
+    if matches.is_empty() {
+        return vec![pattern.to_owned()];
+    }
+    matches.sort();
+    matches
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any run of characters and `?` any single byte
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => {
+            wildcard_match(rest, name) || (!name.is_empty() && wildcard_match(pattern, &name[1..]))
+        }
+        (Some((b'?', rest)), Some((_, name_rest))) => wildcard_match(rest, name_rest),
+        (Some((expected, rest)), Some((found, name_rest))) => {
+            expected == found && wildcard_match(rest, name_rest)
+        }
+        _ => false,
+    }
+}
+
+/// The outcome of [`solve_dir`]
+#[derive(Debug, Default)]
+pub struct DirReport {
+    pub stats: BatchStats,
+    /// Every puzzle file, in name order, with whether it was solved or why it couldn't be read or written
+    pub results: Vec<(PathBuf, io::Result<bool>)>,
+    /// Files that were left alone because they don't have a recognized puzzle extension
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Solves every puzzle file directly inside `dir`, writing each solution alongside its puzzle
+///
+/// Files without a recognized puzzle extension are skipped and listed in the report.
+/// `on_progress` is called after every puzzle; puzzles are solved in parallel, so they may finish in any order
+pub fn solve_dir(
+    dir: &Path,
+    on_progress: Option<&(dyn Fn(BatchProgress) + Sync)>,
+) -> io::Result<DirReport> {
+    let start = Instant::now();
+
+    let mut puzzles = Vec::new();
+    let mut skipped = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if PUZZLE_EXTENSIONS.contains(&ext) => puzzles.push(path),
+            // Our own output from a previous run
+            Some(SOLUTION_EXTENSION) => (),
+            _ => skipped.push(path),
+        }
+    }
+    puzzles.sort();
+    skipped.sort();
+
+    let total = puzzles.len();
+    let progress = Mutex::new(BatchStats::default());
+    let results: Vec<(PathBuf, io::Result<bool>)> = puzzles
+        .into_par_iter()
+        .map(|path| {
+            let result = solve_file(&path);
+
+            let mut stats = progress.lock().unwrap();
+            stats.record(&result);
+            stats.elapsed = start.elapsed();
+            if let Some(on_progress) = on_progress {
+                on_progress(BatchProgress {
+                    done: stats.solved + stats.failed,
+                    total,
+                    stats: *stats,
+                });
+            }
+
+            (path, result)
+        })
+        .collect();
+
+    let mut stats = progress.into_inner().unwrap();
+    stats.elapsed = start.elapsed();
+    Ok(DirReport {
+        stats,
+        results,
+        skipped,
+    })
+}
+
+impl BatchStats {
+    /// Counts one more puzzle, which was solved if `result` is `Ok(true)`
+    fn record(&mut self, result: &io::Result<bool>) {
+        match result {
+            Ok(true) => self.solved += 1,
+            _ => self.failed += 1,
+        }
+    }
+}
+
+/// Solves a single puzzle file and writes its solution next to it, returning whether it was solvable
+///
+/// Constraint lines after the grid are followed the same way as for a single puzzle on the command line
+fn solve_file(path: &Path) -> io::Result<bool> {
+    let invalid = |err| io::Error::new(io::ErrorKind::InvalidData, err);
+    let (grid, constraints) = split_constraints(&fs::read_to_string(path)?).map_err(invalid)?;
+    let mut board: Board = grid
+        .parse()
+        .map_err(|err: ParseError| invalid(err.to_string()))?;
+    for constraint in constraints {
+        board.add_constraint(constraint);
+    }
+    let Some(solution) = solve(&board) else {
+        return Ok(false);
+    };
+    fs::write(
+        path.with_extension(SOLUTION_EXTENSION),
+        solution.to_string(),
+    )?;
+    Ok(true)
+}
+
+/// Why a row of the CSV given to [`solve_csv`] didn't check out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvProblem {
+    /// The `puzzle` or `solution` column isn't a valid board
+    Invalid(ParseError),
+    /// The puzzle has no solution
+    Unsolvable,
+    /// The solver's answer differs from the `solution` column
+    Mismatch,
+}
+
+/// A row of the CSV that didn't check out, with its 1-based line number in the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvFailure {
+    pub row: usize,
+    pub problem: CsvProblem,
+}
+
+impl std::fmt::Display for CsvFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.problem {
+            CsvProblem::Invalid(err) => write!(f, "row {}: {err}", self.row),
+            CsvProblem::Unsolvable => write!(f, "row {}: the puzzle has no solution", self.row),
+            CsvProblem::Mismatch => write!(
+                f,
+                "row {}: the solution found doesn't match the expected one",
+                self.row
+            ),
+        }
+    }
+}
+
+/// The outcome of [`solve_csv`]
+#[cfg(feature = "csv")]
+#[derive(Debug, Clone, Default)]
+pub struct CsvReport {
+    pub stats: BatchStats,
+    /// Every row that failed, in file order
+    pub failures: Vec<CsvFailure>,
+}
+
+/// Solves the `puzzle` column of a CSV file, checking each answer against the `solution` column where there is one
+///
+/// The first line must be a header naming the columns. Boards are 81 characters, with `0` or `.` for a blank cell.
+/// Fields may be quoted, and rows may leave out trailing fields
+#[cfg(feature = "csv")]
+pub fn solve_csv(path: &Path) -> io::Result<CsvReport> {
+    let start = Instant::now();
+    let contents = fs::read(path)?;
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(&contents[..]);
+
+    let header = reader.headers()?.clone();
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|field| field.eq_ignore_ascii_case(name))
+    };
+    let Some(puzzle_column) = column("puzzle") else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the CSV header has no `puzzle` column",
+        ));
+    };
+    let solution_column = column("solution");
+
+    // The reader's own line numbers don't count the blank lines it skips, so they're worked out from byte offsets,
+    // which point at the start of any blank lines ahead of the record
+    let (mut line, mut counted_to) = (1, 0);
+    let rows = reader
+        .records()
+        .map(|record| {
+            let record = record?;
+            let mut byte = record
+                .position()
+                .map_or(0, |position| position.byte() as usize);
+            while matches!(contents.get(byte), Some(b'\n' | b'\r')) {
+                byte += 1;
+            }
+            line += contents[counted_to..byte]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count();
+            counted_to = byte;
+            Ok((line, record))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    let results: Vec<(usize, Result<(), CsvProblem>)> = rows
+        .into_par_iter()
+        .map(|(row, record)| {
+            let fields = record.iter().collect::<Vec<_>>();
+            let result = check_csv_row(puzzle_column, solution_column, &fields);
+            (row, result)
+        })
+        .collect();
+
+    let mut report = CsvReport::default();
+    for (row, result) in results {
+        match result {
+            Ok(()) => report.stats.solved += 1,
+            Err(problem) => {
+                report.stats.failed += 1;
+                report.failures.push(CsvFailure { row, problem });
+            }
+        }
+    }
+    report.stats.elapsed = start.elapsed();
+    Ok(report)
+}
+
+/// Solves the puzzle in `fields` and compares it against the expected solution, if there's a column for it
+#[cfg(feature = "csv")]
+fn check_csv_row(
+    puzzle_column: usize,
+    solution_column: Option<usize>,
+    fields: &[&str],
+) -> Result<(), CsvProblem> {
+    let field = |column: usize| fields.get(column).copied().unwrap_or_default();
+    let puzzle = board_from_csv_field(field(puzzle_column)).map_err(CsvProblem::Invalid)?;
+    let solution = solve(&puzzle).ok_or(CsvProblem::Unsolvable)?;
+
+    // Rows may leave the solution blank even when the column exists
+    if let Some(expected) = solution_column
+        .map(field)
+        .filter(|expected| !expected.is_empty())
+    {
+        let expected = board_from_csv_field(expected).map_err(CsvProblem::Invalid)?;
+        if expected.to_string() != solution.to_string() {
+            return Err(CsvProblem::Mismatch);
+        }
+    }
+    Ok(())
+}
+
+/// Reads an 81-character board as written in CSV datasets, mapping `.` to `0` so either can mark a blank
+fn board_from_csv_field(field: &str) -> Result<Board, ParseError> {
+    let values = field
+        .chars()
+        .map(|c| match c {
+            '.' => 0,
+            // Anything else that isn't a digit becomes an out-of-range value for `from_values` to report
+            c => c.to_digit(10).map_or(u8::MAX, |digit| digit as u8),
+        })
+        .collect::<Vec<_>>();
+    Board::from_values(&values)
+}
+
+/// What [`solve_lines`] writes for each puzzle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineFormat {
+    /// Just the solution, as 81 digits
+    #[default]
+    Solution,
+    /// The solution, a comma, then the puzzle line exactly as it was read
+    SolutionAndPuzzle,
+}
+
+/// The outcome of [`solve_lines`]
+#[derive(Debug, Clone, Default)]
+pub struct LinesReport {
+    /// One line per input line, in the same order
+    pub output: String,
+    pub stats: BatchStats,
+    /// Every line that couldn't be read or solved, in dataset order
+    pub failures: Vec<CsvFailure>,
+}
+
+/// Solves a dataset with one 81-character puzzle per line, using `0` or `.` for blanks
+///
+/// The output has one line per input line, in the same order, so it can stand in for the dataset.
+/// A puzzle that can't be read or solved gets an empty solution, and its line number goes in the report's failures.
+/// Blank input lines are passed through as blank lines
+pub fn solve_lines(dataset: &str, format: LineFormat) -> LinesReport {
+    let start = Instant::now();
+    let lines: Vec<&str> = dataset.lines().collect();
+    let solutions: Vec<Option<Result<Board, CsvProblem>>> = lines
+        .par_iter()
+        .map(|line| {
+            let puzzle = line.trim();
+            (!puzzle.is_empty()).then(|| {
+                let board = board_from_csv_field(puzzle).map_err(CsvProblem::Invalid)?;
+                solve(&board).ok_or(CsvProblem::Unsolvable)
+            })
+        })
+        .collect();
+
+    let mut report = LinesReport::default();
+    for (idx, (line, solution)) in lines.iter().zip(solutions).enumerate() {
+        let solution = match solution {
+            None => {
+                report.output.push('\n');
+                continue;
+            }
+            Some(Ok(solution)) => {
+                report.stats.solved += 1;
+                solution.to_line()
+            }
+            Some(Err(problem)) => {
+                report.stats.failed += 1;
+                report.failures.push(CsvFailure {
+                    row: idx + 1,
+                    problem,
+                });
+                String::new()
+            }
+        };
+        report.output.push_str(&solution);
+        if format == LineFormat::SolutionAndPuzzle {
+            report.output.push(',');
+            report.output.push_str(line.trim());
+        }
+        report.output.push('\n');
+    }
+    report.stats.elapsed = start.elapsed();
+    report
+}
+
+/// How the puzzles of a dataset split up by number of solutions, from [`solution_count_distribution`]
+///
+/// Puzzles are listed by their 1-based line number in the dataset
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SolutionCounts {
+    /// Lines that aren't a valid board
+    pub invalid: Vec<usize>,
+    /// Puzzles with no solution
+    pub unsolvable: Vec<usize>,
+    /// How many puzzles have exactly one solution
+    pub proper: usize,
+    /// Puzzles with more than one solution
+    pub ambiguous: Vec<usize>,
+}
+
+impl std::fmt::Display for SolutionCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} proper, {} ambiguous, {} unsolvable",
+            self.proper,
+            self.ambiguous.len(),
+            self.unsolvable.len()
+        )?;
+        if !self.invalid.is_empty() {
+            write!(f, ", {} invalid", self.invalid.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// Sorts the puzzles of a dataset like [`solve_lines`] takes by how many solutions each has: none, one, or more
+///
+/// Each puzzle is only searched until a second solution turns up, so ambiguous ones don't take long.
+/// Blank lines are skipped
+pub fn solution_count_distribution(dataset: &str) -> SolutionCounts {
+    let solver = Solver::new();
+    let counts: Vec<(usize, Option<usize>)> = dataset
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|(idx, line)| {
+            let count = board_from_csv_field(line.trim())
+                .ok()
+                .map(|board| solver.solutions_up_to(board, 2).len());
+            (idx + 1, count)
+        })
+        .collect();
+
+    let mut distribution = SolutionCounts::default();
+    for (line, count) in counts {
+        match count {
+            None => distribution.invalid.push(line),
+            Some(0) => distribution.unsolvable.push(line),
+            Some(1) => distribution.proper += 1,
+            Some(_) => distribution.ambiguous.push(line),
+        }
+    }
+    distribution
+}
+
+/// Why [`parse_exchange_bank`] couldn't read a line of a puzzle bank
+#[derive(Debug, Clone, PartialEq)]
+pub enum BankProblem {
+    /// The line doesn't have exactly an id, a puzzle, and a rating
+    FieldCount(usize),
+    /// The puzzle isn't a valid board
+    Invalid(ParseError),
+    /// The rating isn't a number
+    Rating(String),
+}
+
+impl std::fmt::Display for BankProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BankProblem::FieldCount(found) => write!(
+                f,
+                "expected an id, a puzzle, and a rating but found {found} fields"
+            ),
+            BankProblem::Invalid(err) => write!(f, "{err}"),
+            BankProblem::Rating(rating) => write!(f, "'{rating}' isn't a difficulty rating"),
+        }
+    }
+}
+
+/// A line of a puzzle bank that [`parse_exchange_bank`] skipped, with its 1-based line number
+#[derive(Debug, Clone, PartialEq)]
+pub struct BankSkip {
+    pub line: usize,
+    pub problem: BankProblem,
+}
+
+impl std::fmt::Display for BankSkip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.problem)
+    }
+}
+
+/// The outcome of [`parse_exchange_bank`]
+#[derive(Debug, Clone, Default)]
+pub struct BankReport {
+    /// Each puzzle's id, board, and difficulty rating, in bank order
+    pub puzzles: Vec<(String, Board, f32)>,
+    /// Every malformed line, in bank order
+    pub skipped: Vec<BankSkip>,
+}
+
+/// Reads a puzzle bank in the Sudoku Exchange format, one `<id> <81-char puzzle> <difficulty rating>` per line
+///
+/// Puzzles use `0` or `.` for blanks. Malformed lines are skipped and listed in the report with their line number,
+/// and blank lines are ignored
+pub fn parse_exchange_bank(bank: &str) -> BankReport {
+    let mut report = BankReport::default();
+    let lines = bank.lines().enumerate();
+    for (idx, line) in lines.filter(|(_, line)| !line.trim().is_empty()) {
+        match parse_bank_line(line) {
+            Ok(entry) => report.puzzles.push(entry),
+            Err(problem) => report.skipped.push(BankSkip {
+                line: idx + 1,
+                problem,
+            }),
+        }
+    }
+    report
+}
+
+fn parse_bank_line(line: &str) -> Result<(String, Board, f32), BankProblem> {
+    let fields = line.split_whitespace().collect::<Vec<_>>();
+    let &[id, puzzle, rating] = fields.as_slice() else {
+        return Err(BankProblem::FieldCount(fields.len()));
+    };
+    let board = board_from_csv_field(puzzle).map_err(BankProblem::Invalid)?;
+    let rating = rating
+        .parse()
+        .map_err(|_| BankProblem::Rating(rating.to_owned()))?;
+    Ok((id.to_owned(), board, rating))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh, empty directory under the system's temp dir, unique to `name` and this process
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("crossword_solver-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn solve_dir_reports_each_file() {
+        let dir = temp_dir("solve_dir");
+        fs::write(dir.join("easy.txt"), include_str!("../examples/easy1.txt")).unwrap();
+        fs::write(dir.join("broken.sdk"), "not a puzzle").unwrap();
+        fs::write(dir.join("notes.md"), "").unwrap();
+
+        let report = solve_dir(&dir, None).unwrap();
+        assert_eq!(report.skipped, vec![dir.join("notes.md")]);
+        assert_eq!(report.stats.solved, 1);
+        assert_eq!(report.stats.failed, 1);
+        let [(broken, broken_result), (easy, easy_result)] = &report.results[..] else {
+            panic!("expected two results, got {:?}", report.results);
+        };
+        assert_eq!(broken, &dir.join("broken.sdk"));
+        assert!(broken_result.is_err());
+        assert_eq!(easy, &dir.join("easy.txt"));
+        assert!(matches!(easy_result, Ok(true)));
+        assert!(dir.join("easy.solution").is_file());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn solve_dir_follows_constraint_lines() {
+        let dir = temp_dir("solve_dir_constraints");
+        fs::write(
+            dir.join("killer.txt"),
+            include_str!("../examples/killer1.txt"),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("bad_cage.txt"),
+            "x".repeat(81) + "\ncage: 2 R1C1 R1C2\n",
+        )
+        .unwrap();
+
+        let report = solve_dir(&dir, None).unwrap();
+        assert_eq!(report.stats.solved, 1);
+        let [(bad, bad_result), (_, killer_result)] = &report.results[..] else {
+            panic!("expected two results, got {:?}", report.results);
+        };
+        assert_eq!(bad, &dir.join("bad_cage.txt"));
+        assert_eq!(
+            bad_result.as_ref().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert!(matches!(killer_result, Ok(true)));
+
+        let (grid, constraints) =
+            split_constraints(include_str!("../examples/killer1.txt")).unwrap();
+        let mut solution: Board = fs::read_to_string(dir.join("killer.solution"))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(grid.parse::<Board>().unwrap().is_blank());
+        for constraint in constraints {
+            solution.add_constraint(constraint);
+        }
+        assert!(solution.is_solved());
+        assert!(solution.verify_constraints().is_ok());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn solve_csv_reads_quoted_fields_and_reports_rows() {
+        let puzzle: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let solved = solve(&puzzle).unwrap();
+        let solution = solved.to_line();
+        // A valid grid, just not this puzzle's
+        let wrong = solved.relabel(&[2, 1, 3, 4, 5, 6, 7, 8, 9]).to_line();
+        let puzzle = puzzle.to_sudoku_line();
+
+        let dir = temp_dir("solve_csv");
+        let path = dir.join("puzzles.csv");
+        fs::write(
+            &path,
+            format!(
+                "name,puzzle,solution\n\
+                 \"Easy, with a comma\",\"{puzzle}\",{solution}\n\
+                 \n\
+                 Wrong answer,{puzzle},{wrong}\n\
+                 No answer,{puzzle}\n\
+                 Broken,not a puzzle,\n"
+            ),
+        )
+        .unwrap();
+        let report = solve_csv(&path).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(report.stats.solved, 2);
+        assert_eq!(report.stats.failed, 2);
+        assert_eq!(
+            report.failures[0],
+            CsvFailure {
+                row: 4,
+                problem: CsvProblem::Mismatch
+            }
+        );
+        assert_eq!(report.failures[1].row, 6);
+        assert!(matches!(report.failures[1].problem, CsvProblem::Invalid(_)));
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn solve_csv_needs_a_puzzle_column() {
+        let dir = temp_dir("solve_csv_header");
+        let path = dir.join("puzzles.csv");
+        fs::write(&path, "board,solution\n").unwrap();
+        let err = solve_csv(&path).unwrap_err();
+        fs::remove_dir_all(dir).unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn solve_lines_keeps_the_dataset_order() {
+        let puzzle: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let solution = solve(&puzzle).unwrap().to_line();
+        let puzzle = puzzle.to_sudoku_line();
+        let dataset = format!("{puzzle}\n\nnot a puzzle\n{}\n", puzzle.replace('.', "0"));
+
+        let report = solve_lines(&dataset, LineFormat::Solution);
+        assert_eq!(report.output, format!("{solution}\n\n\n{solution}\n"));
+        assert_eq!(report.stats.solved, 2);
+        assert_eq!(report.stats.failed, 1);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].row, 3);
+
+        let report = solve_lines(&puzzle, LineFormat::SolutionAndPuzzle);
+        assert_eq!(report.output, format!("{solution},{puzzle}\n"));
+    }
+
+    #[test]
+    fn parse_exchange_bank_reads_sample_lines() {
+        let easy = include_str!("../examples/easy1.txt")
+            .parse::<Board>()
+            .unwrap()
+            .to_sudoku_line();
+        let bank = format!(
+            "0000183b305c {} 1.2\n\n00001cc1e3c6 {} 4.5\n",
+            easy.replace('.', "0"),
+            easy
+        );
+        let report = parse_exchange_bank(&bank);
+        assert_eq!(report.skipped, []);
+        let [
+            (first_id, first, first_rating),
+            (second_id, second, second_rating),
+        ] = &report.puzzles[..]
+        else {
+            panic!("expected two puzzles, got {:?}", report.puzzles);
+        };
+        assert_eq!((first_id.as_str(), *first_rating), ("0000183b305c", 1.2));
+        assert_eq!((second_id.as_str(), *second_rating), ("00001cc1e3c6", 4.5));
+        assert_eq!(first.to_sudoku_line(), easy);
+        assert_eq!(second.to_sudoku_line(), easy);
+    }
+
+    #[test]
+    fn parse_exchange_bank_skips_malformed_lines() {
+        let easy = include_str!("../examples/easy1.txt")
+            .parse::<Board>()
+            .unwrap()
+            .to_sudoku_line();
+        let bank = format!(
+            "a {easy} 1.0\nb {easy}\nc {} 2.0\n\nd {easy} hard\n",
+            &easy[..80]
+        );
+        let report = parse_exchange_bank(&bank);
+        assert_eq!(report.puzzles.len(), 1);
+        assert_eq!(report.puzzles[0].0, "a");
+        let skipped = report
+            .skipped
+            .iter()
+            .map(|skip| skip.line)
+            .collect::<Vec<_>>();
+        assert_eq!(skipped, [2, 3, 5]);
+        assert_eq!(report.skipped[0].problem, BankProblem::FieldCount(2));
+        assert!(matches!(report.skipped[1].problem, BankProblem::Invalid(_)));
+        assert_eq!(
+            report.skipped[2].to_string(),
+            "line 5: 'hard' isn't a difficulty rating"
+        );
+    }
+}
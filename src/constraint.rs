@@ -0,0 +1,642 @@
+//! Extra rules for Sudoku variants, on top of the usual rows, columns, and squares
+
+use itertools::Itertools;
+
+use crate::{Board, BoardIdx, Cause};
+
+/// A variant rule a board can follow in addition to the classic ones
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Constraint {
+    /// Cells a chess knight's move apart can't hold the same number
+    AntiKnight,
+    /// Numbers strictly increase along the path, starting from the bulb in the first cell
+    ///
+    /// Each cell must be a king's move from the one before it
+    Thermometer(Vec<BoardIdx>),
+    /// Windoku: the four 3x3 windows from [`Board::windows`] need every number once, just like the squares
+    Windows,
+    /// X-Sudoku: both main diagonals from [`Board::diagonals`] need every number once
+    Diagonals,
+    /// Killer Sudoku: the cage's cells hold different numbers that add up to its sum
+    Cage(Cage),
+}
+
+/// A group of cells in a Killer Sudoku, which can't repeat a number and have to add up to `sum`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cage {
+    pub cells: Vec<BoardIdx>,
+    pub sum: u32,
+}
+
+impl Cage {
+    /// Whether `sum` can be made from as many different numbers as the cage has cells, which all cells being
+    /// different also needs
+    fn is_possible(&self) -> bool {
+        let len = self.cells.len() as u32;
+        if !(1..=9).contains(&len) || !self.cells.iter().all_unique() {
+            return false;
+        }
+        (len * (len + 1) / 2..=len * (19 - len) / 2).contains(&self.sum)
+    }
+}
+
+impl Constraint {
+    /// Reads a thermometer written as `thermo:` followed by its cells from the bulb on, like `thermo: R1C1 R2C2 R3C2`
+    ///
+    /// Returns `None` if the line isn't a thermometer, or its cells aren't a connected path
+    pub fn parse_thermometer(line: &str) -> Option<Constraint> {
+        let cells = line.trim().strip_prefix("thermo:")?;
+        let path = cells
+            .split_whitespace()
+            .map(parse_cell)
+            .collect::<Option<Vec<_>>>()?;
+        let connected = path.windows(2).all(|pair| {
+            pair[0] != pair[1]
+                && pair[0].col.abs_diff(pair[1].col) <= 1
+                && pair[0].row.abs_diff(pair[1].row) <= 1
+        });
+        (!path.is_empty() && path.len() <= 9 && connected).then_some(Constraint::Thermometer(path))
+    }
+
+    /// Reads a cage written as `cage:` followed by its sum and then its cells, like `cage: 15 R1C1 R1C2 R2C1`
+    ///
+    /// Returns `None` if the line isn't a cage, it repeats a cell or has more than nine, or no different numbers
+    /// in that many cells add up to its sum
+    pub fn parse_cage(line: &str) -> Option<Constraint> {
+        let mut words = line.trim().strip_prefix("cage:")?.split_whitespace();
+        let sum = words.next()?.parse().ok()?;
+        let cells = words.map(parse_cell).collect::<Option<Vec<_>>>()?;
+        let cage = Cage { cells, sum };
+        cage.is_possible().then_some(Constraint::Cage(cage))
+    }
+}
+
+/// Whether the cells of a cage after the first `used.count_ones()` can take different numbers from `candidates`,
+/// none of them in `used`, to bring the total of `used` up to `sum`, with the answers so far in `finishes`
+fn can_finish(used: u16, candidates: &[u16], sum: u32, finishes: &mut [Option<bool>]) -> bool {
+    if let Some(known) = finishes[used as usize] {
+        return known;
+    }
+    let so_far = digit_sum(used);
+    let known = match candidates.get(used.count_ones() as usize) {
+        _ if so_far > sum => false,
+        None => so_far == sum,
+        Some(&cell) => (1..=9).any(|num| {
+            let bit = 1 << num;
+            cell & bit != 0 && used & bit == 0 && can_finish(used | bit, candidates, sum, finishes)
+        }),
+    };
+    finishes[used as usize] = Some(known);
+    known
+}
+
+/// The numbers whose bits are set in `used`, added up
+fn digit_sum(used: u16) -> u32 {
+    (1..=9).filter(|num| used & (1 << num) != 0).sum()
+}
+
+/// A cell written like `R3C7`, for row 3 and column 7, in either case
+fn parse_cell(cell: &str) -> Option<BoardIdx> {
+    let cell = cell.to_ascii_uppercase();
+    let (row, col) = cell.strip_prefix('R')?.split_once('C')?;
+    let (row, col) = (row.parse::<usize>().ok()?, col.parse::<usize>().ok()?);
+    ((1..=9).contains(&row) && (1..=9).contains(&col)).then(|| BoardIdx::new(col - 1, row - 1))
+}
+
+/// Which constraints a board follows, laid out for drawing them, from [`Board::constraints_summary`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConstraintSummary {
+    pub anti_knight: bool,
+    /// Each thermometer's cells, bulb first
+    pub thermometers: Vec<Vec<BoardIdx>>,
+    /// Extra regions that need every number once, like the Windoku windows or the X-Sudoku diagonals
+    pub regions: Vec<[BoardIdx; 9]>,
+    pub cages: Vec<Cage>,
+}
+
+/// Every (column, row) step a knight can make
+const KNIGHT_MOVES: [(isize, isize); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+/// Splits the constraint lines off a puzzle file, returning the rest of the file along with the constraints
+///
+/// A constraint line is `anti-knight`, `windows`, `diagonals`, a thermometer for [`Constraint::parse_thermometer`],
+/// or a cage for [`Constraint::parse_cage`]. Errors with the offending line if a thermometer or cage is invalid
+pub fn split_constraints(board_str: &str) -> Result<(String, Vec<Constraint>), String> {
+    let mut grid = String::new();
+    let mut constraints = Vec::new();
+    for line in board_str.lines() {
+        match line.trim() {
+            "anti-knight" => constraints.push(Constraint::AntiKnight),
+            "windows" => constraints.push(Constraint::Windows),
+            "diagonals" => constraints.push(Constraint::Diagonals),
+            thermo if thermo.starts_with("thermo:") => {
+                match Constraint::parse_thermometer(thermo) {
+                    Some(thermometer) => constraints.push(thermometer),
+                    None => return Err(format!("invalid thermometer '{thermo}'")),
+                }
+            }
+            cage if cage.starts_with("cage:") => match Constraint::parse_cage(cage) {
+                Some(cage) => constraints.push(cage),
+                None => return Err(format!("invalid cage '{cage}'")),
+            },
+            _ => {
+                grid.push_str(line);
+                grid.push('\n');
+            }
+        }
+    }
+    Ok((grid, constraints))
+}
+
+impl Board {
+    /// Makes the board follow `constraint` too, removing candidates that the numbers already played now rule out
+    ///
+    /// If those numbers break the new rule, the cells holding them are left with no candidates, so
+    /// [`Board::verify_detailed`] reports it and a solve finds nothing
+    pub fn add_constraint(&mut self, constraint: Constraint) {
+        if self.constraints.contains(&constraint) {
+            return;
+        }
+        // Read every number first, since propagating the new rule can empty the played cells that break it
+        let played = self
+            .played
+            .iter()
+            .filter_map(|&idx| match self.get(idx).possibilities()[..] {
+                [num] => Some((idx, num)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        self.constraints.push(constraint);
+        self.propagate_constraints();
+
+        for (idx, num) in played {
+            // Replaying a number the rule took away would hide the contradiction
+            if self.get(idx).is_possible(num) {
+                self.play_cell_among(idx, num, &[]);
+            }
+        }
+    }
+
+    /// The four Windoku windows, each with its top left cell one in from a corner of the board's squares
+    pub fn windows(&self) -> [[BoardIdx; 9]; 4] {
+        [(1, 1), (5, 1), (1, 5), (5, 5)].map(|(left, top)| {
+            std::array::from_fn(|idx| BoardIdx::new(left + idx % 3, top + idx / 3))
+        })
+    }
+
+    /// The window from [`Board::windows`] holding `idx`, if any
+    pub fn window_of(&self, idx: BoardIdx) -> Option<[BoardIdx; 9]> {
+        self.windows()
+            .into_iter()
+            .find(|window| window.contains(&idx))
+    }
+
+    /// The two main diagonals, from the top left corner and then from the top right, each going down
+    pub fn diagonals(&self) -> [[BoardIdx; 9]; 2] {
+        [
+            std::array::from_fn(|idx| BoardIdx::new(idx, idx)),
+            std::array::from_fn(|idx| BoardIdx::new(8 - idx, idx)),
+        ]
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    /// The board's constraints gathered by kind, empty for a classic board
+    pub fn constraints_summary(&self) -> ConstraintSummary {
+        let mut summary = ConstraintSummary::default();
+        for constraint in &self.constraints {
+            match constraint {
+                Constraint::AntiKnight => summary.anti_knight = true,
+                Constraint::Thermometer(path) => summary.thermometers.push(path.clone()),
+                Constraint::Windows => summary.regions.extend(self.windows()),
+                Constraint::Diagonals => summary.regions.extend(self.diagonals()),
+                Constraint::Cage(cage) => summary.cages.push(cage.clone()),
+            }
+        }
+        summary
+    }
+
+    /// The cells a knight's move away from `idx`, fewer than eight near the edges
+    pub fn knight_peers(&self, idx: BoardIdx) -> Vec<BoardIdx> {
+        KNIGHT_MOVES
+            .iter()
+            .filter_map(|&(col, row)| {
+                let col = idx.col.checked_add_signed(col).filter(|&col| col < 9)?;
+                let row = idx.row.checked_add_signed(row).filter(|&row| row < 9)?;
+                Some(BoardIdx::new(col, row))
+            })
+            .collect()
+    }
+
+    /// Cells that can't share a number with `idx` because of the board's constraints, beyond its usual peers
+    ///
+    /// Empty, without allocating, for a classic board
+    pub fn constraint_peers(&self, idx: BoardIdx) -> Vec<BoardIdx> {
+        let mut peers = Vec::new();
+        for constraint in &self.constraints {
+            match constraint {
+                Constraint::AntiKnight => peers.extend(self.knight_peers(idx)),
+                // Equal numbers are already ruled out by the bounds from `propagate_constraints`
+                Constraint::Thermometer(_) => (),
+                Constraint::Windows => peers.extend(
+                    self.window_of(idx)
+                        .into_iter()
+                        .flatten()
+                        .filter(|&peer| peer != idx),
+                ),
+                // The middle cell is on both diagonals
+                Constraint::Diagonals => peers.extend(
+                    self.diagonals()
+                        .into_iter()
+                        .filter(|diagonal| diagonal.contains(&idx))
+                        .flatten()
+                        .filter(|&peer| peer != idx),
+                ),
+                Constraint::Cage(cage) if cage.cells.contains(&idx) => {
+                    peers.extend(cage.cells.iter().copied().filter(|&peer| peer != idx))
+                }
+                Constraint::Cage(_) => (),
+            }
+        }
+        peers
+    }
+
+    /// Which numbers each cell of `cage` can still hold, as bits like [`crate::Cell::important_bits`], in the cage's order
+    ///
+    /// A number is kept only if the rest of the cage can be filled in from its candidates with different numbers
+    /// that make up the sum. Each set of numbers used so far is only looked at once, so this stays quick however
+    /// many orders the cells could be filled in
+    fn cage_candidates(&self, cage: &Cage) -> Vec<u16> {
+        let candidates: Vec<u16> = cage
+            .cells
+            .iter()
+            .map(|&idx| self.get(idx).important_bits())
+            .collect();
+        // Whether the cells after those numbered in `used` can be finished off, by `used`
+        let mut finishes = vec![None; 1 << 10];
+        let mut kept = vec![0; candidates.len()];
+        let mut reached = vec![0u16];
+        for (pos, &cell) in candidates.iter().enumerate() {
+            let mut next = Vec::new();
+            for &used in &reached {
+                for num in 1..=9 {
+                    let bit = 1 << num;
+                    if cell & bit != 0
+                        && used & bit == 0
+                        && can_finish(used | bit, &candidates, cage.sum, &mut finishes)
+                    {
+                        kept[pos] |= bit;
+                        next.push(used | bit);
+                    }
+                }
+            }
+            next.sort_unstable();
+            next.dedup();
+            reached = next;
+        }
+        kept
+    }
+
+    /// Whether the numbers placed in `cage` leave room for its sum: not past it, and with enough left over for the
+    /// empty cells, however they're filled
+    fn cage_fits(&self, cage: &Cage) -> bool {
+        let mut placed = 0;
+        let mut used = 0u16;
+        let mut empty = 0;
+        for &idx in &cage.cells {
+            match self.get(idx).possibilities()[..] {
+                [num] => {
+                    placed += u32::from(num);
+                    used |= 1 << num;
+                }
+                _ => empty += 1,
+            }
+        }
+        let unused: Vec<u32> = (1..=9).filter(|num| used & (1 << num) == 0).collect();
+        let (Some(low), Some(high)) = (
+            unused.get(..empty).map(|low| low.iter().sum::<u32>()),
+            unused
+                .get(unused.len().saturating_sub(empty)..)
+                .map(|high| high.iter().sum::<u32>()),
+        ) else {
+            return false;
+        };
+        (placed + low..=placed + high).contains(&cage.sum)
+    }
+
+    /// Removes candidates ruled out by constraints that depend on more than a single played cell, until none are left
+    ///
+    /// On a thermometer each cell has to be above the smallest candidate before it and below the largest one after it.
+    /// In a cage each candidate has to leave a way to make up the sum with the others.
+    /// Returns whether anything was removed
+    pub fn propagate_constraints(&mut self) -> bool {
+        let thermometers = self
+            .constraints
+            .iter()
+            .filter_map(|constraint| match constraint {
+                Constraint::Thermometer(path) => Some(path.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let cages = self
+            .constraints
+            .iter()
+            .filter_map(|constraint| match constraint {
+                Constraint::Cage(cage) => Some(cage.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let mut cage_states = vec![None; cages.len()];
+
+        let mut changed = false;
+        loop {
+            let mut changed_now = false;
+            for path in &thermometers {
+                let mut low = 0;
+                for &idx in path {
+                    for num in 1..=low.min(9) {
+                        changed_now |= self.eliminate_candidate(idx, num, Cause::Thermometer);
+                    }
+                    low = self.get(idx).possibilities().first().copied().unwrap_or(9);
+                }
+                let mut high = 10;
+                for &idx in path.iter().rev() {
+                    for num in high.max(1)..=9 {
+                        changed_now |= self.eliminate_candidate(idx, num, Cause::Thermometer);
+                    }
+                    high = self.get(idx).possibilities().last().copied().unwrap_or(1);
+                }
+            }
+            for (cage, last) in cages.iter().zip(&mut cage_states) {
+                // A cage whose candidates are as they were at its last pass has nothing new to remove
+                let state: Vec<u16> = cage
+                    .cells
+                    .iter()
+                    .map(|&idx| self.get(idx).important_bits())
+                    .collect();
+                if last.as_ref() == Some(&state) {
+                    continue;
+                }
+                let kept = self.cage_candidates(cage);
+                *last = Some(kept.clone());
+                for (&idx, kept) in cage.cells.iter().zip(kept) {
+                    for num in 1..=9 {
+                        if kept & (1 << num) == 0 {
+                            changed_now |= self.eliminate_candidate(idx, num, Cause::Cage);
+                        }
+                    }
+                }
+            }
+            if !changed_now {
+                return changed;
+            }
+            changed = true;
+        }
+    }
+
+    /// Whether the numbers already placed break one of the board's constraints
+    ///
+    /// That's two cells related by a constraint down to the same single number, a window or diagonal with a repeated number,
+    /// two numbers on a thermometer too close together to fit the cells between them, or a cage whose numbers can no
+    /// longer add up to its sum
+    pub(crate) fn verify_constraints(&self) -> Result<(), ()> {
+        if self.constraints.is_empty() {
+            return Ok(());
+        }
+        if self.constraints.contains(&Constraint::Windows) {
+            self.verify_units(&self.windows())?;
+        }
+        if self.constraints.contains(&Constraint::Diagonals) {
+            self.verify_units(&self.diagonals())?;
+        }
+        for constraint in &self.constraints {
+            if let Constraint::Cage(cage) = constraint
+                && !self.cage_fits(cage)
+            {
+                return Err(());
+            }
+            let Constraint::Thermometer(path) = constraint else {
+                continue;
+            };
+            let placed = path
+                .iter()
+                .enumerate()
+                .filter_map(|(pos, &idx)| match self.get(idx).possibilities()[..] {
+                    [num] => Some((pos, num as usize)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let fits = placed
+                .windows(2)
+                .all(|pair| pair[1].1 >= pair[0].1 + (pair[1].0 - pair[0].0));
+            if !fits {
+                return Err(());
+            }
+        }
+        for (col, row) in itertools::iproduct!(0..9, 0..9) {
+            let idx = BoardIdx::new(col, row);
+            let [num] = self.get(idx).possibilities()[..] else {
+                continue;
+            };
+            let clashes = self
+                .constraint_peers(idx)
+                .into_iter()
+                .any(|peer| self.get(peer).possibilities()[..] == [num]);
+            if clashes {
+                return Err(());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Solver;
+
+    /// A puzzle file with constraint lines, read like the command line reads it
+    fn load(file: &str) -> Board {
+        let (grid, constraints) = split_constraints(file).unwrap();
+        let mut board: Board = grid.parse().unwrap();
+        for constraint in constraints {
+            board.add_constraint(constraint);
+        }
+        board
+    }
+
+    #[test]
+    fn thermometer_bounds_each_cell_by_its_place() {
+        let path = vec![
+            BoardIdx::new(0, 0),
+            BoardIdx::new(1, 0),
+            BoardIdx::new(2, 1),
+        ];
+        let mut board = Board::new();
+        board.add_constraint(Constraint::Thermometer(path.clone()));
+        assert_eq!(board.candidates(path[0]), (1..=7).collect::<Vec<_>>());
+        assert_eq!(board.candidates(path[1]), (2..=8).collect::<Vec<_>>());
+        assert_eq!(board.candidates(path[2]), (3..=9).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn thermometer_bounds_follow_played_numbers() {
+        let path = vec![
+            BoardIdx::new(0, 0),
+            BoardIdx::new(1, 0),
+            BoardIdx::new(2, 0),
+        ];
+        let mut board = Board::new();
+        board.play_cell(path[1], 4);
+        board.add_constraint(Constraint::Thermometer(path.clone()));
+        assert_eq!(board.candidates(path[0]), vec![1, 2, 3]);
+        assert_eq!(board.candidates(path[1]), vec![4]);
+        assert_eq!(board.candidates(path[2]), vec![5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn thermo_example_solves_within_its_thermometers() {
+        let board = load(include_str!("../examples/thermo1.txt"));
+        let solution = Solver::new().solve(board).unwrap();
+        assert!(solution.is_solved());
+        assert!(solution.verify_constraints().is_ok());
+    }
+
+    #[test]
+    fn thermometer_broken_by_the_givens_is_a_contradiction() {
+        let mut board: Board = format!("53{}", "x".repeat(79)).parse().unwrap();
+        board.add_constraint(Constraint::Thermometer(vec![
+            BoardIdx::new(0, 0),
+            BoardIdx::new(1, 0),
+        ]));
+        assert!(board.verify_detailed().is_err());
+        assert_eq!(Solver::new().solve(board), None);
+    }
+
+    #[test]
+    fn parse_thermometer_needs_a_connected_path() {
+        assert_eq!(
+            Constraint::parse_thermometer("thermo: R1C1 r2c2"),
+            Some(Constraint::Thermometer(vec![
+                BoardIdx::new(0, 0),
+                BoardIdx::new(1, 1),
+            ]))
+        );
+        assert_eq!(Constraint::parse_thermometer("thermo: R1C1 R1C3"), None);
+        assert_eq!(Constraint::parse_thermometer("thermo: R1C1 R1C1"), None);
+        assert_eq!(Constraint::parse_thermometer("thermo:"), None);
+    }
+
+    #[test]
+    fn windows_are_four_offset_squares() {
+        let windows = Board::new().windows();
+        assert_eq!(windows[0][0], BoardIdx::new(1, 1));
+        assert_eq!(windows[3][8], BoardIdx::new(7, 7));
+        assert!(windows.iter().flatten().all_unique());
+        assert_eq!(Board::new().window_of(BoardIdx::new(4, 4)), None);
+        assert_eq!(
+            Board::new().window_of(BoardIdx::new(6, 2)),
+            Some(windows[1])
+        );
+    }
+
+    #[test]
+    fn playing_in_a_window_clears_the_rest_of_it() {
+        let mut board = Board::new();
+        board.add_constraint(Constraint::Windows);
+        board.play_cell(BoardIdx::new(1, 1), 4);
+        // Same window, but no row, column, or square in common
+        assert!(!board.get(BoardIdx::new(3, 3)).is_possible(4));
+        assert!(board.get(BoardIdx::new(4, 4)).is_possible(4));
+    }
+
+    #[test]
+    fn windoku_example_solves_within_the_windows() {
+        let board = load(include_str!("../examples/windoku1.txt"));
+        let solution = Solver::new().solve(board).unwrap();
+        assert!(solution.is_solved());
+        assert!(solution.verify_constraints().is_ok());
+        for window in solution.windows() {
+            assert!(
+                window
+                    .iter()
+                    .map(|&idx| solution.played_num(idx))
+                    .all_unique()
+            );
+        }
+    }
+
+    #[test]
+    fn a_repeat_in_a_window_fails_verification() {
+        let mut board = Board::new();
+        board.add_constraint(Constraint::Windows);
+        board.set_raw(BoardIdx::new(1, 1), crate::Cell::fixed(4));
+        board.set_raw(BoardIdx::new(3, 3), crate::Cell::fixed(4));
+        assert!(board.verify_constraints().is_err());
+    }
+
+    #[test]
+    fn cage_keeps_only_numbers_that_can_make_its_sum() {
+        let cells = vec![BoardIdx::new(0, 0), BoardIdx::new(1, 0)];
+        let mut board = Board::new();
+        board.add_constraint(Constraint::Cage(Cage {
+            cells: cells.clone(),
+            sum: 3,
+        }));
+        assert_eq!(board.candidates(cells[0]), vec![1, 2]);
+        assert_eq!(board.candidates(cells[1]), vec![1, 2]);
+
+        let mut board = Board::new();
+        board.play_cell(cells[0], 9);
+        board.add_constraint(Constraint::Cage(Cage {
+            cells: cells.clone(),
+            sum: 10,
+        }));
+        assert_eq!(board.candidates(cells[1]), vec![1]);
+    }
+
+    #[test]
+    fn killer_example_solves_within_its_cages() {
+        let board = load(include_str!("../examples/killer1.txt"));
+        let solution = Solver::new().solve(board).unwrap();
+        assert!(solution.is_solved());
+        assert!(solution.verify_constraints().is_ok());
+    }
+
+    #[test]
+    fn cage_broken_by_the_givens_is_a_contradiction() {
+        let mut board: Board = format!("12{}", "x".repeat(79)).parse().unwrap();
+        board.add_constraint(Constraint::Cage(Cage {
+            cells: vec![BoardIdx::new(0, 0), BoardIdx::new(1, 0)],
+            sum: 10,
+        }));
+        assert!(board.verify_detailed().is_err());
+        assert_eq!(Solver::new().solve(board), None);
+    }
+
+    #[test]
+    fn parse_cage_needs_a_reachable_sum() {
+        assert_eq!(
+            Constraint::parse_cage("cage: 15 R1C1 r1c2"),
+            Some(Constraint::Cage(Cage {
+                cells: vec![BoardIdx::new(0, 0), BoardIdx::new(1, 0)],
+                sum: 15,
+            }))
+        );
+        assert_eq!(Constraint::parse_cage("cage: 2 R1C1 R1C2"), None);
+        assert_eq!(Constraint::parse_cage("cage: 18 R1C1 R1C2"), None);
+        assert_eq!(Constraint::parse_cage("cage: 4 R1C1 R1C1"), None);
+        assert_eq!(Constraint::parse_cage("thermo: R1C1 R1C2"), None);
+    }
+}
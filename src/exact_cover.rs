@@ -0,0 +1,67 @@
+//! Exporting a board as an exact cover problem for external DLX or SAT solvers
+
+use itertools::iproduct;
+
+use crate::{Board, BoardIdx};
+
+/// Columns in the standard Sudoku exact cover matrix: 81 each for cells, row-numbers, column-numbers, and square-numbers
+pub const EXACT_COVER_COLUMNS: usize = 4 * 81;
+
+/// A board as an exact cover problem: a solution is a set of rows covering every column exactly once
+///
+/// Column `idx` means cell `idx` is filled, `81 + row * 9 + num - 1` means `row` has a `num`,
+/// `162 + col * 9 + num - 1` means `col` has a `num`, and `243 + square * 9 + num - 1` means `square` has a `num`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExactCoverMatrix {
+    /// The placement each matrix row stands for
+    pub placements: Vec<(BoardIdx, u8)>,
+    /// The four columns covered by each matrix row, in ascending order
+    pub columns: Vec<[usize; 4]>,
+}
+
+impl ExactCoverMatrix {
+    /// Builds the board a set of chosen matrix rows describes, playing each of their placements
+    ///
+    /// Panics if a row index is out of range
+    #[track_caller]
+    pub fn to_board(&self, chosen_rows: &[usize]) -> Board {
+        let mut board = Board::new();
+        for &row in chosen_rows {
+            let (idx, num) = self.placements[row];
+            board.play_cell(idx, num);
+        }
+        board
+    }
+}
+
+impl Board {
+    /// The exact cover problem solved by this board
+    ///
+    /// There's a matrix row for each candidate left in each cell, so givens (and anything else already eliminated)
+    /// are pre-applied: a played cell only gets the row for its number
+    pub fn to_exact_cover(&self) -> ExactCoverMatrix {
+        let mut matrix = ExactCoverMatrix {
+            placements: Vec::new(),
+            columns: Vec::new(),
+        };
+
+        for (row, col, num) in iproduct!(0..9, 0..9, 1..=9u8) {
+            let idx = BoardIdx::new(col, row);
+            if !self.get(idx).is_possible(num) {
+                continue;
+            }
+
+            let digit = num as usize - 1;
+            let square = idx.square().to_idx();
+            matrix.placements.push((idx, num));
+            matrix.columns.push([
+                idx.idx,
+                81 + row * 9 + digit,
+                162 + col * 9 + digit,
+                243 + square * 9 + digit,
+            ]);
+        }
+
+        matrix
+    }
+}
@@ -0,0 +1,305 @@
+//! A controller for interactive play, so a frontend only has to draw the board and forward input
+//!
+//! Every action returns the [`GameEvent`]s it caused, in order. Nothing here draws anything
+
+use crate::{Board, BoardIdx, MoveError, Solver, techniques::Difficulty};
+
+/// Something that changed, or was found out, because of a [`Game`] action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEvent {
+    /// The cell later actions apply to
+    Selected(BoardIdx),
+    /// `num` was entered at `idx`
+    CellSet { idx: BoardIdx, num: u8 },
+    /// `num` was crossed off the candidates of `idx`, or put back if `crossed_off` is false
+    PencilToggled {
+        idx: BoardIdx,
+        num: u8,
+        crossed_off: bool,
+    },
+    /// The move wasn't made, and this is why
+    Rejected(MoveError),
+    /// A number entered at this cell doesn't match the solution
+    Mistake(BoardIdx),
+    /// A hint for `num` at `idx`, and what it takes to find it; it's entered right after
+    Hint {
+        idx: BoardIdx,
+        num: u8,
+        difficulty: Difficulty,
+    },
+    /// The last action was taken back
+    Undone,
+    /// Every cell is filled in correctly
+    Completed,
+}
+
+/// A puzzle being played, with a selected cell and an undo history
+pub struct Game {
+    board: Board,
+    /// The puzzle's first solution, which entries are checked against, or `None` if it has none
+    solution: Option<Board>,
+    selected: Option<BoardIdx>,
+    /// The board before each action that changed it, most recent last
+    history: Vec<Board>,
+}
+
+impl Game {
+    /// Starts a game on `board`, whose played cells become givens the player can't change
+    pub fn new(mut board: Board) -> Self {
+        board.freeze();
+        let solution = Solver::new().solutions_up_to(board.clone(), 1).pop();
+        Self {
+            board,
+            solution,
+            selected: None,
+            history: Vec::new(),
+        }
+    }
+
+    /// The board as it stands, for drawing
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn selected(&self) -> Option<BoardIdx> {
+        self.selected
+    }
+
+    /// Makes `idx` the cell that [`Game::input`] and [`Game::toggle_pencil`] act on
+    pub fn select(&mut self, idx: BoardIdx) -> Vec<GameEvent> {
+        self.selected = Some(idx);
+        vec![GameEvent::Selected(idx)]
+    }
+
+    /// Enters `num` in the selected cell, if it's a legal move there. Does nothing without a selection
+    ///
+    /// A legal move can still be wrong; that's only reported by [`Game::check`]
+    pub fn input(&mut self, num: u8) -> Vec<GameEvent> {
+        let Some(idx) = self.selected else {
+            return Vec::new();
+        };
+        let before = self.board.clone();
+        if let Err(err) = self.board.play_checked(idx, num) {
+            return vec![GameEvent::Rejected(err)];
+        }
+        self.history.push(before);
+        self.after_move(vec![GameEvent::CellSet { idx, num }])
+    }
+
+    /// Crosses `num` off the selected cell's candidates, or puts it back if the player crossed it off before.
+    /// Does nothing without a selection, or on a filled in cell
+    pub fn toggle_pencil(&mut self, num: u8) -> Vec<GameEvent> {
+        let Some(idx) = self.selected else {
+            return Vec::new();
+        };
+        let before = self.board.clone();
+        let crossed_off = match self.board.eliminate(idx, num) {
+            Err(err) => return vec![GameEvent::Rejected(err)],
+            Ok(true) => true,
+            Ok(false) if self.board.restore_candidate(idx, num) => false,
+            Ok(false) => return Vec::new(),
+        };
+        self.history.push(before);
+        vec![GameEvent::PencilToggled {
+            idx,
+            num,
+            crossed_off,
+        }]
+    }
+
+    /// Fills in the easiest cell to find next, as [`Board::solution_path`] would
+    ///
+    /// If the entries so far leave no solution there's nothing to hint at, so this reports them like [`Game::check`]
+    pub fn hint(&mut self) -> Vec<GameEvent> {
+        let next = self
+            .board
+            .solution_path()
+            .and_then(|path| path.first().copied());
+        let Some((idx, num, difficulty)) = next else {
+            return self.check();
+        };
+        self.history.push(self.board.clone());
+        self.board.play_cell(idx, num);
+        self.after_move(vec![
+            GameEvent::Hint {
+                idx,
+                num,
+                difficulty,
+            },
+            GameEvent::CellSet { idx, num },
+        ])
+    }
+
+    /// Every entry that doesn't match the solution, in row-major order, or [`GameEvent::Completed`] if the board is done
+    pub fn check(&self) -> Vec<GameEvent> {
+        let mut events: Vec<_> = self.mistakes().map(GameEvent::Mistake).collect();
+        if events.is_empty() && self.is_completed() {
+            events.push(GameEvent::Completed);
+        }
+        events
+    }
+
+    /// Takes back the last entry, pencil mark, or hint. Does nothing at the start of the game
+    pub fn undo(&mut self) -> Vec<GameEvent> {
+        match self.history.pop() {
+            Some(board) => {
+                self.board = board;
+                vec![GameEvent::Undone]
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Adds [`GameEvent::Completed`] to `events` if the move that caused them finished the puzzle
+    fn after_move(&self, mut events: Vec<GameEvent>) -> Vec<GameEvent> {
+        if self.is_completed() {
+            events.push(GameEvent::Completed);
+        }
+        events
+    }
+
+    /// Entries that don't match the solution, in row-major order. There are none if there's no solution
+    fn mistakes(&self) -> impl Iterator<Item = BoardIdx> + '_ {
+        (0..81).map(BoardIdx::from_linear).filter(|&idx| {
+            self.solution.as_ref().is_some_and(|solution| {
+                self.board.played.contains(&idx) && self.board.get(idx) != solution.get(idx)
+            })
+        })
+    }
+
+    /// Whether the board is full and matches the solution, so a puzzle without one is never completed
+    fn is_completed(&self) -> bool {
+        self.solution.is_some() && self.board.is_solved() && self.mistakes().next().is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn easy() -> Board {
+        include_str!("../examples/easy1.txt").parse().unwrap()
+    }
+
+    /// Enters every unplayed cell of `grid` that the game's board doesn't have yet, returning the last events
+    fn fill_in(game: &mut Game, grid: &Board) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        for idx in (0..81).map(BoardIdx::from_linear) {
+            if !game.board().played.contains(&idx) {
+                game.select(idx);
+                events = game.input(grid.get(idx).possibilities()[0]);
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn hints_finish_the_puzzle() {
+        let mut game = Game::new(easy());
+        let solution = Solver::new().solve(easy()).unwrap();
+        let mut events = Vec::new();
+        while !events.contains(&GameEvent::Completed) {
+            events = game.hint();
+            assert!(matches!(events[0], GameEvent::Hint { .. }), "{events:?}");
+        }
+        assert_eq!(game.board().to_line(), solution.to_line());
+        assert_eq!(game.check(), [GameEvent::Completed]);
+    }
+
+    #[test]
+    fn input_completes_only_the_solution() {
+        let mut game = Game::new(easy());
+        let solution = Solver::new().solve(easy()).unwrap();
+        assert_eq!(
+            fill_in(&mut game, &solution).last(),
+            Some(&GameEvent::Completed)
+        );
+
+        // A full, valid grid that isn't the first solution still has mistakes, so it isn't completed
+        let mut game = Game::new(Board::new());
+        let other = game
+            .solution
+            .as_ref()
+            .unwrap()
+            .relabel(&[2, 1, 3, 4, 5, 6, 7, 8, 9]);
+        let events = fill_in(&mut game, &other);
+        assert!(game.board().is_solved());
+        assert!(!events.contains(&GameEvent::Completed), "{events:?}");
+        let check = game.check();
+        assert!(!check.is_empty());
+        assert!(
+            check
+                .iter()
+                .all(|event| matches!(event, GameEvent::Mistake(_)))
+        );
+    }
+
+    #[test]
+    fn input_needs_a_legal_move() {
+        let mut game = Game::new(easy());
+        assert_eq!(game.input(5), []);
+
+        let given = BoardIdx::new(2, 0);
+        game.select(given);
+        assert_eq!(
+            game.input(1),
+            [GameEvent::Rejected(MoveError::Frozen(given))]
+        );
+
+        let empty = BoardIdx::new(0, 0);
+        game.select(empty);
+        assert_eq!(
+            game.input(1),
+            [GameEvent::Rejected(MoveError::NotPossible(empty, 1))]
+        );
+    }
+
+    #[test]
+    fn check_reports_a_wrong_entry_until_undone() {
+        let mut game = Game::new(easy());
+        let solution = Solver::new().solve(easy()).unwrap();
+        let (idx, wrong) = (0..81)
+            .map(BoardIdx::from_linear)
+            .find_map(|idx| {
+                let right = solution.get(idx).possibilities()[0];
+                let candidates = game.board().get(idx).possibilities();
+                candidates
+                    .iter()
+                    .find(|&&num| num != right)
+                    .map(|&num| (idx, num))
+            })
+            .unwrap();
+        game.select(idx);
+        assert_eq!(game.input(wrong), [GameEvent::CellSet { idx, num: wrong }]);
+        assert_eq!(game.check(), [GameEvent::Mistake(idx)]);
+        assert_eq!(game.undo(), [GameEvent::Undone]);
+        assert_eq!(game.check(), []);
+        assert_eq!(game.undo(), []);
+    }
+
+    #[test]
+    fn toggle_pencil_crosses_off_and_puts_back() {
+        let mut game = Game::new(easy());
+        let idx = BoardIdx::new(0, 0);
+        let num = game.board().get(idx).possibilities()[0];
+        game.select(idx);
+        assert_eq!(
+            game.toggle_pencil(num),
+            [GameEvent::PencilToggled {
+                idx,
+                num,
+                crossed_off: true
+            }]
+        );
+        assert!(!game.board().get(idx).is_possible(num));
+        assert_eq!(
+            game.toggle_pencil(num),
+            [GameEvent::PencilToggled {
+                idx,
+                num,
+                crossed_off: false
+            }]
+        );
+        assert!(game.board().get(idx).is_possible(num));
+    }
+}
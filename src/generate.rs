@@ -0,0 +1,534 @@
+//! Making new puzzles by taking clues away from a random solved grid
+
+use std::collections::HashSet;
+
+use itertools::{Itertools, iproduct};
+
+use crate::{
+    Board, BoardIdx, Solver, SquareIdx,
+    rng::Rng,
+    techniques::{Difficulty, Technique},
+};
+
+/// The order [`generate`] tries taking clues away in, which shapes what the puzzle looks like
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RemovalOrder {
+    /// Any clue, in a random order
+    #[default]
+    Random,
+    /// Clues from the most crowded units first, so the remaining clues end up spread out
+    ByConstraint,
+    /// Clues in pairs mirrored through the center, so the puzzle has 180 degree rotational symmetry
+    Symmetric,
+}
+
+/// What [`Board::minimal_clue_count`] found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClueEstimate {
+    /// Clues tried, each one different
+    pub sampled: usize,
+    /// How many of those could be taken away, one after another, with the solution staying unique
+    pub removed: usize,
+    /// The clues left after that
+    pub estimate: usize,
+}
+
+/// Settings for [`generate`]
+#[derive(Debug, Clone, Default)]
+pub struct GeneratorOptions {
+    /// The same seed and options always give the same puzzle
+    pub seed: u64,
+    pub removal: RemovalOrder,
+    /// Stop taking clues away once this many are left, or carry on until none can go if `None`
+    ///
+    /// A clue only goes if the solution stays unique without it, so the puzzle can be left with more than this
+    pub clues: Option<usize>,
+}
+
+/// Generates a puzzle with exactly one solution, which can't lose any more clues (in `options.removal` order)
+/// without losing that, unless it got down to `options.clues` first
+pub fn generate(options: &GeneratorOptions) -> Board {
+    let mut rng = Rng::new(options.seed);
+    let solution = random_solution(&Solver::new(), &mut rng);
+    solution.reduce(options.removal, options.clues.unwrap_or(0), &mut rng)
+}
+
+/// A random solved grid: the three squares on the diagonal are shuffled, and the rest is solved around them
+///
+/// Those squares don't share any units, so any numbers placed in them are consistent.
+/// The solve is sequential, since the parallel one can return a different solution each run
+fn random_solution(solver: &Solver, rng: &mut Rng) -> Board {
+    let mut board = Board::new();
+    for square in [SquareIdx::TL, SquareIdx::MM, SquareIdx::BR] {
+        let mut nums: Vec<u8> = (1..=9).collect();
+        rng.shuffle(&mut nums);
+        for (idx, num) in board.iter_square(square).into_iter().zip(nums) {
+            board.play_cell(idx, num);
+        }
+    }
+    solver
+        .solutions_up_to(board, 1)
+        .pop()
+        .expect("filled diagonal squares always leave a solvable board")
+}
+
+impl Board {
+    /// A board with `count` numbers played in random cells, none of them clashing, for exercising propagation and
+    /// rendering on varied inputs
+    ///
+    /// Each number is checked with [`Board::play_checked`], and skipped if it would leave some cell with no candidates,
+    /// so the result always passes [`Board::verify_detailed`]. It isn't necessarily solvable, let alone uniquely.
+    /// Stops early, with fewer numbers, if no cell can take another one
+    pub fn quasi_random_fill(count: usize, rng: &mut Rng) -> Board {
+        let mut board = Board::new();
+        let mut cells = iproduct!(0..9, 0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+            .collect_vec();
+        rng.shuffle(&mut cells);
+
+        let mut placed = 0;
+        for idx in cells {
+            if placed == count {
+                break;
+            }
+            let mut nums = board.get(idx).possibilities();
+            rng.shuffle(&mut nums);
+            let played = nums.into_iter().find_map(|num| {
+                let mut trial = board.clone();
+                trial.play_checked(idx, num).ok()?;
+                trial.verify().is_ok().then_some(trial)
+            });
+            if let Some(trial) = played {
+                board = trial;
+                placed += 1;
+            }
+        }
+        board
+    }
+}
+
+impl Board {
+    /// Greedily takes away played cells in a random order, skipping any whose removal would allow a second solution
+    ///
+    /// No single clue of the result can be removed, though a different order might find a smaller set overall.
+    /// `self` should have exactly one solution; a solved grid works, as does any unique puzzle
+    pub fn reduce_to_minimal(&self, rng: &mut Rng) -> Board {
+        self.reduce(RemovalOrder::Random, 0, rng)
+    }
+
+    /// Like [`Board::reduce_to_minimal`], but only settles for a puzzle that needs `tier`, or something harder,
+    /// somewhere along its [`Board::solution_path`], so it's sure to exercise that skill
+    ///
+    /// Each attempt reduces in a fresh random order and grades the result, and `None` comes back if none of the
+    /// `attempts` needed enough. That's slow: an attempt costs a uniqueness check per clue plus a full grade, tens of
+    /// milliseconds, and the harder tiers can take hundreds of attempts to hit. The same `rng` state always gives the
+    /// same puzzle. `self` should have exactly one solution
+    pub fn reduce_to_minimal_requiring(
+        &self,
+        tier: Difficulty,
+        attempts: usize,
+        rng: &mut Rng,
+    ) -> Option<Board> {
+        (0..attempts).find_map(|_| {
+            let puzzle = self.reduce(RemovalOrder::Random, 0, rng);
+            let hardest = puzzle
+                .solution_path()?
+                .into_iter()
+                .map(|(_, _, difficulty)| difficulty)
+                .max()?;
+            (hardest >= tier).then_some(puzzle)
+        })
+    }
+
+    /// A fresh minimal puzzle whose hardest step along its [`Board::solution_path`] is `technique`, for practising
+    /// that one technique, or `None` if none of the `attempts` came out that way
+    ///
+    /// Each attempt reduces a new random solution in a random order, then grades it. A puzzle that needs a harder
+    /// technique, or a guess, is thrown away along with the ones that don't need `technique` at all. Slower still than
+    /// [`Board::reduce_to_minimal_requiring`], since only one tier is kept. The same `rng` state always gives the same puzzle
+    pub fn generate_requiring(
+        technique: Technique,
+        attempts: usize,
+        rng: &mut Rng,
+    ) -> Option<Board> {
+        let solver = Solver::new();
+        (0..attempts).find_map(|_| {
+            let puzzle = random_solution(&solver, rng).reduce(RemovalOrder::Random, 0, rng);
+            let hardest = puzzle
+                .solution_path()?
+                .into_iter()
+                .map(|(_, _, difficulty)| difficulty)
+                .max()?;
+            (hardest == Difficulty::Technique(technique)).then_some(puzzle)
+        })
+    }
+
+    /// A quick, approximate count of the clues a minimal version of this puzzle has, from at most `samples` checks
+    ///
+    /// Like [`Board::reduce_to_minimal`], randomly picked clues are taken away whenever the puzzle keeps its unique
+    /// solution without them, but only the first `samples` clues are tried. So it's an upper bound, and exact only once
+    /// every clue has been tried. `self` should have exactly one solution
+    pub fn minimal_clue_count(&self, samples: usize, rng: &mut Rng) -> ClueEstimate {
+        let solver = Solver::new();
+        let mut clues: Vec<BoardIdx> = iproduct!(0..9, 0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+            .filter(|idx| self.played.contains(idx))
+            .collect();
+        rng.shuffle(&mut clues);
+
+        let sampled = samples.min(clues.len());
+        let mut kept = clues.split_off(sampled);
+        let mut removed = 0;
+        for (pos, &clue) in clues.iter().enumerate() {
+            let remaining = [&kept[..], &clues[pos + 1..]].concat();
+            match solver.solutions_up_to(self.with_clues(&remaining), 2).len() == 1 {
+                true => removed += 1,
+                false => kept.push(clue),
+            }
+        }
+        ClueEstimate {
+            sampled,
+            removed,
+            estimate: kept.len(),
+        }
+    }
+
+    /// Whether this puzzle has exactly one solution and loses it without any single one of its clues, which is what
+    /// [`Board::reduce_to_minimal`] produces
+    ///
+    /// Costs a uniqueness check per clue, so tens of milliseconds for a typical puzzle
+    pub fn is_minimal(&self) -> bool {
+        let solver = Solver::new();
+        let clues: Vec<BoardIdx> = iproduct!(0..9, 0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+            .filter(|idx| self.played.contains(idx))
+            .collect();
+        let unique =
+            |clues: &[BoardIdx]| solver.solutions_up_to(self.with_clues(clues), 2).len() == 1;
+        unique(&clues)
+            && (0..clues.len()).all(|pos| !unique(&[&clues[..pos], &clues[pos + 1..]].concat()))
+    }
+
+    /// [`Board::reduce_to_minimal`], trying clues in `order` and never going below `floor` of them
+    fn reduce(&self, order: RemovalOrder, floor: usize, rng: &mut Rng) -> Board {
+        let solver = Solver::new();
+        let mut clues: Vec<BoardIdx> = iproduct!(0..9, 0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+            .filter(|idx| self.played.contains(idx))
+            .collect();
+        let mut tried = Vec::new();
+        while clues.len() > floor
+            && let Some(group) = self.next_removal(order, &clues, &tried, rng)
+        {
+            tried.extend(&group);
+            let remaining: Vec<BoardIdx> = clues
+                .iter()
+                .copied()
+                .filter(|idx| !group.contains(idx))
+                .collect();
+            // A symmetric pair can overshoot by one, so it's passed over when there's room for only one more
+            if remaining.len() >= floor
+                && solver.solutions_up_to(self.with_clues(&remaining), 2).len() == 1
+            {
+                clues = remaining;
+            }
+        }
+        self.with_clues(&clues)
+    }
+
+    /// A puzzle with just the numbers at `keep` as clues, usually cut out of a solution
+    ///
+    /// Every other cell starts over with all its candidates, and the kept ones are played back onto an empty board
+    /// with the same constraints, so nothing this board ruled out carries over. Cells in `keep` that aren't played here,
+    /// or that a clash has left with no candidates, are left out. There's no check that the clues still pin down a
+    /// single solution
+    pub fn keep_clues(&self, keep: &HashSet<BoardIdx>) -> Board {
+        let clues = keep
+            .iter()
+            .copied()
+            .filter(|idx| self.played.contains(idx))
+            .sorted_by_key(|idx| idx.idx)
+            .collect_vec();
+        self.with_clues(&clues)
+    }
+
+    /// The numbers played in this board at `clues`, played onto an empty board with the same constraints
+    ///
+    /// A clue without a number, because a clash left it with no candidates, is skipped
+    fn with_clues(&self, clues: &[BoardIdx]) -> Board {
+        let mut board = Board::new();
+        for constraint in &self.constraints {
+            board.add_constraint(constraint.clone());
+        }
+        for &idx in clues {
+            if let Some(num) = self.played_num(idx) {
+                board.play_cell(idx, num);
+            }
+        }
+        board
+    }
+
+    /// The next clues [`generate`] should try removing together, out of the `clues` it hasn't `tried` yet
+    fn next_removal(
+        &self,
+        order: RemovalOrder,
+        clues: &[BoardIdx],
+        tried: &[BoardIdx],
+        rng: &mut Rng,
+    ) -> Option<Vec<BoardIdx>> {
+        let untried: Vec<BoardIdx> = clues
+            .iter()
+            .copied()
+            .filter(|idx| !tried.contains(idx))
+            .collect();
+        if untried.is_empty() {
+            return None;
+        }
+
+        match order {
+            RemovalOrder::Random => Some(vec![untried[rng.below(untried.len())]]),
+            RemovalOrder::ByConstraint => {
+                let crowding = |idx: BoardIdx| {
+                    clues
+                        .iter()
+                        .filter(|&&clue| {
+                            clue.col == idx.col
+                                || clue.row == idx.row
+                                || clue.square() == idx.square()
+                        })
+                        .count()
+                };
+                let most = untried.iter().map(|&idx| crowding(idx)).max()?;
+                let crowded: Vec<BoardIdx> = untried
+                    .into_iter()
+                    .filter(|&idx| crowding(idx) == most)
+                    .collect();
+                Some(vec![crowded[rng.below(crowded.len())]])
+            }
+            RemovalOrder::Symmetric => {
+                let idx = untried[rng.below(untried.len())];
+                let mirror = BoardIdx::new(8 - idx.col, 8 - idx.row);
+                Some(match mirror == idx {
+                    true => vec![idx],
+                    false => vec![idx, mirror],
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BOARD_CELLS, Cell};
+
+    #[test]
+    fn keep_clues_skips_an_emptied_cell() {
+        let mut board = Board::new();
+        let (kept, emptied) = (BoardIdx::new(4, 4), BoardIdx::new(0, 0));
+        board.play_cell(kept, 3);
+        board.play_cell(emptied, 5);
+        board.set_raw(emptied, Cell::none_possible());
+        let clues = board.keep_clues(&HashSet::from([kept, emptied]));
+        assert_eq!(clues.played_num(kept), Some(3));
+        assert_eq!(clues.played_num(emptied), None);
+        assert_eq!(clues.clue_count(), 1);
+    }
+
+    fn easy() -> Board {
+        include_str!("../examples/easy1.txt").parse().unwrap()
+    }
+
+    #[test]
+    fn keeping_every_cell_gives_back_the_solution() {
+        let solution = crate::solve(&easy()).unwrap();
+        let all = (0..BOARD_CELLS).map(BoardIdx::from_linear).collect();
+        let kept = solution.keep_clues(&all);
+        assert_eq!(kept.to_line(), solution.to_line());
+        assert_eq!(kept.clue_count(), BOARD_CELLS);
+    }
+
+    #[test]
+    fn keeping_the_puzzles_clues_still_solves() {
+        let puzzle = easy();
+        let solution = crate::solve(&puzzle).unwrap();
+        let kept = solution.keep_clues(&puzzle.played);
+        assert_eq!(kept.to_sudoku_line(), puzzle.to_sudoku_line());
+        assert!(kept.has_unique_solution());
+        assert_eq!(crate::solve(&kept).unwrap().to_line(), solution.to_line());
+    }
+
+    #[test]
+    fn keep_clues_starts_the_rest_over() {
+        let solution = crate::solve(&easy()).unwrap();
+        let kept = solution.keep_clues(&HashSet::from([BoardIdx::new(0, 0)]));
+        assert_eq!(kept.clue_count(), 1);
+        // Only the kept clue's peers lose a candidate
+        assert_eq!(kept.get(BoardIdx::new(8, 8)), Cell::any_possible());
+        assert_eq!(kept.get(BoardIdx::new(8, 0)).num_possibilities(), 8);
+    }
+
+    #[test]
+    fn quasi_random_fill_passes_verify() {
+        for seed in 0..20 {
+            let mut rng = Rng::new(seed);
+            for count in [0, 10, 30, 60] {
+                let board = Board::quasi_random_fill(count, &mut rng);
+                assert!(
+                    board.verify_detailed().is_ok(),
+                    "seed {seed}, {count} cells"
+                );
+                assert!(board.played.len() <= count);
+            }
+        }
+    }
+
+    #[test]
+    fn quasi_random_fill_places_the_count_asked_for_when_it_can() {
+        let mut rng = Rng::new(3);
+        assert_eq!(Board::quasi_random_fill(20, &mut rng).played.len(), 20);
+        assert_eq!(Board::quasi_random_fill(0, &mut rng), Board::new());
+        // The same seed gives the same board
+        assert_eq!(
+            Board::quasi_random_fill(20, &mut Rng::new(9)),
+            Board::quasi_random_fill(20, &mut Rng::new(9))
+        );
+    }
+
+    #[test]
+    fn reduce_to_minimal_requiring_needs_the_tier() {
+        let solution = crate::solve(&easy()).unwrap();
+        let tier = Difficulty::Technique(Technique::NakedPair);
+        let puzzle = solution
+            .reduce_to_minimal_requiring(tier, 50, &mut Rng::new(0))
+            .unwrap();
+        let hardest = puzzle
+            .solution_path()
+            .unwrap()
+            .into_iter()
+            .map(|(_, _, difficulty)| difficulty)
+            .max()
+            .unwrap();
+        assert!(hardest >= tier);
+        assert!(puzzle.has_unique_solution());
+        assert_eq!(crate::solve(&puzzle).unwrap(), solution);
+        assert_eq!(
+            solution.reduce_to_minimal_requiring(tier, 50, &mut Rng::new(0)),
+            Some(puzzle)
+        );
+    }
+
+    #[test]
+    fn generate_requiring_needs_the_technique() {
+        let puzzle = Board::generate_requiring(Technique::NakedPair, 3, &mut Rng::new(0)).unwrap();
+        assert!(puzzle.has_unique_solution());
+        assert!(puzzle.rate_difficulty().unwrap() >= crate::techniques::Rating::Medium);
+        let hardest = puzzle
+            .solution_path()
+            .unwrap()
+            .into_iter()
+            .map(|(_, _, difficulty)| difficulty)
+            .max();
+        assert_eq!(hardest, Some(Difficulty::Technique(Technique::NakedPair)));
+    }
+
+    #[test]
+    fn reduce_to_minimal_is_unique_and_minimal() {
+        let solution = crate::solve(&easy()).unwrap();
+        let puzzle = solution.reduce_to_minimal(&mut Rng::new(1));
+        assert!(puzzle.has_unique_solution());
+        assert_eq!(crate::solve(&puzzle).unwrap(), solution);
+
+        let clues: Vec<BoardIdx> = (0..BOARD_CELLS)
+            .map(BoardIdx::from_linear)
+            .filter(|idx| puzzle.played.contains(idx))
+            .collect();
+        assert!(clues.len() >= 17);
+        for pos in 0..clues.len() {
+            let fewer = puzzle.with_clues(&[&clues[..pos], &clues[pos + 1..]].concat());
+            assert!(!fewer.has_unique_solution());
+        }
+    }
+
+    #[test]
+    fn minimal_clue_count_is_seeded_and_tightens_with_more_samples() {
+        let puzzle = easy();
+        assert!(puzzle.has_unique_solution());
+        assert_eq!(puzzle.clue_count(), 38);
+
+        let partial = puzzle.minimal_clue_count(10, &mut Rng::new(3));
+        assert_eq!(
+            partial,
+            ClueEstimate {
+                sampled: 10,
+                removed: 8,
+                estimate: 30
+            }
+        );
+        assert_eq!(puzzle.minimal_clue_count(10, &mut Rng::new(3)), partial);
+
+        // The same order, carried on through every clue
+        let full = puzzle.minimal_clue_count(100, &mut Rng::new(3));
+        assert_eq!(
+            full,
+            ClueEstimate {
+                sampled: 38,
+                removed: 14,
+                estimate: 24
+            }
+        );
+        assert!(full.removed >= partial.removed);
+    }
+
+    #[test]
+    fn generate_is_seeded_and_stops_at_the_clue_count() {
+        for removal in [RemovalOrder::Random, RemovalOrder::ByConstraint] {
+            let options = |seed| GeneratorOptions {
+                seed,
+                removal,
+                clues: Some(30),
+            };
+            let puzzle = generate(&options(5));
+            assert_eq!(puzzle.clue_count(), 30, "{removal:?}");
+            assert!(puzzle.has_unique_solution());
+            assert_eq!(generate(&options(5)), puzzle);
+            assert_ne!(generate(&options(6)).to_line(), puzzle.to_line());
+        }
+    }
+
+    #[test]
+    fn symmetric_generation_keeps_its_symmetry() {
+        let options = GeneratorOptions {
+            seed: 5,
+            removal: RemovalOrder::Symmetric,
+            clues: Some(30),
+        };
+        let puzzle = generate(&options);
+        // A pair that would take it to 29 is passed over
+        assert!((30..=31).contains(&puzzle.clue_count()));
+        assert!(puzzle.has_unique_solution());
+        assert!(matches!(
+            puzzle.clue_symmetry(),
+            Some(symmetry) if symmetry <= crate::transform::Symmetry::HalfTurn
+        ));
+        assert_eq!(generate(&options), puzzle);
+    }
+
+    #[test]
+    fn is_minimal_until_a_clue_is_added_back() {
+        let solution = crate::solve(&easy()).unwrap();
+        let mut puzzle = solution.reduce_to_minimal(&mut Rng::new(2));
+        assert!(puzzle.is_minimal());
+
+        let blank = (0..BOARD_CELLS)
+            .map(BoardIdx::from_linear)
+            .find(|idx| !puzzle.played.contains(idx))
+            .unwrap();
+        puzzle.play_cell(blank, solution.played_num(blank).unwrap());
+        assert!(puzzle.has_unique_solution());
+        assert!(!puzzle.is_minimal());
+
+        assert!(!easy().is_minimal(), "the easy puzzle has clues to spare");
+        assert!(!Board::new().is_minimal(), "a blank board isn't unique");
+    }
+}
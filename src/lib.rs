@@ -0,0 +1,3463 @@
+//! Solving, generating, and grading Sudoku puzzles
+//!
+//! [`Board`] holds a puzzle, and [`solve`] finds a solution to one. The modules cover parsing, rendering, the
+//! logical techniques, puzzle generation, and batch solving
+
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+#![cfg_attr(all(feature = "simd", test), feature(test))]
+
+use std::{
+    array,
+    borrow::Cow,
+    collections::HashSet,
+    sync::{
+        Arc, LazyLock, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use constraint::Constraint;
+use itertools::{Itertools, iproduct};
+use parse::{ParseError, RowLayout};
+use rayon::prelude::*;
+use render::RenderOptions;
+use rng::Rng;
+
+pub mod alphabet;
+#[cfg(feature = "tokio")]
+pub mod async_solve;
+pub mod batch;
+pub mod constraint;
+pub mod exact_cover;
+pub mod game;
+pub mod generate;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod parse;
+pub mod render;
+pub mod rng;
+pub mod samurai;
+#[cfg(feature = "serde")]
+mod serde_support;
+#[cfg(feature = "simd")]
+mod simd;
+pub mod sized;
+pub mod storage;
+pub mod techniques;
+pub mod transform;
+
+/// The nth bit represents if the number `n` is possible in this cell
+///
+/// Only bits `1` through `9` are ever set, so the others can't leak into comparisons or rendering
+#[derive(Clone, Copy, Eq)]
+pub struct Cell(u16);
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        self.important_bits() == other.important_bits()
+    }
+}
+
+impl std::hash::Hash for Cell {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.important_bits().hash(state);
+    }
+}
+
+impl std::fmt::Debug for Cell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.possibilities().iter().join(","))
+    }
+}
+
+impl std::fmt::Display for Cell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol(&RenderOptions::default()))
+    }
+}
+
+impl Cell {
+    /// The bits that carry information, `1` through `9`
+    const IMPORTANT: u16 = 0b1111111110;
+
+    /// A cell whose only candidate is `num`, which must be in `1..=9`
+    #[track_caller]
+    pub fn fixed(num: u8) -> Self {
+        debug_assert!(
+            (1..=9).contains(&num),
+            "Cell::fixed called with out of range num {num}"
+        );
+        Self(1 << num & Self::IMPORTANT)
+    }
+
+    pub fn any_possible() -> Self {
+        Self(Self::IMPORTANT)
+    }
+
+    pub fn none_possible() -> Self {
+        Self(0)
+    }
+
+    /// The internal representation without the bits that don't carry information
+    ///
+    /// Only the bits `1` through `9` are important
+    pub fn important_bits(&self) -> u16 {
+        self.0 & Self::IMPORTANT
+    }
+
+    pub fn is_possible(&self, num: u8) -> bool {
+        (self.0 >> num) & 1 == 1
+    }
+
+    /// Marks `num` as possible or impossible for this cell
+    ///
+    /// `num` must be in `1..=9`; anything else would touch a bit that doesn't carry information
+    #[track_caller]
+    pub fn set_possible(&mut self, num: u8, possible: bool) {
+        debug_assert!(
+            (1..=9).contains(&num),
+            "Cell::set_possible called with out of range num {num}"
+        );
+        if possible {
+            self.0 |= 1 << num;
+        } else {
+            self.0 &= !(1 << num);
+        }
+    }
+
+    /// Which numbers are possible for this cell
+    pub fn possibilities(&self) -> Vec<u8> {
+        (1u8..10).filter(|&num| self.is_possible(num)).collect()
+    }
+
+    /// The numbers possible in both this cell and `other`
+    pub fn both_possible(self, other: Cell) -> Cell {
+        Cell(self.important_bits() & other.important_bits())
+    }
+
+    pub fn num_possibilities(&self) -> usize {
+        self.important_bits().count_ones() as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SquareIdx {
+    TL,
+    TM,
+    TR,
+    ML,
+    MM,
+    MR,
+    BL,
+    BM,
+    BR,
+}
+
+impl SquareIdx {
+    pub fn from_idx(idx: usize) -> Self {
+        use SquareIdx::*;
+        match idx {
+            0 => TL,
+            1 => TM,
+            2 => TR,
+            3 => ML,
+            4 => MM,
+            5 => MR,
+            6 => BL,
+            7 => BM,
+            8 => BR,
+            _ => panic!(),
+        }
+    }
+
+    pub fn to_idx(self) -> usize {
+        use SquareIdx::*;
+        match self {
+            TL => 0,
+            TM => 1,
+            TR => 2,
+            ML => 3,
+            MM => 4,
+            MR => 5,
+            BL => 6,
+            BM => 7,
+            BR => 8,
+        }
+    }
+
+    pub fn to_topleft_cell(self) -> BoardIdx {
+        use SquareIdx::*;
+        match self {
+            TL => BoardIdx::new(0, 0),
+            TM => BoardIdx::new(3, 0),
+            TR => BoardIdx::new(6, 0),
+            ML => BoardIdx::new(0, 3),
+            MM => BoardIdx::new(3, 3),
+            MR => BoardIdx::new(6, 3),
+            BL => BoardIdx::new(0, 6),
+            BM => BoardIdx::new(3, 6),
+            BR => BoardIdx::new(6, 6),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "serde_support::Coords", try_from = "serde_support::Coords")
+)]
+pub struct BoardIdx {
+    col: usize,
+    row: usize,
+    idx: usize,
+}
+
+impl BoardIdx {
+    pub fn new(col: usize, row: usize) -> Self {
+        Self {
+            col,
+            row,
+            idx: col + row * 9,
+        }
+    }
+
+    /// The cell at `idx` in row-major order, the same as [`BoardIdx::new`] with `idx % 9` and `idx / 9`
+    ///
+    /// Panics if `idx` is past the last cell
+    #[track_caller]
+    pub fn from_linear(idx: usize) -> Self {
+        assert!(idx < BOARD_CELLS, "Cell index {idx} is out of bounds");
+        Self::new(idx % 9, idx / 9)
+    }
+
+    /// Whether `other` is a different cell sharing a row, column, or square with this one
+    pub fn sees(&self, other: BoardIdx) -> bool {
+        *self != other
+            && (self.col == other.col || self.row == other.row || self.square() == other.square())
+    }
+
+    #[track_caller]
+    pub fn square(&self) -> SquareIdx {
+        use SquareIdx::*;
+        match (self.col / 3, self.row / 3) {
+            (0, 0) => TL,
+            (1, 0) => TM,
+            (2, 0) => TR,
+            (0, 1) => ML,
+            (1, 1) => MM,
+            (2, 1) => MR,
+            (0, 2) => BL,
+            (1, 2) => BM,
+            (2, 2) => BR,
+            _ => unreachable!("Invalid board idx!"),
+        }
+    }
+}
+
+impl std::fmt::Display for BoardIdx {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "R{}C{}", self.row + 1, self.col + 1)
+    }
+}
+
+/// Why a candidate was removed from a cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cause {
+    /// The number was played in this peer
+    Placement(BoardIdx),
+    /// The user crossed it off through [`Board::eliminate`]
+    User,
+    NakedPair,
+    NakedQuad,
+    HiddenQuad,
+    Skyscraper,
+    TwoStringKite,
+    SimpleColoring,
+    UniqueRectangle,
+    BugPlusOne,
+    Nishio,
+    Thermometer,
+    Cage,
+}
+
+/// A single candidate removal, recorded when provenance tracking is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elimination {
+    pub cell: BoardIdx,
+    pub num: u8,
+    pub cause: Cause,
+}
+
+impl std::fmt::Display for Elimination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.cause {
+            Cause::Placement(peer) => write!(
+                f,
+                "{} removed from {} because of the {} in {peer}",
+                self.num, self.cell, self.num
+            ),
+            Cause::User => write!(f, "{} crossed off {} by hand", self.num, self.cell),
+            Cause::NakedPair => {
+                write!(f, "{} removed from {} by a naked pair", self.num, self.cell)
+            }
+            Cause::NakedQuad => {
+                write!(f, "{} removed from {} by a naked quad", self.num, self.cell)
+            }
+            Cause::HiddenQuad => write!(
+                f,
+                "{} removed from {} by a hidden quad",
+                self.num, self.cell
+            ),
+            Cause::Skyscraper => {
+                write!(f, "{} removed from {} by a skyscraper", self.num, self.cell)
+            }
+            Cause::TwoStringKite => write!(
+                f,
+                "{} removed from {} by a two-string kite",
+                self.num, self.cell
+            ),
+            Cause::SimpleColoring => {
+                write!(
+                    f,
+                    "{} removed from {} by simple coloring",
+                    self.num, self.cell
+                )
+            }
+            Cause::UniqueRectangle => write!(
+                f,
+                "{} removed from {} by a unique rectangle",
+                self.num, self.cell
+            ),
+            Cause::BugPlusOne => write!(f, "{} removed from {} by BUG+1", self.num, self.cell),
+            Cause::Nishio => write!(f, "{} removed from {} by Nishio", self.num, self.cell),
+            Cause::Thermometer => write!(
+                f,
+                "{} removed from {} by a thermometer",
+                self.num, self.cell
+            ),
+            Cause::Cage => write!(f, "{} removed from {} by a cage", self.num, self.cell),
+        }
+    }
+}
+
+/// A row, column, or square, numbered from 0 like in [`Board::iter_row`] and friends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Column(usize),
+    Row(usize),
+    Square(usize),
+}
+
+impl Unit {
+    /// The unit at `unit` in [`Board::units`]
+    pub fn from_idx(unit: usize) -> Self {
+        match unit {
+            0..9 => Unit::Column(unit),
+            9..18 => Unit::Row(unit - 9),
+            _ => Unit::Square(unit - 18),
+        }
+    }
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Unit::Column(col) => write!(f, "column {}", col + 1),
+            Unit::Row(row) => write!(f, "row {}", row + 1),
+            Unit::Square(square) => write!(f, "square {:?}", SquareIdx::from_idx(*square)),
+        }
+    }
+}
+
+/// Solved cells per unit, from [`Board::units_completeness`], each array indexed like [`Board::iter_row`] and friends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnitCompleteness {
+    pub rows: [u8; 9],
+    pub cols: [u8; 9],
+    pub squares: [u8; 9],
+}
+
+/// What [`Board::verify_detailed`] or [`Board::validate`] found wrong with a board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conflict {
+    /// `num` is in both `cells`, which share `unit`
+    Repeated {
+        unit: Unit,
+        num: u8,
+        cells: [BoardIdx; 2],
+    },
+    /// The cell has no candidates left
+    NoCandidates(BoardIdx),
+    /// One of the board's variant constraints is broken
+    Constraint,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Conflict::Repeated {
+                unit,
+                num,
+                cells: [first, second],
+            } => write!(f, "{unit} has a {num} in both {first} and {second}"),
+            Conflict::NoCandidates(idx) => write!(f, "{idx} has no candidates left"),
+            Conflict::Constraint => write!(f, "a variant constraint is broken"),
+        }
+    }
+}
+
+impl std::error::Error for Conflict {}
+
+/// Why [`Board::play_checked`] or [`Board::eliminate`] refused a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    /// The cell is a given locked in by [`Board::freeze`]
+    Frozen(BoardIdx),
+    /// The cell already holds a number
+    AlreadyPlayed(BoardIdx),
+    /// The number isn't among the cell's candidates
+    NotPossible(BoardIdx, u8),
+}
+
+impl std::fmt::Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveError::Frozen(idx) => write!(f, "{idx} is a given and can't be changed"),
+            MoveError::AlreadyPlayed(idx) => write!(f, "{idx} already holds a number"),
+            MoveError::NotPossible(idx, num) => write!(f, "{num} can't go in {idx}"),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+pub const BOARD_CELLS: usize = 9 * 9;
+
+/// The fewest clues a puzzle with a unique solution can have
+const MIN_PROPER_CLUES: usize = 17;
+
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "serde_support::BoardState",
+        try_from = "serde_support::BoardState"
+    )
+)]
+pub struct Board {
+    cells: [Cell; BOARD_CELLS],
+    played: HashSet<BoardIdx>,
+    /// Only `Some` once [`Board::track_provenance`] is called, so the usual path never pays for it
+    provenance: Option<Vec<Elimination>>,
+    /// Candidates removed through [`Board::eliminate`], oldest first, so they can be undone
+    user_eliminations: Vec<(BoardIdx, u8)>,
+    /// Variant rules on top of the classic ones, usually none
+    constraints: Vec<Constraint>,
+    /// Cells locked by [`Board::freeze`], empty until then
+    givens: HashSet<BoardIdx>,
+}
+
+/// Every cell's candidates as [`Board::render_pencilmarks`] lays them out, so they line up however many are left
+impl std::fmt::Debug for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render_pencilmarks())
+    }
+}
+
+impl std::fmt::Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(&RenderOptions::default()))
+    }
+}
+
+/// Two boards are equal when every cell has the same candidates left, however they got there: which cells were
+/// played, in what order, and the board's constraints and givens don't come into it
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.cells == other.cells
+    }
+}
+
+impl Eq for Board {}
+
+impl std::hash::Hash for Board {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.cells.hash(state);
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Board {
+    pub fn new() -> Self {
+        Self {
+            cells: array::from_fn(|_| Cell::any_possible()),
+            played: HashSet::new(),
+            provenance: None,
+            user_eliminations: Vec::new(),
+            constraints: Vec::new(),
+            givens: HashSet::new(),
+        }
+    }
+
+    /// Starts recording every candidate elimination from here on, along with its cause
+    pub fn track_provenance(&mut self) {
+        self.provenance.get_or_insert_with(Vec::new);
+    }
+
+    /// The eliminations recorded since [`Board::track_provenance`] was called, oldest first
+    pub fn provenance(&self) -> Option<&[Elimination]> {
+        self.provenance.as_deref()
+    }
+
+    /// Every candidate removal since tracking started or the log was last cleared, with its cause, oldest first
+    ///
+    /// The log lives on the board through every later move until [`Board::clear_removal_log`], and is only kept
+    /// once [`Board::track_provenance`] has turned it on; until then it's always empty, and costs nothing
+    pub fn removal_log(&self) -> &[Elimination] {
+        self.provenance().unwrap_or_default()
+    }
+
+    /// Empties [`Board::removal_log`], leaving tracking on if it was
+    pub fn clear_removal_log(&mut self) {
+        if let Some(provenance) = &mut self.provenance {
+            provenance.clear();
+        }
+    }
+
+    /// Reads a pasted grid like [`Board::from_str`](Board#impl-FromStr-for-Board), but never gives up on it
+    ///
+    /// Missing cells are left blank, anything past the ninth row or the ninth cell of a row is ignored, and
+    /// clashing clues are played anyway, leaving a board that doesn't verify
+    pub fn from_str_lenient(board_str: &str) -> Self {
+        let mut board = Board::new();
+        let rows = RowLayout::detect(board_str).rows(board_str);
+        for (row, cells) in rows.into_iter().enumerate() {
+            for (col, num) in cells.into_iter().enumerate() {
+                if let Some(num) = num {
+                    board.play_cell(BoardIdx::new(col, row), num);
+                }
+            }
+        }
+        board
+    }
+
+    /// The candidate mask of every cell, indexed `[row][col]`
+    ///
+    /// Bit `n` is set when `n` is still possible, and only bits `1` through `9` are ever set
+    pub fn to_candidate_grid(&self) -> [[u16; 9]; 9] {
+        array::from_fn(|row| {
+            array::from_fn(|col| self.get(BoardIdx::new(col, row)).important_bits())
+        })
+    }
+
+    /// How constrained every cell is, indexed `[row][col]`: `9` minus its number of candidates
+    ///
+    /// A fresh cell is `0`, a cell down to one number `8`, and a contradiction `9`
+    pub fn candidate_heatmap(&self) -> [[u8; 9]; 9] {
+        array::from_fn(|row| {
+            array::from_fn(|col| 9 - self.get(BoardIdx::new(col, row)).num_possibilities() as u8)
+        })
+    }
+
+    /// Rebuilds a board from [`Board::to_candidate_grid`] masks, keeping every candidate exactly as given
+    ///
+    /// No cell starts out played, so cells left with a single candidate are picked up by the solver as naked singles.
+    /// Bits outside `1` through `9` are ignored
+    pub fn from_candidate_grid(grid: &[[u16; 9]; 9]) -> Self {
+        let mut board = Board::new();
+        for (row, col) in iproduct!(0..9, 0..9) {
+            board.set_raw(
+                BoardIdx::new(col, row),
+                Cell(grid[row][col] & Cell::IMPORTANT),
+            );
+        }
+        board
+    }
+
+    /// Parses like [`Board::from_str`](Board#impl-FromStr-for-Board), but only accepts puzzles with exactly one solution
+    ///
+    /// A blank board is rejected up front with [`ParseError::NoGivens`] rather than as [`ParseError::NotUnique`]
+    pub fn from_str_unique(board_str: &str) -> Result<Self, ParseError> {
+        let board: Board = board_str.parse()?;
+        if board.is_blank() {
+            return Err(ParseError::NoGivens);
+        }
+        match Solver::new().solutions_up_to(board.clone(), 2).len() {
+            0 => Err(ParseError::Unsolvable),
+            1 => Ok(board),
+            _ => Err(ParseError::NotUnique),
+        }
+    }
+
+    /// Whether every cell still has all nine candidates, like a puzzle with no givens
+    ///
+    /// Any valid grid solves a blank board, so [`solve`] just returns the first one it finds
+    pub fn is_blank(&self) -> bool {
+        self.total_candidates() == BOARD_CELLS * 9
+    }
+
+    pub fn has_unique_solution(&self) -> bool {
+        Solver::new().solutions_up_to(self.clone(), 2).len() == 1
+    }
+
+    /// How many clues the puzzle has: its givens from [`Board::freeze`] if there are any, otherwise every played cell
+    ///
+    /// A board parsed from a puzzle has only its clues played, so freezing it first keeps later moves out of the count
+    pub fn clue_count(&self) -> usize {
+        match self.givens.is_empty() {
+            true => self.played.len(),
+            false => self.givens.len(),
+        }
+    }
+
+    /// Whether this is a proper puzzle: exactly one solution, from at least the 17 clues any such puzzle needs
+    ///
+    /// Too few clues is checked first, so that case skips the solver
+    pub fn is_proper(&self) -> bool {
+        self.clue_count() >= MIN_PROPER_CLUES && self.has_unique_solution()
+    }
+
+    pub fn get(&self, idx: BoardIdx) -> Cell {
+        self.cells[idx.idx]
+    }
+
+    /// The numbers still possible at `idx`, in ascending order; shorthand for the [`Cell::possibilities`] of [`Board::get`]
+    pub fn candidates(&self, idx: BoardIdx) -> Vec<u8> {
+        self.get(idx).possibilities()
+    }
+
+    /// The number played at `idx`, or `None` if it isn't played or a clash has since left it with no candidates
+    pub fn played_num(&self, idx: BoardIdx) -> Option<u8> {
+        match self.get(idx).possibilities()[..] {
+            [num] if self.played.contains(&idx) => Some(num),
+            _ => None,
+        }
+    }
+
+    fn get_mut(&mut self, idx: BoardIdx) -> &mut Cell {
+        &mut self.cells[idx.idx]
+    }
+
+    fn set_raw(&mut self, idx: BoardIdx, cell: Cell) {
+        self.cells[idx.idx] = cell;
+    }
+
+    pub fn play_cell(&mut self, idx: BoardIdx, num: u8) {
+        let peers = self.peers(idx);
+        self.play_cell_among(idx, num, &peers);
+    }
+
+    /// Like [`Board::play_cell`], but returns every candidate it removed, in row-major order, for traces and undo
+    ///
+    /// That includes the other candidates of `idx` itself as well as `num` from its peers
+    pub fn play_cell_tracked(&mut self, idx: BoardIdx, num: u8) -> Vec<(BoardIdx, u8)> {
+        let before = self.cells;
+        self.play_cell(idx, num);
+        before
+            .iter()
+            .zip(&self.cells)
+            .enumerate()
+            .flat_map(|(cell, (before, after))| {
+                before
+                    .possibilities()
+                    .into_iter()
+                    .filter(|&removed| !after.is_possible(removed))
+                    .map(move |removed| (BoardIdx::from_linear(cell), removed))
+            })
+            .collect()
+    }
+
+    /// Plays `num` at `idx`, eliminating it only from the given `peers` and any cells the board's constraints relate it to
+    fn play_cell_among(&mut self, idx: BoardIdx, num: u8, peers: &[BoardIdx]) {
+        self.played.insert(idx);
+        self.set_raw(idx, Cell::fixed(num));
+        let constraint_peers = self.constraint_peers(idx);
+
+        // Every caller passes either no peers or all 20 usual ones, which the vector path covers in one go
+        #[cfg(feature = "simd")]
+        let peers = if self.provenance.is_none() && peers.len() == 20 {
+            debug_assert!(peers.iter().all(|&peer| simd::is_usual_peer(idx, peer)));
+            simd::eliminate_from_peers(&mut self.cells, idx, num);
+            &[]
+        } else {
+            peers
+        };
+
+        for &to_update in peers.iter().chain(&constraint_peers) {
+            if let Some(provenance) = &mut self.provenance
+                && self.cells[to_update.idx].is_possible(num)
+            {
+                provenance.push(Elimination {
+                    cell: to_update,
+                    num,
+                    cause: Cause::Placement(idx),
+                });
+            }
+            self.get_mut(to_update).set_possible(num, false);
+        }
+    }
+
+    /// Removes `num` from the candidates of `idx`, returning whether it was possible before
+    fn eliminate_candidate(&mut self, idx: BoardIdx, num: u8, cause: Cause) -> bool {
+        if !self.get(idx).is_possible(num) {
+            return false;
+        }
+        if let Some(provenance) = &mut self.provenance {
+            provenance.push(Elimination {
+                cell: idx,
+                num,
+                cause,
+            });
+        }
+        self.get_mut(idx).set_possible(num, false);
+        true
+    }
+
+    /// Locks every cell played so far as a given, so [`Board::play_checked`] and [`Board::eliminate`] refuse to touch it
+    ///
+    /// Freezing again adds whatever was played since. The solver doesn't look at givens, it never changes played cells anyway
+    pub fn freeze(&mut self) {
+        self.givens.extend(self.played.iter().copied());
+    }
+
+    /// Whether `idx` was locked by [`Board::freeze`]
+    pub fn is_given(&self, idx: BoardIdx) -> bool {
+        self.givens.contains(&idx)
+    }
+
+    /// Plays `num` at `idx` like [`Board::play_cell`], but only if it's a legal move on an empty cell
+    pub fn play_checked(&mut self, idx: BoardIdx, num: u8) -> Result<(), MoveError> {
+        if self.is_given(idx) {
+            return Err(MoveError::Frozen(idx));
+        }
+        if self.played.contains(&idx) {
+            return Err(MoveError::AlreadyPlayed(idx));
+        }
+        if !self.get(idx).is_possible(num) {
+            return Err(MoveError::NotPossible(idx, num));
+        }
+        self.play_cell(idx, num);
+        Ok(())
+    }
+
+    /// Plays each move in order with [`Board::play_checked`], stopping at the first one that's refused
+    ///
+    /// The error carries the position of that move in `moves`; the moves before it stay played
+    pub fn play_sequence(&mut self, moves: &[(BoardIdx, u8)]) -> Result<(), (usize, MoveError)> {
+        for (pos, &(idx, num)) in moves.iter().enumerate() {
+            self.play_checked(idx, num).map_err(|err| (pos, err))?;
+        }
+        Ok(())
+    }
+
+    /// Solves a copy of this board with `assumptions` played first, for asking "what if this cell were that number?"
+    ///
+    /// `None` if an assumption can't be played, like one already ruled out, or if the board can't be solved with them.
+    /// The board itself is left as it is
+    pub fn solve_with_assumptions(&self, assumptions: &[(BoardIdx, u8)]) -> Option<Board> {
+        let mut board = self.clone();
+        board.play_sequence(assumptions).ok()?;
+        solve(&board)
+    }
+
+    /// Crosses `num` off the candidates of `idx`, like a player's pencil mark, returning whether it was possible before
+    ///
+    /// Other played cells are left alone and give `Ok(false)`; only givens are an error.
+    /// The solver works from the remaining candidates, so it respects this too
+    pub fn eliminate(&mut self, idx: BoardIdx, num: u8) -> Result<bool, MoveError> {
+        if self.is_given(idx) {
+            return Err(MoveError::Frozen(idx));
+        }
+        if self.played.contains(&idx) || !self.eliminate_candidate(idx, num, Cause::User) {
+            return Ok(false);
+        }
+        self.user_eliminations.push((idx, num));
+        Ok(true)
+    }
+
+    /// Restores the most recent candidate crossed off through [`Board::eliminate`], returning it
+    ///
+    /// The candidate only comes back if it's still legal: if a peer has since been played with it, it stays gone
+    pub fn undo_eliminate(&mut self) -> Option<(BoardIdx, u8)> {
+        let &(idx, num) = self.user_eliminations.last()?;
+        self.restore_candidate(idx, num);
+        Some((idx, num))
+    }
+
+    /// Takes back crossing `num` off `idx` through [`Board::eliminate`], however long ago, returning whether it came back
+    ///
+    /// Like [`Board::undo_eliminate`], a candidate a peer has since been played with stays gone
+    pub fn restore_candidate(&mut self, idx: BoardIdx, num: u8) -> bool {
+        let Some(pos) = self
+            .user_eliminations
+            .iter()
+            .rposition(|&elimination| elimination == (idx, num))
+        else {
+            return false;
+        };
+        self.user_eliminations.remove(pos);
+        let blocked = self
+            .peers(idx)
+            .iter()
+            .any(|peer| self.played.contains(peer) && self.get(*peer) == Cell::fixed(num));
+        if self.played.contains(&idx) || blocked {
+            return false;
+        }
+        self.get_mut(idx).set_possible(num, true);
+        true
+    }
+
+    /// Takes back playing `idx`, returning whether it was played, and works every candidate out again with
+    /// [`Board::recompute_candidates`], so numbers it had ruled out in its peers come back unless something else
+    /// still rules them out
+    ///
+    /// Like [`Board::play_cell`] it doesn't check for givens: a given that's unplayed stops being one
+    pub fn unplay_cell(&mut self, idx: BoardIdx) -> bool {
+        if !self.played.remove(&idx) {
+            return false;
+        }
+        self.givens.remove(&idx);
+        self.recompute_candidates();
+        true
+    }
+
+    /// Works out every unplayed cell's candidates from scratch: all nine, less whatever the played cells, the
+    /// constraints, and the candidates crossed off through [`Board::eliminate`] rule out
+    ///
+    /// Anything else that narrowed them down, like a solving technique, is forgotten, and so nothing is added to the
+    /// provenance. Played cells keep their numbers
+    pub fn recompute_candidates(&mut self) {
+        let provenance = self.provenance.take();
+        let before = std::mem::replace(&mut self.cells, array::from_fn(|_| Cell::any_possible()));
+        for idx in self.played.clone() {
+            match before[idx.idx].possibilities()[..] {
+                [num] => self.play_cell_among(idx, num, &self.peers(idx)),
+                // A played cell a contradiction emptied stays empty
+                _ => self.set_raw(idx, before[idx.idx]),
+            }
+        }
+        for &(idx, num) in &self.user_eliminations {
+            if !self.played.contains(&idx) {
+                self.cells[idx.idx].set_possible(num, false);
+            }
+        }
+        if !self.constraints.is_empty() {
+            self.propagate_constraints();
+        }
+        self.provenance = provenance;
+    }
+
+    /// Crosses off every candidate `other` has ruled out, through [`Board::eliminate`], returning how many went
+    ///
+    /// For filling in a player's pencil marks from a copy the solver worked on. Givens and other played cells are left
+    /// alone, and so is any cell `other` has no candidates in common with, so no cell is ever emptied
+    pub fn apply_eliminations_from(&mut self, other: &Board) -> usize {
+        let mut removed = 0;
+        for idx in (0..BOARD_CELLS).map(BoardIdx::from_linear) {
+            let ours = self.get(idx);
+            if self.played.contains(&idx)
+                || ours.both_possible(other.get(idx)).num_possibilities() == 0
+            {
+                continue;
+            }
+            for num in ours.possibilities() {
+                if !other.get(idx).is_possible(num) && self.eliminate(idx, num) == Ok(true) {
+                    removed += 1;
+                }
+            }
+        }
+        removed
+    }
+
+    /// The candidate removals the number played at `idx` accounts for: that number, from each unplayed peer
+    ///
+    /// This is what [`Board::play_cell`] eliminates, as data, including from the cells the board's constraints
+    /// relate `idx` to. Works the same for givens and for cells played later, and is empty if `idx` isn't played or
+    /// has been left with no candidates. Another played cell may rule out some of the same candidates
+    pub fn eliminations_from(&self, idx: BoardIdx) -> Vec<(BoardIdx, u8)> {
+        let Some(num) = self.played_num(idx) else {
+            return Vec::new();
+        };
+        self.peers(idx)
+            .into_iter()
+            .chain(self.constraint_peers(idx))
+            .unique()
+            .filter(|peer| !self.played.contains(peer))
+            .map(|peer| (peer, num))
+            .collect()
+    }
+
+    /// The 20 cells sharing a row, column, or square with `idx`, excluding `idx` itself
+    pub fn peers(&self, idx: BoardIdx) -> [BoardIdx; 20] {
+        let mut peers = self
+            .iter_square(idx.square())
+            .into_iter()
+            .chain(self.iter_col(idx.col))
+            .chain(self.iter_row(idx.row))
+            .unique()
+            .filter(|&peer| peer != idx);
+        array::from_fn(|_| peers.next().unwrap())
+    }
+
+    /// The played peers of `idx` already holding `num`, for pointing out what a move clashes with
+    ///
+    /// Cells related by the board's constraints count too, after the usual peers. Empty if `num` fits
+    pub fn conflicting_peers(&self, idx: BoardIdx, num: u8) -> Vec<BoardIdx> {
+        self.peers(idx)
+            .into_iter()
+            .chain(self.constraint_peers(idx))
+            .unique()
+            .filter(|peer| self.played.contains(peer) && self.get(*peer) == Cell::fixed(num))
+            .collect()
+    }
+
+    /// The other cells in the row, the column, and the square of `idx`, in that order, each excluding `idx` itself
+    ///
+    /// Unlike [`Board::peers`], cells are listed once per unit they share, so the square's list overlaps the other two
+    pub fn peers_by_unit(&self, idx: BoardIdx) -> ([BoardIdx; 8], [BoardIdx; 8], [BoardIdx; 8]) {
+        let others = |unit: [BoardIdx; 9]| {
+            let mut others = unit.into_iter().filter(|&peer| peer != idx);
+            array::from_fn(|_| others.next().unwrap())
+        };
+        (
+            others(self.iter_row(idx.row)),
+            others(self.iter_col(idx.col)),
+            others(self.iter_square(idx.square())),
+        )
+    }
+
+    /// Solves this board where it is, returning whether a solution was found
+    ///
+    /// Forced moves are made on `self` directly, so a board that only needs those is never cloned.
+    /// If there's no solution the board is left with whatever was played before the search ran out of options
+    pub fn solve_in_place(&mut self) -> bool {
+        Solver::new().solve_in_place(self)
+    }
+
+    /// The solved grid for this board, leaving the board itself untouched
+    ///
+    /// The read-only counterpart to [`Board::solve_in_place`], solving a clone. Nothing is cached, so callers that ask
+    /// repeatedly should keep the result or reuse a [`Solver`]
+    pub fn solution(&self) -> Option<Board> {
+        let mut board = self.clone();
+        board.solve_in_place().then_some(board)
+    }
+
+    /// Every unplayed cell with its number of candidates, fewest first
+    ///
+    /// Cells with the same count stay in row-major order
+    pub fn unsolved_by_candidates(&self) -> Vec<(BoardIdx, u8)> {
+        let mut unsolved = iproduct!(0..9, 0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+            .filter(|idx| !self.played.contains(idx))
+            .map(|idx| (idx, self.get(idx).num_possibilities() as u8))
+            .collect_vec();
+        unsolved.sort_by_key(|&(_, candidates)| candidates);
+        unsolved
+    }
+
+    /// The unit closest to completion and how many unplayed cells it has left
+    ///
+    /// Finished units are skipped unless every unit is finished. Ties go to the first unit in [`Board::units`] order
+    pub fn most_constrained_unit(&self) -> ([BoardIdx; 9], usize) {
+        let empty =
+            |unit: &[BoardIdx; 9]| unit.iter().filter(|idx| !self.played.contains(idx)).count();
+        self.units()
+            .into_iter()
+            .map(|unit| (unit, empty(&unit)))
+            .min_by_key(|&(_, empty)| (empty == 0, empty))
+            .expect("there are always 27 units")
+    }
+
+    /// How many candidates remain across all cells, played ones included
+    ///
+    /// Eliminations only ever lower this, so it's a cheap way to tell whether propagation made progress
+    pub fn total_candidates(&self) -> usize {
+        self.cells.iter().map(Cell::num_possibilities).sum()
+    }
+
+    /// A hash of every cell's candidates, for keying a cache of solves
+    ///
+    /// Boards with the same cells always get the same signature, whatever was played to get there, and the value
+    /// is stable across runs and platforms. It's FNV-1a, so it's quick but not meant to resist collisions on purpose
+    pub fn solution_signature(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+        self.cells
+            .iter()
+            .flat_map(|cell| cell.important_bits().to_le_bytes())
+            .fold(FNV_OFFSET, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+            })
+    }
+
+    /// How many cells still need to change to turn this board into `solution`:
+    /// cells that aren't played yet, plus played cells holding a different number
+    ///
+    /// `solution` must be fully solved, with a single possibility left in every cell
+    pub fn hamming_distance(&self, solution: &Board) -> usize {
+        iproduct!(0..9, 0..9)
+            .map(|(col, row)| BoardIdx::new(col, row))
+            .filter(|idx| {
+                debug_assert_eq!(solution.get(*idx).num_possibilities(), 1);
+                !self.played.contains(idx) || self.get(*idx) != solution.get(*idx)
+            })
+            .count()
+    }
+
+    /// The value of `idx` in this puzzle's solution
+    ///
+    /// Returns `None` if the puzzle doesn't have exactly one solution, since there's no single correct value to reveal
+    pub fn reveal_cell(&self, idx: BoardIdx) -> Option<u8> {
+        let [solution] = &Solver::new().solutions_up_to(self.clone(), 2)[..] else {
+            return None;
+        };
+        solution.get(idx).possibilities().first().copied()
+    }
+
+    /// Every unplayed cell where `num` is still possible, in row-major order
+    ///
+    /// Played cells are never included, even the ones holding `num` itself,
+    /// so a number that's already placed only shows up in cells outside of its peers
+    pub fn candidate_cells(&self, num: u8) -> Vec<BoardIdx> {
+        iproduct!(0..9, 0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+            .filter(|idx| !self.played.contains(idx) && self.get(*idx).is_possible(num))
+            .collect()
+    }
+
+    /// How many unplayed cells still have each number as a candidate, indexed by the number, so index `0` is always `0`
+    ///
+    /// The counts are the lengths of [`Board::candidate_cells`] for every number at once; a number with few
+    /// cells left is close to being placed
+    pub fn digit_candidate_counts(&self) -> [usize; 10] {
+        let mut counts = [0; 10];
+        for (idx, cell) in self.cells.iter().enumerate() {
+            if self.played.contains(&BoardIdx::from_linear(idx)) {
+                continue;
+            }
+            let bits = cell.important_bits();
+            for (num, count) in counts.iter_mut().enumerate().skip(1) {
+                *count += usize::from((bits >> num) & 1 == 1);
+            }
+        }
+        counts
+    }
+
+    /// Like [`Board::candidate_cells`], but only the cells of `unit`, in its order
+    pub fn cells_with_candidate(&self, unit: &[BoardIdx; 9], num: u8) -> Vec<BoardIdx> {
+        unit.iter()
+            .copied()
+            .filter(|idx| !self.played.contains(idx) && self.get(*idx).is_possible(num))
+            .collect()
+    }
+
+    /// The candidates every one of `cells` still has, as a mask with bit `n` set for `n`, like [`Cell`]
+    ///
+    /// All of `1`-`9` for no cells at all
+    pub fn common_candidates(&self, cells: &[BoardIdx]) -> u16 {
+        cells
+            .iter()
+            .fold(Cell::any_possible().important_bits(), |common, &idx| {
+                common & self.get(idx).important_bits()
+            })
+    }
+
+    /// Every candidate of any cell in `unit`, as a mask like [`Board::common_candidates`]
+    ///
+    /// Played cells count with their number
+    pub fn unit_candidate_union(&self, unit: &[BoardIdx; 9]) -> u16 {
+        unit.iter()
+            .fold(0, |union, &idx| union | self.get(idx).important_bits())
+    }
+
+    /// How many cells of `unit` can hold each number, indexed by the number, so index `0` is always `0`
+    ///
+    /// Played cells count with their number, so a number placed in the unit has a count of `1`
+    pub fn unit_candidate_counts(&self, unit: &[BoardIdx; 9]) -> [u8; 10] {
+        let mut counts = [0; 10];
+        for &idx in unit {
+            let bits = self.get(idx).important_bits();
+            for (num, count) in counts.iter_mut().enumerate().skip(1) {
+                *count += ((bits >> num) & 1) as u8;
+            }
+        }
+        counts
+    }
+
+    /// Whether the candidate state agrees with the played cells, which is stricter than [`Board::verify_detailed`]
+    ///
+    /// Besides passing `verify`, no cell may still list a number that one of its played peers holds.
+    /// Useful for catching corrupt candidate state imported from elsewhere
+    pub fn is_consistent(&self) -> bool {
+        self.verify().is_ok()
+            && self.played.iter().all(|&played| {
+                let num = self.get(played).possibilities()[0];
+                self.peers(played)
+                    .iter()
+                    .all(|&peer| !self.get(peer).is_possible(num))
+            })
+    }
+
+    /// Narrows every cell down to the candidates it has in both this board and `other`, for combining what
+    /// independent techniques worked out on copies of the same board
+    ///
+    /// Cells played on `other` count as played here too. Errors with the first problem [`Board::verify_detailed`]
+    /// finds if the two analyses contradict each other, leaving the board intersected anyway
+    pub fn intersect_candidates(&mut self, other: &Board) -> Result<(), Conflict> {
+        for (cell, &theirs) in self.cells.iter_mut().zip(&other.cells) {
+            *cell = cell.both_possible(theirs);
+        }
+        self.played.extend(other.played.iter().copied());
+        self.verify_detailed()
+    }
+
+    /// How many cells hold each number, with cells not down to a single number counted at index 0
+    pub fn digit_counts(&self) -> [u8; 10] {
+        let mut counts = [0; 10];
+        for cell in &self.cells {
+            match cell.possibilities()[..] {
+                [num] => counts[num as usize] += 1,
+                _ => counts[0] += 1,
+            }
+        }
+        counts
+    }
+
+    /// How many cells of each unit are down to a single number, from 0 to 9
+    pub fn units_completeness(&self) -> UnitCompleteness {
+        let solved = self.units().map(|unit| {
+            unit.iter()
+                .filter(|&&idx| self.get(idx).num_possibilities() == 1)
+                .count() as u8
+        });
+        UnitCompleteness {
+            cols: array::from_fn(|col| solved[col]),
+            rows: array::from_fn(|row| solved[9 + row]),
+            squares: array::from_fn(|square| solved[18 + square]),
+        }
+    }
+
+    /// The Shannon entropy of the unplayed cells in bits, treating each as equally likely to be any of its candidates
+    ///
+    /// That's the sum of `log2` of each cell's candidate count, so 0 for a finished board. It's only a heuristic for
+    /// ranking: more entropy roughly means more left to work out, but it knows nothing about how hard the logic is
+    pub fn entropy(&self) -> f64 {
+        iproduct!(0..9, 0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+            .filter(|idx| !self.played.contains(idx))
+            .map(|idx| (self.get(idx).num_possibilities().max(1) as f64).log2())
+            // `sum` of nothing is -0.0, which would print as "-0" for a finished board
+            .fold(0.0, |total, bits| total + bits)
+    }
+
+    /// Whether the board is a complete, valid grid
+    ///
+    /// On top of `verify`, every number must appear exactly nine times, as a cheap last check against corrupt boards
+    pub fn is_solved(&self) -> bool {
+        self.verify().is_ok() && self.digit_counts() == [0, 9, 9, 9, 9, 9, 9, 9, 9, 9]
+    }
+
+    pub(crate) fn verify(&self) -> Result<(), ()> {
+        self.verify_detailed().map_err(|_| ())
+    }
+
+    /// Checks that every cell has a candidate left and no unit has a number twice, saying what's wrong if not
+    ///
+    /// Reports the first problem in [`Board::units`] order
+    pub fn verify_detailed(&self) -> Result<(), Conflict> {
+        for (unit, cells) in self.units().iter().enumerate() {
+            let mut seen: [Option<BoardIdx>; 10] = [None; 10];
+            for &idx in cells {
+                match self.get(idx).possibilities()[..] {
+                    [] => return Err(Conflict::NoCandidates(idx)),
+                    [num] => {
+                        if let Some(first) = seen[num as usize] {
+                            return Err(Conflict::Repeated {
+                                unit: Unit::from_idx(unit),
+                                num,
+                                cells: [first, idx],
+                            });
+                        }
+                        seen[num as usize] = Some(idx);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        self.verify_constraints().map_err(|()| Conflict::Constraint)
+    }
+
+    /// Like [`Board::verify_detailed`], but finds every problem instead of stopping at the first, so a UI can point
+    /// out all the offending cells at once
+    ///
+    /// Each cell with no candidates left comes first, once, in row-major order. Then, in [`Board::units`] order, each
+    /// repeat of a number pairs the cell it's repeated in with the first cell of the unit to have it. A broken variant
+    /// constraint adds one [`Conflict::Constraint`] at the end
+    pub fn validate(&self) -> Result<(), Vec<Conflict>> {
+        let mut conflicts = (0..BOARD_CELLS)
+            .map(BoardIdx::from_linear)
+            .filter(|&idx| self.get(idx).num_possibilities() == 0)
+            .map(Conflict::NoCandidates)
+            .collect::<Vec<_>>();
+        for (unit, cells) in self.units().iter().enumerate() {
+            let mut seen: [Option<BoardIdx>; 10] = [None; 10];
+            for &idx in cells {
+                let [num] = self.get(idx).possibilities()[..] else {
+                    continue;
+                };
+                match seen[num as usize] {
+                    Some(first) => conflicts.push(Conflict::Repeated {
+                        unit: Unit::from_idx(unit),
+                        num,
+                        cells: [first, idx],
+                    }),
+                    None => seen[num as usize] = Some(idx),
+                }
+            }
+        }
+        if self.verify_constraints().is_err() {
+            conflicts.push(Conflict::Constraint);
+        }
+        match conflicts.is_empty() {
+            true => Ok(()),
+            false => Err(conflicts),
+        }
+    }
+
+    /// Like [`Board::verify`], but only checks the row, column, and square of `idx`
+    ///
+    /// After playing a single cell those are the only units that can newly hold a repeated number, so this is
+    /// enough when the rest of the board was already verified
+    fn verify_around(&self, idx: BoardIdx) -> Result<(), ()> {
+        self.verify_units(&[
+            self.iter_col(idx.col),
+            self.iter_row(idx.row),
+            self.iter_square(idx.square()),
+        ])?;
+        self.verify_constraints()
+    }
+
+    fn verify_units(&self, units: &[[BoardIdx; 9]]) -> Result<(), ()> {
+        for seq in units {
+            let mut seen = HashSet::new();
+            for &elem in seq {
+                match self.get(elem).possibilities()[..] {
+                    [] => return Err(()),
+                    [num] if !seen.insert(num) => return Err(()),
+                    _ => (),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// All 27 units: the columns, then the rows, then the squares
+    pub fn units(&self) -> [[BoardIdx; 9]; 27] {
+        array::from_fn(|unit| match unit {
+            0..9 => self.iter_col(unit),
+            9..18 => self.iter_row(unit - 9),
+            _ => self.iter_square(SquareIdx::from_idx(unit - 18)),
+        })
+    }
+
+    pub fn iter_square(&self, square: SquareIdx) -> [BoardIdx; 9] {
+        let offset = square.to_topleft_cell();
+        array::from_fn(|idx| {
+            let col = idx / 3;
+            let row = idx % 3;
+            BoardIdx::new(col + offset.col, row + offset.row)
+        })
+    }
+
+    /// 0-9, left to right
+    pub fn iter_col(&self, col: usize) -> [BoardIdx; 9] {
+        array::from_fn(|row| BoardIdx::new(col, row))
+    }
+
+    /// 0-9, top to bottom
+    pub fn iter_row(&self, row: usize) -> [BoardIdx; 9] {
+        array::from_fn(|col| BoardIdx::new(col, row))
+    }
+}
+
+/// Lookup tables precomputed once and shared across many solves
+pub struct Solver {
+    peers: [[BoardIdx; 20]; BOARD_CELLS],
+    units: [[BoardIdx; 9]; 27],
+    options: SolveOptions,
+}
+
+/// How the search picks the next cell to guess in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BranchHeuristic {
+    /// The cell with the fewest candidates, the first one found on ties
+    #[default]
+    MostConstrained,
+    /// The cell with the fewest candidates, preferring the earliest unfinished square on ties
+    ///
+    /// Fills the board roughly square by square, which animates nicely. It never changes whether a solution is found
+    RegionOrder,
+}
+
+/// What [`Solver::solve`] does once its search is [`SolveOptions::max_depth`] guesses deep
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DepthLimit {
+    /// Carries on from there with the iterative search behind [`Solver::solutions`], which doesn't grow the stack
+    /// but runs on one thread. It still stops once the cancel flag of [`Solver::solve_until`] is set
+    #[default]
+    Iterate,
+    /// Gives up, which [`Solver::try_solve`] reports as [`TooDeep`]
+    Fail,
+}
+
+/// A callback for [`SolveOptions::progress`], given a rough fraction of the search done so far, from 0 to 1
+#[derive(Clone)]
+pub struct Progress(Arc<dyn Fn(f64) + Send + Sync>);
+
+impl Progress {
+    pub fn new(report: impl Fn(f64) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(report))
+    }
+}
+
+impl std::fmt::Debug for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Progress(..)")
+    }
+}
+
+/// Counts off the candidates of a search's first guess as their branches finish, reporting to a [`Progress`]
+///
+/// Reports happen under a lock, so they arrive in order even when branches run in parallel
+struct ProgressTracker<'a> {
+    progress: &'a Progress,
+    branches: usize,
+    done: Mutex<usize>,
+}
+
+impl<'a> ProgressTracker<'a> {
+    fn new(progress: &'a Progress, branches: usize) -> Self {
+        Self {
+            progress,
+            branches,
+            done: Mutex::new(0),
+        }
+    }
+
+    fn branch_done(&self) {
+        let mut done = self.done.lock().unwrap();
+        *done += 1;
+        (self.progress.0)(*done as f64 / self.branches as f64);
+    }
+
+    /// The search is over, whether or not every branch was tried
+    fn finish(&self) {
+        let _done = self.done.lock().unwrap();
+        (self.progress.0)(1.0);
+    }
+}
+
+/// How many guesses deep [`Solver::solve`] recurses by default: as many as there are cells,
+/// so a default solve always stays recursive, since each guess fills a cell
+pub const DEFAULT_MAX_DEPTH: usize = BOARD_CELLS;
+
+/// How many guesses deep [`Solver::solve`] hands branches to rayon by default: as many as there are cells, so every
+/// guess does, as it did before [`SolveOptions::parallel_depth`] existed
+///
+/// Deep branches are usually settled in microseconds, so a lower depth like 2 can save queueing them on other
+/// threads. Whether it does depends on the machine, and it hasn't been measured on more than one core
+pub const DEFAULT_PARALLEL_DEPTH: usize = BOARD_CELLS;
+
+/// Knobs for how [`Solver`] searches
+#[derive(Debug, Clone)]
+pub struct SolveOptions {
+    pub heuristic: BranchHeuristic,
+    /// How many guesses deep the recursive search in [`Solver::solve`] goes before `past_max_depth` takes over,
+    /// [`DEFAULT_MAX_DEPTH`] unless set
+    pub max_depth: usize,
+    pub past_max_depth: DepthLimit,
+    /// How many guesses deep the search still tries branches in parallel; any deeper and each thread tries its
+    /// branches in turn. [`DEFAULT_PARALLEL_DEPTH`] unless set, and 0 searches as if `sequential` were set
+    pub parallel_depth: usize,
+    /// Search on the calling thread only, never handing branches to rayon, so the same board always gives the same
+    /// solution. Off by default
+    pub sequential: bool,
+    /// Shuffles the order each guess's candidates are tried in, the same way every run, for reproducing a reported
+    /// search exactly. Setting it also makes the search sequential, as reproducible runs can't be parallel ones
+    pub seed: Option<u64>,
+    /// Told how far [`Solver::solve`] and [`Solver::solve_in_place`] have got, after each candidate of the first guess
+    /// is done with, then with 1 when the search ends
+    ///
+    /// Only a rough estimate: it's the fraction of those candidates tried, and one branch can take far longer than
+    /// the rest. The values never go down. A puzzle solved without guessing never reports. `None` by default
+    pub progress: Option<Progress>,
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        Self {
+            heuristic: BranchHeuristic::default(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            past_max_depth: DepthLimit::default(),
+            parallel_depth: DEFAULT_PARALLEL_DEPTH,
+            sequential: false,
+            seed: None,
+            progress: None,
+        }
+    }
+}
+
+/// The search reached [`SolveOptions::max_depth`] with [`DepthLimit::Fail`] set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooDeep;
+
+impl std::fmt::Display for TooDeep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the search needed more guesses than its depth limit allows"
+        )
+    }
+}
+
+impl std::error::Error for TooDeep {}
+
+/// Why [`Solver::solve_or_explain`] found no solution: the first dead end its search ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadEnd {
+    /// What was wrong with the board at the dead end
+    pub conflict: Conflict,
+    /// The guess that led there, or `None` if the forced moves alone break the puzzle
+    pub after: Option<(BoardIdx, u8)>,
+}
+
+impl std::fmt::Display for DeadEnd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.after {
+            Some((idx, num)) => write!(f, "{} after placing {idx}={num}", self.conflict),
+            None => write!(f, "{} before any guess", self.conflict),
+        }
+    }
+}
+
+impl std::error::Error for DeadEnd {}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Self::with_options(SolveOptions::default())
+    }
+
+    pub fn with_options(options: SolveOptions) -> Self {
+        let board = Board::new();
+        Self {
+            peers: array::from_fn(|idx| board.peers(BoardIdx::from_linear(idx))),
+            units: board.units(),
+            options,
+        }
+    }
+
+    /// Whether to try branches one at a time, which [`SolveOptions::seed`] implies
+    fn is_sequential(&self) -> bool {
+        self.options.sequential || self.options.seed.is_some()
+    }
+
+    /// Whether the branches of a board reached after `depth` guesses are tried in parallel
+    fn is_parallel_at(&self, depth: usize) -> bool {
+        !self.is_sequential() && depth < self.options.parallel_depth
+    }
+
+    /// The candidates of `next` in the order the search tries them: ascending, or shuffled by [`SolveOptions::seed`]
+    ///
+    /// The shuffle only depends on the seed, the cell, and how many cells are played, so reruns take the same path
+    fn branch_order(&self, board: &Board, next: BoardIdx) -> Vec<u8> {
+        let mut possibilities = board.get(next).possibilities();
+        if let Some(seed) = self.options.seed {
+            let node = (board.played.len() * BOARD_CELLS + next.idx) as u64;
+            Rng::new(seed ^ node.wrapping_mul(0x9e3779b97f4a7c15)).shuffle(&mut possibilities);
+        }
+        possibilities
+    }
+
+    /// Orders cells with the same number of candidates for [`Solver::expand`], lowest first
+    fn tiebreak(&self, idx: BoardIdx) -> usize {
+        match self.options.heuristic {
+            BranchHeuristic::MostConstrained => 0,
+            BranchHeuristic::RegionOrder => idx.square().to_idx(),
+        }
+    }
+
+    /// Plays every naked single, then reports what's left to do
+    fn expand(&self, board: &mut Board) -> Node {
+        self.expand_after(board, None)
+    }
+
+    /// Like [`Solver::expand`], but when `last_move` is given, only verifies around it instead of the whole board
+    ///
+    /// That's only safe when `board` is a verified node with just `last_move` played on top of it
+    fn expand_after(&self, board: &mut Board, last_move: Option<BoardIdx>) -> Node {
+        if !board.constraints.is_empty() {
+            board.propagate_constraints();
+        }
+        let verified = match last_move {
+            Some(idx) => board.verify_around(idx),
+            None => board
+                .verify_units(&self.units)
+                .and_then(|()| board.verify_constraints()),
+        };
+        if verified.is_err() {
+            return Node::Contradiction;
+        }
+
+        let mut least_possibilities_cell = None;
+        let mut least_possibilities = (usize::MAX, usize::MAX);
+
+        for (col, row) in iproduct!(0..9, 0..9) {
+            let idx = BoardIdx::new(col, row);
+            if board.played.contains(&idx) {
+                continue;
+            }
+
+            let possibilities = board.get(idx).num_possibilities();
+            if possibilities == 0 {
+                return Node::Contradiction;
+            }
+            if possibilities == 1 {
+                let num = board.get(idx).possibilities()[0];
+                board.play_cell_among(idx, num, &self.peers[idx.idx]);
+                continue;
+            }
+            let possibilities = (possibilities, self.tiebreak(idx));
+            if least_possibilities > possibilities {
+                least_possibilities = possibilities;
+                least_possibilities_cell = Some(idx);
+            }
+        }
+
+        match least_possibilities_cell {
+            // A guess is the last resort, and whatever the logic changes has to be verified all over again
+            Some(_) if techniques::propagate(board) => self.expand_after(board, None),
+            Some(next) => Node::Branch(next),
+            // This means all cells are played, thus it's solved
+            None => Node::Solved,
+        }
+    }
+
+    pub fn solve(&self, board: Board) -> Option<Board> {
+        self.solve_until(board, &NEVER_CANCELLED)
+    }
+
+    /// Like [`Solver::solve`], but gives up with `None` as soon as `cancel` is set
+    ///
+    /// Also `None` if the search goes too deep with [`DepthLimit::Fail`] set; see [`Solver::try_solve`] to tell those apart
+    pub fn solve_until(&self, board: Board, cancel: &AtomicBool) -> Option<Board> {
+        self.search(board, None, 0, cancel).ok().flatten()
+    }
+
+    /// Like [`Solver::solve`], but errors instead of returning `None` when the search goes too deep
+    /// with [`DepthLimit::Fail`] set
+    pub fn try_solve(&self, board: Board) -> Result<Option<Board>, TooDeep> {
+        self.search(board, None, 0, &NEVER_CANCELLED)
+    }
+
+    /// `depth` is how many guesses were made to reach `board`
+    fn search(
+        &self,
+        mut board: Board,
+        last_move: Option<BoardIdx>,
+        depth: usize,
+        cancel: &AtomicBool,
+    ) -> Result<Option<Board>, TooDeep> {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+
+        let next = match self.expand_after(&mut board, last_move) {
+            Node::Contradiction => return Ok(None),
+            Node::Solved => return Ok(Some(board)),
+            Node::Branch(next) => next,
+        };
+
+        // A cell left with one candidate by the forced moves isn't a guess, and doesn't count towards the depth
+        let possibilities = self.branch_order(&board, next);
+        let guessing = possibilities.len() > 1;
+        if guessing && depth >= self.options.max_depth {
+            return match self.options.past_max_depth {
+                DepthLimit::Iterate => Ok(self.solutions_until(board, cancel).next()),
+                DepthLimit::Fail => Err(TooDeep),
+            };
+        }
+        let tracker = self.first_guess_tracker(depth, &possibilities);
+        let parallel = self.is_parallel_at(depth);
+        let depth = depth + usize::from(guessing);
+
+        let branch = |&possibility: &u8| {
+            let mut new_board = board.clone();
+            new_board.play_cell_among(next, possibility, &self.peers[next.idx]);
+
+            // Running too deep ends the whole search, just like a solution does
+            let found = match self.search(new_board, Some(next), depth, cancel) {
+                Ok(None) => None,
+                found => Some(found),
+            };
+            if let Some(tracker) = &tracker {
+                tracker.branch_done();
+            }
+            found
+        };
+        let found = match parallel {
+            false => possibilities.iter().find_map(branch),
+            true => possibilities.par_iter().find_map_any(branch),
+        };
+        if let Some(tracker) = &tracker {
+            tracker.finish();
+        }
+        found.unwrap_or(Ok(None))
+    }
+
+    /// A [`ProgressTracker`] for the candidates of the guess about to be made, if it's the first one and
+    /// [`SolveOptions::progress`] is set
+    fn first_guess_tracker(
+        &self,
+        depth: usize,
+        possibilities: &[u8],
+    ) -> Option<ProgressTracker<'_>> {
+        let progress = self.options.progress.as_ref()?;
+        (depth == 0 && possibilities.len() > 1)
+            .then(|| ProgressTracker::new(progress, possibilities.len()))
+    }
+
+    /// Like [`Solver::solve`], but also measures the search tree
+    ///
+    /// Searches on a single thread so the numbers are the same from run to run
+    pub fn solve_with_stats(&self, board: Board) -> (Option<Board>, SolveStats) {
+        let mut stats = SolveStats::default();
+        let solution = self.solve_counting(board, None, 0, &mut stats);
+        (solution, stats)
+    }
+
+    fn solve_counting(
+        &self,
+        mut board: Board,
+        last_move: Option<BoardIdx>,
+        depth: usize,
+        stats: &mut SolveStats,
+    ) -> Option<Board> {
+        stats.nodes += 1;
+        stats.max_depth = stats.max_depth.max(depth);
+        let played = board.played.len();
+        let node = self.expand_after(&mut board, last_move);
+        stats.propagated += board.played.len() - played;
+        let next = match node {
+            Node::Contradiction => {
+                stats.first_backtrack_depth.get_or_insert(depth);
+                return None;
+            }
+            Node::Solved => {
+                stats.solution_depth = Some(depth);
+                return Some(board);
+            }
+            Node::Branch(next) => next,
+        };
+
+        let possibilities = board.get(next).possibilities();
+        let guessing = possibilities.len() > 1;
+        stats.branch_nodes += 1;
+        stats.branches += possibilities.len();
+        possibilities.into_iter().find_map(|possibility| {
+            let mut new_board = board.clone();
+            new_board.play_cell_among(next, possibility, &self.peers[next.idx]);
+            match guessing {
+                true => stats.guesses += 1,
+                false => stats.propagated += 1,
+            }
+            let solution = self.solve_counting(new_board, Some(next), depth + 1, stats);
+            if solution.is_none() && guessing {
+                stats.backtracks += 1;
+            }
+            solution
+        })
+    }
+
+    /// Like [`Solver::solve`], but tells `on_event` about every cell the search fills in and every guess it makes
+    /// or takes back, for driving a visualizer
+    ///
+    /// Branches run in parallel just as in [`Solver::solve`], so `on_event` is called from several threads at once,
+    /// and events from different branches arrive interleaved. Only the events of any one branch come in order; with
+    /// [`SolveOptions::sequential`] set they all do
+    pub fn solve_with_progress(
+        &self,
+        board: Board,
+        on_event: &(dyn Fn(SolveEvent) + Sync),
+    ) -> Option<Board> {
+        self.solve_reporting(board, None, 0, on_event)
+    }
+
+    fn solve_reporting(
+        &self,
+        mut board: Board,
+        last_move: Option<BoardIdx>,
+        depth: usize,
+        on_event: &(dyn Fn(SolveEvent) + Sync),
+    ) -> Option<Board> {
+        let played = board.played.clone();
+        let node = self.expand_after(&mut board, last_move);
+        for idx in (0..BOARD_CELLS).map(BoardIdx::from_linear) {
+            if board.played.contains(&idx)
+                && !played.contains(&idx)
+                && let [num] = board.get(idx).possibilities()[..]
+            {
+                on_event(SolveEvent::Fixed { idx, num });
+            }
+        }
+        let next = match node {
+            Node::Contradiction => return None,
+            Node::Solved => return Some(board),
+            Node::Branch(next) => next,
+        };
+
+        let possibilities = self.branch_order(&board, next);
+        let guessing = possibilities.len() > 1;
+        let parallel = self.is_parallel_at(depth);
+        let depth = depth + usize::from(guessing);
+        let branch = |&num: &u8| {
+            let mut new_board = board.clone();
+            new_board.play_cell_among(next, num, &self.peers[next.idx]);
+            on_event(match guessing {
+                true => SolveEvent::Guess { idx: next, num },
+                false => SolveEvent::Fixed { idx: next, num },
+            });
+            let solution = self.solve_reporting(new_board, Some(next), depth, on_event);
+            if solution.is_none() && guessing {
+                on_event(SolveEvent::Backtrack { idx: next, num });
+            }
+            solution
+        };
+        match parallel {
+            false => possibilities.iter().find_map(branch),
+            true => possibilities.par_iter().find_map_any(branch),
+        }
+    }
+
+    /// Like [`Solver::solve`], but says why when there's no solution
+    ///
+    /// Searches on a single thread so the dead end reported is the same from run to run. Every branch of an
+    /// unsolvable puzzle ends in one; this reports the first, as found by trying candidates in order
+    pub fn solve_or_explain(&self, board: Board) -> Result<Board, DeadEnd> {
+        let mut first = None;
+        self.solve_explaining(board, None, &mut first)
+            .ok_or_else(|| first.expect("a failed search hits at least one dead end"))
+    }
+
+    fn solve_explaining(
+        &self,
+        mut board: Board,
+        last_move: Option<(BoardIdx, u8)>,
+        first: &mut Option<DeadEnd>,
+    ) -> Option<Board> {
+        let next = match self.expand_after(&mut board, last_move.map(|(idx, _)| idx)) {
+            Node::Contradiction => {
+                if first.is_none() {
+                    let conflict = board
+                        .verify_detailed()
+                        .expect_err("expand only gives up on a board that doesn't verify");
+                    *first = Some(DeadEnd {
+                        conflict,
+                        after: last_move,
+                    });
+                }
+                return None;
+            }
+            Node::Solved => return Some(board),
+            Node::Branch(next) => next,
+        };
+
+        board
+            .get(next)
+            .possibilities()
+            .into_iter()
+            .find_map(|possibility| {
+                let mut new_board = board.clone();
+                new_board.play_cell_among(next, possibility, &self.peers[next.idx]);
+                self.solve_explaining(new_board, Some((next, possibility)), first)
+            })
+    }
+
+    /// Like [`Solver::solve`], but fills in `board` itself instead of returning a new one
+    pub fn solve_in_place(&self, board: &mut Board) -> bool {
+        let next = match self.expand(board) {
+            Node::Contradiction => return false,
+            Node::Solved => return true,
+            Node::Branch(next) => next,
+        };
+
+        let possibilities = self.branch_order(board, next);
+        let tracker = self.first_guess_tracker(0, &possibilities);
+        let depth = usize::from(possibilities.len() > 1);
+        let branch = |&possibility: &u8| {
+            let mut new_board = board.clone();
+            new_board.play_cell_among(next, possibility, &self.peers[next.idx]);
+            let solution = self
+                .search(new_board, None, depth, &NEVER_CANCELLED)
+                .ok()
+                .flatten();
+            if let Some(tracker) = &tracker {
+                tracker.branch_done();
+            }
+            solution
+        };
+        let solution = match self.is_parallel_at(0) {
+            false => possibilities.iter().find_map(branch),
+            true => possibilities.par_iter().find_map_any(branch),
+        };
+        if let Some(tracker) = &tracker {
+            tracker.finish();
+        }
+        match solution {
+            Some(solution) => {
+                *board = solution;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Finds up to `limit` solutions, searching sequentially
+    ///
+    /// Every branch fixes a different number in the same cell, so the solutions found are always distinct
+    ///
+    /// Every solution found is kept in full, so use [`Solver::solutions`] to go through more than a handful
+    pub fn solutions_up_to(&self, board: Board, limit: usize) -> Vec<Board> {
+        let mut found = Vec::new();
+        self.collect_solutions(board, None, limit, &mut found);
+        found
+    }
+
+    /// Like [`Solver::solutions_up_to`], but also says whether there were more than `max` solutions
+    ///
+    /// The `bool` is `true` when the list was cut short. Finding that out takes looking for one extra solution
+    pub fn solutions_capped(&self, board: Board, max: usize) -> (Vec<Board>, bool) {
+        let mut found = self.solutions_up_to(board, max.saturating_add(1));
+        let truncated = found.len() > max;
+        found.truncate(max);
+        (found, truncated)
+    }
+
+    fn collect_solutions(
+        &self,
+        mut board: Board,
+        last_move: Option<BoardIdx>,
+        limit: usize,
+        found: &mut Vec<Board>,
+    ) {
+        if found.len() >= limit {
+            return;
+        }
+
+        let next = match self.expand_after(&mut board, last_move) {
+            Node::Contradiction => return,
+            Node::Solved => return found.push(board),
+            Node::Branch(next) => next,
+        };
+
+        for possibility in board.get(next).possibilities() {
+            let mut new_board = board.clone();
+            new_board.play_cell_among(next, possibility, &self.peers[next.idx]);
+            self.collect_solutions(new_board, Some(next), limit, found);
+        }
+    }
+
+    /// The solution whose numbers, read row by row, sort first, or `None` if there isn't one
+    ///
+    /// Guesses go in row-major order, smallest number first, so the first solution found is the smallest without
+    /// comparing any. That ignores [`SolveOptions::heuristic`], so it's slower than [`Solver::solve`].
+    /// Branches still run in parallel unless the search is sequential, but the earliest one to succeed always wins,
+    /// so the answer is the same on every run and with any number of threads
+    pub fn lexicographically_smallest(&self, board: Board) -> Option<Board> {
+        self.smallest_from(board, None)
+    }
+
+    fn smallest_from(&self, mut board: Board, last_move: Option<BoardIdx>) -> Option<Board> {
+        match self.expand_after(&mut board, last_move) {
+            Node::Contradiction => return None,
+            Node::Solved => return Some(board),
+            Node::Branch(_) => (),
+        }
+
+        // Forced moves hold in every solution, so only the first cell that's still open decides the order
+        let next = (0..BOARD_CELLS)
+            .map(BoardIdx::from_linear)
+            .find(|idx| !board.played.contains(idx))
+            .expect("a board that needs a guess has an unplayed cell");
+        let branch = |&possibility: &u8| {
+            let mut new_board = board.clone();
+            new_board.play_cell_among(next, possibility, &self.peers[next.idx]);
+            self.smallest_from(new_board, Some(next))
+        };
+        let possibilities = board.get(next).possibilities();
+        match self.is_sequential() {
+            true => possibilities.iter().find_map(branch),
+            false => possibilities.par_iter().find_map_first(branch),
+        }
+    }
+
+    /// How many solutions `board` has, counting no further than `cap`, so a `cap` of 2 asks whether it's unique
+    ///
+    /// Each guess splits the search on different numbers for the same cell, so no two branches can finish the same
+    /// grid and every solution is counted once. Runs on [`Solver::solutions`], so a high `cap` doesn't keep every
+    /// solution around
+    pub fn count_solutions(&self, board: Board, cap: usize) -> usize {
+        self.solutions(board).take(cap).count()
+    }
+
+    /// Every solution of `board`, found one at a time as the iterator is advanced
+    ///
+    /// Meant for boards with lots of solutions: branches still to be explored are kept as just the cells played on
+    /// top of `board`, and a full board is only rebuilt from those when it's explored.
+    /// Rebuilding costs some speed, so [`Solver::solutions_up_to`] is faster when only a few are needed
+    pub fn solutions(&self, board: Board) -> Solutions<'_> {
+        self.solutions_until(board, &NEVER_CANCELLED)
+    }
+
+    /// Like [`Solver::solutions`], but running out as soon as `cancel` is set
+    fn solutions_until<'a>(&'a self, board: Board, cancel: &'a AtomicBool) -> Solutions<'a> {
+        Solutions {
+            solver: self,
+            base: board,
+            pending: vec![Vec::new()],
+            cancel,
+        }
+    }
+}
+
+/// The flag searches that can't be cancelled check
+pub(crate) static NEVER_CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// The iterator returned by [`Solver::solutions`]
+pub struct Solutions<'a> {
+    solver: &'a Solver,
+    base: Board,
+    /// The cells played on top of the base board in each branch still to be explored, the next one last
+    pending: Vec<Vec<(BoardIdx, u8)>>,
+    /// Once set, the iterator acts as if there were no solutions left
+    cancel: &'a AtomicBool,
+}
+
+impl Iterator for Solutions<'_> {
+    type Item = Board;
+
+    fn next(&mut self) -> Option<Board> {
+        while let Some(moves) = self.pending.pop() {
+            if self.cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            // Candidates only ever go away by playing a peer or by logic that expanding redoes, so replaying the moves
+            // in any order gives back the same board
+            let mut board = self.base.clone();
+            for &(idx, num) in &moves {
+                board.play_cell_among(idx, num, &self.solver.peers[idx.idx]);
+            }
+
+            let next = match self.solver.expand(&mut board) {
+                Node::Contradiction => continue,
+                Node::Solved => return Some(board),
+                Node::Branch(next) => next,
+            };
+
+            // A forced move can empty a played peer, and then every branch from here is a dead end
+            let moves: Option<Vec<_>> = board
+                .played
+                .difference(&self.base.played)
+                .map(|&idx| match board.get(idx).possibilities()[..] {
+                    [num] => Some((idx, num)),
+                    _ => None,
+                })
+                .collect();
+            let Some(moves) = moves else {
+                continue;
+            };
+            for possibility in board.get(next).possibilities().into_iter().rev() {
+                let mut branch = moves.clone();
+                branch.push((next, possibility));
+                self.pending.push(branch);
+            }
+        }
+        None
+    }
+}
+
+// The search hands boards between rayon's threads and shares the solver's tables across them,
+// so this fails to compile if any of these stop being safe to do that with
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Cell>();
+    assert_send_sync::<BoardIdx>();
+    assert_send_sync::<Board>();
+    assert_send_sync::<Solver>();
+};
+
+/// Measurements of the search tree explored by [`Solver::solve_with_stats`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolveStats {
+    /// Every board the search looked at, the starting one included
+    pub nodes: usize,
+    /// Nodes where the search had to guess
+    pub branch_nodes: usize,
+    /// The possible guesses summed over every branching node, whether or not they were all tried
+    pub branches: usize,
+    /// How many guesses deep the solution was, if there is one
+    pub solution_depth: Option<usize>,
+    /// How many guesses deep the search first hit a contradiction and had to back up, or `None` if it never did
+    pub first_backtrack_depth: Option<usize>,
+    /// Candidates tried at cells that had more than one
+    pub guesses: usize,
+    /// Guesses that led to a contradiction and were taken back
+    pub backtracks: usize,
+    /// Cells filled in without a guess, by propagation or because a single candidate was left
+    pub propagated: usize,
+    /// The most guesses deep the search went, counted like [`SolveStats::solution_depth`]
+    pub max_depth: usize,
+}
+
+impl SolveStats {
+    /// How many guesses a branching node offers on average, or 0 if the search never had to guess
+    pub fn average_branching_factor(&self) -> f64 {
+        match self.branch_nodes {
+            0 => 0.0,
+            branch_nodes => self.branches as f64 / branch_nodes as f64,
+        }
+    }
+}
+
+/// Something [`Solver::solve_with_progress`] did, as told to its callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveEvent {
+    /// `idx` was filled in with `num` because it was the only candidate left, by logic rather than a guess
+    Fixed { idx: BoardIdx, num: u8 },
+    /// The search tried `num` for `idx`, one of its candidates
+    Guess { idx: BoardIdx, num: u8 },
+    /// Guessing `num` for `idx` led nowhere, so the search took it back
+    Backtrack { idx: BoardIdx, num: u8 },
+}
+
+/// The state of a search node after its forced moves have been played
+enum Node {
+    Contradiction,
+    Solved,
+    Branch(BoardIdx),
+}
+
+/// A solution of `board`, or `None` if it has none, leaving `board` as it is
+///
+/// For a board with several solutions it's whichever the parallel search finds first, which can differ from run to
+/// run; see [`solve_lexicographically_smallest`] for a fixed one
+pub fn solve(board: &Board) -> Option<Board> {
+    Solver::new().solve(board.clone())
+}
+
+/// Like [`solve`], but hands `board` straight back when it's already a finished grid
+///
+/// Only a board that still needs solving gets cloned, which saves a copy per board when checking data that's
+/// mostly solved already
+pub fn solve_borrowed(board: &Board) -> Option<Cow<'_, Board>> {
+    match board.is_solved() && board.verify_constraints().is_ok() {
+        true => Some(Cow::Borrowed(board)),
+        false => solve(board).map(Cow::Owned),
+    }
+}
+
+/// Like [`solve`], but gives up with `None` as soon as `cancel` is set, from another thread say; see
+/// [`Solver::solve_until`]
+///
+/// Every branch of the search checks `cancel` before it goes on, parallel ones included, so it stops promptly
+pub fn solve_with_cancel(board: Board, cancel: &AtomicBool) -> Option<Board> {
+    Solver::new().solve_until(board, cancel)
+}
+
+/// Like [`solve`], but says why when there's no solution, as in "R4C5 has no candidates left after placing R4C1=7"
+pub fn solve_or_explain(board: Board) -> Result<Board, DeadEnd> {
+    Solver::new().solve_or_explain(board)
+}
+
+/// At most `max` of `board`'s solutions, and whether there were more; see [`Solver::solutions_capped`]
+pub fn solutions_capped(board: Board, max: usize) -> (Vec<Board>, bool) {
+    Solver::new().solutions_capped(board, max)
+}
+
+/// How many solutions `board` has, up to `cap`; see [`Solver::count_solutions`]
+pub fn count_solutions(board: Board, cap: usize) -> usize {
+    Solver::new().count_solutions(board, cap)
+}
+
+/// The solver behind [`solutions`], built once so the iterator it returns doesn't have to borrow one
+static DEFAULT_SOLVER: LazyLock<Solver> = LazyLock::new(Solver::new);
+
+/// Every solution of `board`, each found only when the iterator is advanced; see [`Solver::solutions`]
+///
+/// Each guess splits the search on different numbers for the same cell, so no grid comes out twice. Stopping early,
+/// as with `.take(5)`, leaves the rest of the search undone
+pub fn solutions(board: Board) -> impl Iterator<Item = Board> {
+    DEFAULT_SOLVER.solutions(board)
+}
+
+pub fn solve_with(board: Board, options: SolveOptions) -> Option<Board> {
+    Solver::with_options(options).solve(board)
+}
+
+pub fn solve_with_stats(board: Board) -> (Option<Board>, SolveStats) {
+    Solver::new().solve_with_stats(board)
+}
+
+/// A solution of `board`, telling `on_event` how the search gets there; see [`Solver::solve_with_progress`]
+pub fn solve_with_progress(board: Board, on_event: &(dyn Fn(SolveEvent) + Sync)) -> Option<Board> {
+    Solver::new().solve_with_progress(board, on_event)
+}
+
+/// The first of `board`'s solutions when each is written out as its 81 numbers, so boards with several solutions
+/// give the same one every time, however many threads there are; see [`Solver::lexicographically_smallest`]
+///
+/// This is the deterministic counterpart to [`solve`], for regression tests and reproducing failures
+pub fn solve_lexicographically_smallest(board: Board) -> Option<Board> {
+    Solver::new().lexicographically_smallest(board)
+}
+
+/// Like [`solve`], but without recursing, for threads with little stack to spare
+///
+/// It's the first solution from [`Solver::solutions`], which keeps the branches it hasn't explored yet on a
+/// heap-allocated stack, each as just the moves played on top of `board`. It runs on one thread and grows no deeper
+/// however many guesses a puzzle takes. On a puzzle with a single solution it's the same answer [`solve`] gives
+pub fn solve_iterative(board: Board) -> Option<Board> {
+    Solver::new().solutions(board).next()
+}
+
+/// How many solutions [`solve_nearest`] looks through before settling for the closest one so far
+pub const NEAREST_SEARCH_LIMIT: usize = 10_000;
+
+/// The solution that's the fewest cells away from `reference`, by [`Board::hamming_distance`]
+///
+/// `reference` can be a partly filled grid, whose blank cells count against every solution equally.
+/// Only the first [`NEAREST_SEARCH_LIMIT`] solutions are compared, so on a puzzle with more than that
+/// the answer is the closest of those rather than of all of them. Returns `None` if there's no solution
+pub fn solve_nearest(board: Board, reference: &Board) -> Option<Board> {
+    let mut nearest: Option<(usize, Board)> = None;
+    for solution in Solver::new().solutions(board).take(NEAREST_SEARCH_LIMIT) {
+        let distance = reference.hamming_distance(&solution);
+        if nearest.as_ref().is_none_or(|&(best, _)| distance < best) {
+            nearest = Some((distance, solution));
+        }
+        if distance == 0 {
+            break;
+        }
+    }
+    nearest.map(|(_, solution)| solution)
+}
+
+/// Solves `board` one step at a time, yielding a snapshot after each step, for animating the search
+///
+/// Each step plays one pass of naked singles on top of the latest guess. When that's not enough, the following
+/// steps try each guess for the most constrained cell in turn, and dead ends are yielded too before backing up. The last item is the solved board, or the final
+/// dead end if there's no solution. Runs on a single thread so the steps come out in order
+pub fn solve_steps(board: Board) -> impl Iterator<Item = Board> {
+    SolveSteps {
+        solver: Solver::new(),
+        pending: vec![board],
+    }
+}
+
+pub struct SolveSteps {
+    solver: Solver,
+    /// Boards still to be explored, the next one last
+    pending: Vec<Board>,
+}
+
+impl Iterator for SolveSteps {
+    type Item = Board;
+
+    fn next(&mut self) -> Option<Board> {
+        let mut board = self.pending.pop()?;
+        match self.solver.expand(&mut board) {
+            Node::Contradiction => (),
+            Node::Solved => self.pending.clear(),
+            Node::Branch(next) => {
+                for possibility in board.get(next).possibilities().into_iter().rev() {
+                    let mut new_board = board.clone();
+                    new_board.play_cell_among(next, possibility, &self.solver.peers[next.idx]);
+                    self.pending.push(new_board);
+                }
+            }
+        }
+        Some(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_possible_toggles_each_valid_num() {
+        let mut cell = Cell::none_possible();
+        for num in 1..=9 {
+            cell.set_possible(num, true);
+            assert!(cell.is_possible(num));
+        }
+        assert_eq!(cell, Cell::any_possible());
+        for num in 1..=9 {
+            cell.set_possible(num, false);
+            assert!(!cell.is_possible(num));
+        }
+        assert_eq!(cell.num_possibilities(), 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "out of range num 0")]
+    fn set_possible_rejects_zero() {
+        Cell::any_possible().set_possible(0, false);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "out of range num 10")]
+    fn set_possible_rejects_ten() {
+        Cell::none_possible().set_possible(10, true);
+    }
+
+    fn easy() -> Board {
+        include_str!("../examples/easy1.txt").parse().unwrap()
+    }
+
+    #[test]
+    fn candidate_grid_round_trips() {
+        let mut board = easy();
+        assert!(board.eliminate(BoardIdx::new(0, 0), 5).unwrap());
+        let grid = board.to_candidate_grid();
+        let restored = Board::from_candidate_grid(&grid);
+        assert_eq!(restored.to_candidate_grid(), grid);
+        assert_eq!(
+            Solver::new().solve(restored).map(|b| b.to_line()),
+            Solver::new().solve(board).map(|b| b.to_line())
+        );
+    }
+
+    #[test]
+    fn candidate_grid_ignores_unused_bits() {
+        let mut grid = Board::new().to_candidate_grid();
+        grid[0][0] = 0b1 | 1 << 3 | 1 << 12;
+        assert_eq!(
+            Board::from_candidate_grid(&grid).candidates(BoardIdx::new(0, 0)),
+            vec![3]
+        );
+    }
+
+    #[test]
+    fn total_candidates_drops_after_each_elimination() {
+        let mut board = Board::new();
+        assert_eq!(board.total_candidates(), 81 * 9);
+        let before = board.total_candidates();
+        board.play_cell(BoardIdx::new(4, 4), 5);
+        let after_play = board.total_candidates();
+        assert!(after_play < before);
+        assert!(board.eliminate(BoardIdx::new(0, 0), 1).unwrap());
+        assert_eq!(board.total_candidates(), after_play - 1);
+        assert!(!board.eliminate(BoardIdx::new(0, 0), 1).unwrap());
+        assert_eq!(board.total_candidates(), after_play - 1);
+    }
+
+    #[test]
+    fn from_str_unique_accepts_a_proper_puzzle() {
+        let board = Board::from_str_unique(include_str!("../examples/easy1.txt")).unwrap();
+        assert_eq!(board.to_line(), easy().to_line());
+    }
+
+    #[test]
+    fn from_str_unique_rejects_several_solutions() {
+        let one_clue = format!("1{}", ".".repeat(80));
+        assert_eq!(
+            Board::from_str_unique(&one_clue),
+            Err(ParseError::NotUnique)
+        );
+    }
+
+    #[test]
+    fn from_str_unique_rejects_no_solution() {
+        assert_eq!(
+            Board::from_str_unique(include_str!("../examples/fail3.txt")),
+            Err(ParseError::Unsolvable)
+        );
+    }
+
+    #[test]
+    fn from_str_unique_rejects_a_blank_board() {
+        assert_eq!(
+            Board::from_str_unique(include_str!("../examples/empty.txt")),
+            Err(ParseError::NoGivens)
+        );
+    }
+
+    #[test]
+    fn eliminations_from_covers_constraint_peers() {
+        let mut board = Board::new();
+        board.add_constraint(Constraint::AntiKnight);
+        let idx = BoardIdx::new(4, 4);
+        board.play_cell(idx, 5);
+        let removed = board.eliminations_from(idx);
+        assert_eq!(removed.len(), 20 + 8);
+        assert!(removed.contains(&(BoardIdx::new(6, 5), 5)));
+        assert!(removed.iter().all(|&(peer, num)| num == 5 && peer != idx));
+    }
+
+    #[test]
+    fn eliminations_from_is_empty_for_an_emptied_cell() {
+        let mut board = Board::new();
+        let idx = BoardIdx::new(0, 0);
+        board.play_cell(idx, 5);
+        board.set_raw(idx, Cell::none_possible());
+        assert_eq!(board.eliminations_from(idx), vec![]);
+        assert_eq!(board.eliminations_from(BoardIdx::new(1, 1)), vec![]);
+    }
+
+    #[test]
+    fn digit_counts_of_a_solution_are_all_nine() {
+        let solution = Solver::new().solve(easy()).unwrap();
+        assert_eq!(solution.digit_counts(), [0, 9, 9, 9, 9, 9, 9, 9, 9, 9]);
+        assert!(solution.is_solved());
+    }
+
+    #[test]
+    fn digit_counts_catch_an_overrepresented_digit() {
+        let mut solution = Solver::new().solve(easy()).unwrap();
+        let idx = (0..BOARD_CELLS)
+            .map(BoardIdx::from_linear)
+            .find(|&idx| solution.get(idx) == Cell::fixed(1))
+            .unwrap();
+        solution.set_raw(idx, Cell::fixed(2));
+        assert_eq!(solution.digit_counts(), [0, 8, 10, 9, 9, 9, 9, 9, 9, 9]);
+        assert!(!solution.is_solved());
+    }
+
+    #[test]
+    fn digit_counts_put_open_cells_at_zero() {
+        let mut board = Board::new();
+        board.play_cell(BoardIdx::new(0, 0), 4);
+        assert_eq!(board.digit_counts(), [80, 0, 0, 0, 1, 0, 0, 0, 0, 0]);
+        assert!(!board.is_solved());
+    }
+
+    #[test]
+    fn candidate_heatmap_rises_as_cells_get_constrained() {
+        assert_eq!(Board::new().candidate_heatmap(), [[0; 9]; 9]);
+
+        let mut board = Board::new();
+        board.play_cell(BoardIdx::new(0, 0), 1);
+        let heatmap = board.candidate_heatmap();
+        assert_eq!(heatmap[0][0], 8);
+        // A peer lost the 1, anything else is untouched
+        assert_eq!(heatmap[0][8], 1);
+        assert_eq!(heatmap[8][0], 1);
+        assert_eq!(heatmap[1][1], 1);
+        assert_eq!(heatmap[4][4], 0);
+
+        board.set_raw(BoardIdx::new(4, 4), Cell::none_possible());
+        assert_eq!(board.candidate_heatmap()[4][4], 9);
+    }
+
+    #[test]
+    fn solution_signature_survives_a_round_trip() {
+        let solution = Solver::new().solve(easy()).unwrap();
+        let rebuilt = Board::from_candidate_grid(&solution.to_candidate_grid());
+        assert!(rebuilt.played.is_empty());
+        assert_eq!(rebuilt.solution_signature(), solution.solution_signature());
+        let reparsed: Board = solution.to_line().parse().unwrap();
+        assert_eq!(reparsed.solution_signature(), solution.solution_signature());
+        assert_ne!(easy().solution_signature(), solution.solution_signature());
+    }
+
+    #[test]
+    fn solution_signature_is_stable() {
+        assert_eq!(Board::new().solution_signature(), 0xb097ef1b566f39e6);
+    }
+
+    #[test]
+    fn clue_count_counts_the_example_clues() {
+        let extreme: Board = include_str!("../examples/extreme1.txt").parse().unwrap();
+        assert_eq!(easy().clue_count(), 38);
+        assert_eq!(extreme.clue_count(), 22);
+        assert_eq!(Board::new().clue_count(), 0);
+    }
+
+    #[test]
+    fn clue_count_leaves_out_later_moves_once_frozen() {
+        let mut puzzle: Board = include_str!("../examples/extreme1.txt").parse().unwrap();
+        puzzle.freeze();
+        let solution = solve(&puzzle).unwrap();
+        assert_eq!(solution.played.len(), BOARD_CELLS);
+        assert_eq!(solution.clue_count(), 22);
+    }
+
+    #[test]
+    fn is_proper_needs_one_solution_and_enough_clues() {
+        let extreme: Board = include_str!("../examples/extreme1.txt").parse().unwrap();
+        let unsolvable: Board = include_str!("../examples/fail3.txt").parse().unwrap();
+        assert!(easy().is_proper());
+        assert!(extreme.is_proper());
+        assert!(!unsolvable.is_proper());
+        assert!(!Board::new().is_proper());
+
+        // Fewer clues than any proper puzzle, however they're placed
+        let solution = solve(&extreme).unwrap();
+        let mut sparse = Board::new();
+        for idx in (0..MIN_PROPER_CLUES - 1).map(BoardIdx::from_linear) {
+            sparse.play_cell(idx, solution.get(idx).possibilities()[0]);
+        }
+        assert!(!sparse.is_proper());
+    }
+
+    #[test]
+    fn solve_with_assumptions() {
+        let puzzle = easy();
+        let solution = solve(&puzzle).unwrap();
+        let idx = BoardIdx::new(0, 0);
+        let right = solution.get(idx).possibilities()[0];
+        let wrong = *puzzle
+            .candidates(idx)
+            .iter()
+            .find(|&&num| num != right)
+            .unwrap();
+
+        let solved = puzzle.solve_with_assumptions(&[(idx, right)]).unwrap();
+        assert_eq!(solved.to_line(), solution.to_line());
+        // The puzzle has one solution, so any other candidate leads to a contradiction
+        assert_eq!(puzzle.solve_with_assumptions(&[(idx, wrong)]), None);
+        // Nor can a cell that already holds a number
+        assert_eq!(
+            puzzle.solve_with_assumptions(&[(BoardIdx::new(2, 0), 1)]),
+            None
+        );
+        assert_eq!(puzzle, easy());
+    }
+
+    #[test]
+    fn units_completeness_counts_solved_cells() {
+        let mut board = Board::new();
+        board.play_cell(BoardIdx::new(0, 0), 1);
+        board.play_cell(BoardIdx::new(1, 0), 2);
+        board.play_cell(BoardIdx::new(4, 4), 5);
+        assert_eq!(
+            board.units_completeness(),
+            UnitCompleteness {
+                rows: [2, 0, 0, 0, 1, 0, 0, 0, 0],
+                cols: [1, 1, 0, 0, 1, 0, 0, 0, 0],
+                squares: [2, 0, 0, 0, 1, 0, 0, 0, 0],
+            }
+        );
+
+        let solution = solve(&easy()).unwrap().units_completeness();
+        assert_eq!(solution.rows, [9; 9]);
+        assert_eq!(solution.cols, [9; 9]);
+        assert_eq!(solution.squares, [9; 9]);
+    }
+
+    #[test]
+    fn units_completeness_counts_unplayed_singles() {
+        // The last cell of a row is down to one number without being played
+        let mut board = Board::new();
+        for col in 0..8 {
+            board.play_cell(BoardIdx::new(col, 0), col as u8 + 1);
+        }
+        let completeness = board.units_completeness();
+        assert_eq!(completeness.rows[0], 9);
+        assert_eq!(completeness.cols[8], 1);
+        assert_eq!(completeness.squares[2], 3);
+    }
+
+    #[test]
+    fn cells_with_candidate_skips_played_cells() {
+        let mut board = Board::new();
+        board.play_cell(BoardIdx::new(0, 0), 1);
+        board.eliminate(BoardIdx::new(4, 1), 2).unwrap();
+        let row = board.units()[9];
+        assert_eq!(board.cells_with_candidate(&row, 1), []);
+        assert_eq!(board.cells_with_candidate(&row, 2), row[1..].to_vec());
+        let row = board.units()[10];
+        let without = row.iter().copied().filter(|idx| idx.col != 4).collect_vec();
+        assert_eq!(board.cells_with_candidate(&row, 2), without);
+    }
+
+    #[test]
+    fn common_candidates_masks_what_every_cell_has() {
+        let mut grid = Board::new().to_candidate_grid();
+        grid[0][0] = 0b0000_0110;
+        grid[0][1] = 0b0001_0100;
+        let board = Board::from_candidate_grid(&grid);
+        let (first, second) = (BoardIdx::new(0, 0), BoardIdx::new(1, 0));
+        assert_eq!(board.common_candidates(&[first, second]), 0b0000_0100);
+        assert_eq!(board.common_candidates(&[first]), 0b0000_0110);
+        assert_eq!(
+            board.common_candidates(&[]),
+            Cell::any_possible().important_bits()
+        );
+        assert_eq!(
+            board.common_candidates(&[BoardIdx::new(5, 5)]),
+            0b11_1111_1110
+        );
+    }
+
+    #[test]
+    fn solves_a_batch_across_threads() {
+        let puzzles: Vec<Board> = [
+            include_str!("../examples/easy1.txt"),
+            include_str!("../examples/extreme1.txt"),
+            include_str!("../examples/fail3.txt"),
+            include_str!("../examples/empty.txt"),
+        ]
+        .into_iter()
+        .map(|puzzle| puzzle.parse().unwrap())
+        .collect();
+        let solver = Solver::new();
+        let expected = puzzles
+            .iter()
+            .map(|puzzle| solver.solve(puzzle.clone()))
+            .collect_vec();
+
+        // One solver and the puzzles themselves shared between the threads, with each solution sent back
+        let solved = std::thread::scope(|scope| {
+            let handles = puzzles
+                .iter()
+                .map(|puzzle| scope.spawn(|| solver.solve(puzzle.clone())))
+                .collect_vec();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect_vec()
+        });
+        assert_eq!(solved, expected);
+        assert!(solved[0].is_some() && solved[2].is_none());
+    }
+
+    #[test]
+    fn solution_leaves_the_puzzle_unchanged() {
+        let puzzle = easy();
+        let solution = puzzle.solution().unwrap();
+        assert_eq!(puzzle, easy());
+        assert!(solution.is_solved());
+        assert_eq!(solution.to_line(), solve(&puzzle).unwrap().to_line());
+
+        let unsolvable: Board = include_str!("../examples/fail3.txt").parse().unwrap();
+        assert_eq!(unsolvable.solution(), None);
+        assert_eq!(
+            unsolvable,
+            include_str!("../examples/fail3.txt").parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn entropy_is_higher_for_the_harder_board() {
+        let extreme: Board = include_str!("../examples/extreme1.txt").parse().unwrap();
+        assert!(easy().entropy() < extreme.entropy());
+        assert!((Board::new().entropy() - 81.0 * 9f64.log2()).abs() < 1e-9);
+        assert_eq!(solve(&easy()).unwrap().entropy().to_string(), "0");
+    }
+
+    #[test]
+    fn entropy_drops_as_candidates_go() {
+        let mut board = easy();
+        let before = board.entropy();
+        board.fill_naked_singles_once();
+        assert!(board.entropy() < before);
+    }
+
+    #[test]
+    fn peers_by_unit_of_a_corner_and_the_center() {
+        let at = |cells: &[(usize, usize)]| {
+            cells
+                .iter()
+                .map(|&(col, row)| BoardIdx::new(col, row))
+                .collect_vec()
+        };
+        // Squares are walked column by column, so only which cells they hold is checked
+        let sorted =
+            |cells: [BoardIdx; 8]| cells.into_iter().sorted_by_key(|idx| idx.idx).collect_vec();
+        let board = Board::new();
+
+        let (row, col, square) = board.peers_by_unit(BoardIdx::new(0, 0));
+        assert_eq!(row.to_vec(), at(&(1..9).map(|col| (col, 0)).collect_vec()));
+        assert_eq!(col.to_vec(), at(&(1..9).map(|row| (0, row)).collect_vec()));
+        let corner_square = [
+            (1, 0),
+            (2, 0),
+            (0, 1),
+            (1, 1),
+            (2, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2),
+        ];
+        assert_eq!(sorted(square), at(&corner_square));
+
+        let center = BoardIdx::new(4, 4);
+        let (row, col, square) = board.peers_by_unit(center);
+        let others = [0, 1, 2, 3, 5, 6, 7, 8];
+        assert_eq!(row.to_vec(), at(&others.map(|col| (col, 4))));
+        assert_eq!(col.to_vec(), at(&others.map(|row| (4, row))));
+        let center_square = [
+            (3, 3),
+            (4, 3),
+            (5, 3),
+            (3, 4),
+            (5, 4),
+            (3, 5),
+            (4, 5),
+            (5, 5),
+        ];
+        assert_eq!(sorted(square), at(&center_square));
+        // Together they're the flat peers, with the square's cells that share the row or column counted twice
+        let split = [row, col, square]
+            .concat()
+            .into_iter()
+            .unique()
+            .sorted_by_key(|idx| idx.idx);
+        assert!(split.eq(board.peers(center).into_iter().sorted_by_key(|idx| idx.idx)));
+    }
+
+    #[test]
+    fn solutions_capped_stops_at_the_cap() {
+        let (found, truncated) = solutions_capped(Board::new(), 3);
+        assert_eq!(found.len(), 3);
+        assert!(truncated);
+        assert!(found.iter().all(Board::is_solved));
+        assert_eq!(found.iter().map(Board::to_line).unique().count(), 3);
+
+        let (found, truncated) = solutions_capped(easy(), 3);
+        assert_eq!(found.len(), 1);
+        assert!(!truncated);
+
+        let unsolvable: Board = include_str!("../examples/fail3.txt").parse().unwrap();
+        assert_eq!(solutions_capped(unsolvable, 3), (Vec::new(), false));
+        assert_eq!(solutions_capped(Board::new(), 0), (Vec::new(), true));
+    }
+
+    #[test]
+    fn sequential_search_is_valid_and_deterministic() {
+        let solver = Solver::with_options(SolveOptions {
+            sequential: true,
+            ..SolveOptions::default()
+        });
+        let solved = solver.solve(easy()).unwrap();
+        assert_eq!(solved.to_line(), solve(&easy()).unwrap().to_line());
+
+        // The empty board has a huge choice of solutions, but every sequential run settles on the same one
+        let first = solver.solve(Board::new()).unwrap();
+        assert!(first.is_solved());
+        for _ in 0..3 {
+            assert_eq!(
+                solver.solve(Board::new()).unwrap().to_line(),
+                first.to_line()
+            );
+        }
+    }
+
+    #[test]
+    fn unit_candidates_of_the_easy_top_row() {
+        let board = easy();
+        let top = board.units()[9];
+        assert_eq!(board.unit_candidate_union(&top), 0b11_1111_1110);
+        assert_eq!(
+            board.unit_candidate_counts(&top),
+            [0, 1, 1, 1, 2, 3, 4, 1, 2, 1]
+        );
+    }
+
+    #[test]
+    fn unit_candidates_leave_out_missing_numbers() {
+        let mut grid = Board::new().to_candidate_grid();
+        grid[0] = [0b110; 9];
+        let board = Board::from_candidate_grid(&grid);
+        let top = board.units()[9];
+        assert_eq!(board.unit_candidate_union(&top), 0b110);
+        assert_eq!(
+            board.unit_candidate_counts(&top),
+            [0, 9, 9, 0, 0, 0, 0, 0, 0, 0]
+        );
+        let left = board.units()[0];
+        assert_eq!(
+            board.unit_candidate_counts(&left),
+            [0, 9, 9, 8, 8, 8, 8, 8, 8, 8]
+        );
+    }
+
+    #[test]
+    fn first_backtrack_depth_is_none_without_a_wrong_guess() {
+        let (solution, stats) = Solver::new().solve_with_stats(easy());
+        assert!(solution.is_some());
+        assert_eq!(stats.first_backtrack_depth, None);
+        assert_eq!(stats.backtracks, 0);
+    }
+
+    #[test]
+    fn first_backtrack_depth_of_a_harder_puzzle() {
+        let extreme: Board = include_str!("../examples/extreme1.txt").parse().unwrap();
+        let (solution, stats) = Solver::new().solve_with_stats(extreme);
+        assert!(solution.is_some());
+        let depth = stats.first_backtrack_depth.unwrap();
+        assert!((1..=stats.max_depth).contains(&depth), "{stats:?}");
+        assert!(stats.backtracks > 0);
+
+        // A board that's already stuck backs up before any guess
+        let mut stuck = Board::new();
+        stuck.set_raw(BoardIdx::new(0, 0), Cell::none_possible());
+        let (solution, stats) = Solver::new().solve_with_stats(stuck);
+        assert_eq!(solution, None);
+        assert_eq!(stats.first_backtrack_depth, Some(0));
+    }
+
+    #[test]
+    fn both_possible_keeps_the_shared_numbers() {
+        let mut first = Cell::none_possible();
+        let mut second = Cell::none_possible();
+        for num in [1, 4, 7] {
+            first.set_possible(num, true);
+        }
+        for num in [4, 7, 9] {
+            second.set_possible(num, true);
+        }
+        assert_eq!(first.both_possible(second).possibilities(), vec![4, 7]);
+        assert_eq!(first.both_possible(Cell::any_possible()), first);
+    }
+
+    #[test]
+    fn intersect_candidates_combines_two_analyses() {
+        let puzzle: Board = include_str!("../examples/extreme1.txt").parse().unwrap();
+        let solution = solve(&puzzle).unwrap();
+        let mut nishio = puzzle.clone();
+        techniques::nishio(&mut nishio, techniques::NISHIO_DEPTH);
+        let mut singles = puzzle.clone();
+        techniques::hidden_singles(&mut singles);
+
+        let mut combined = nishio.clone();
+        assert_eq!(combined.intersect_candidates(&singles), Ok(()));
+        for idx in (0..BOARD_CELLS).map(BoardIdx::from_linear) {
+            let both = nishio.get(idx).both_possible(singles.get(idx));
+            assert_eq!(combined.get(idx), both);
+            assert!(
+                combined
+                    .get(idx)
+                    .is_possible(solution.get(idx).possibilities()[0])
+            );
+        }
+        assert!(combined.total_candidates() < nishio.total_candidates());
+        assert!(combined.total_candidates() < singles.total_candidates());
+        assert!(singles.played.is_subset(&combined.played));
+    }
+
+    #[test]
+    fn intersect_candidates_reports_a_contradiction() {
+        let mut first = Board::new();
+        first.play_cell(BoardIdx::new(0, 0), 1);
+        let mut second = Board::new();
+        second.play_cell(BoardIdx::new(0, 0), 2);
+        let corner = BoardIdx::new(0, 0);
+        assert_eq!(
+            first.intersect_candidates(&second),
+            Err(Conflict::NoCandidates(corner))
+        );
+        assert_eq!(first.get(corner).num_possibilities(), 0);
+    }
+
+    #[test]
+    fn digit_candidate_counts_on_a_partly_solved_board() {
+        let mut board = Board::new();
+        board.play_cell(BoardIdx::new(0, 0), 1);
+        board.play_cell(BoardIdx::new(4, 4), 2);
+        let counts = board.digit_candidate_counts();
+        // 79 cells are left, and each played number is crossed off its 20 peers, which don't overlap here
+        assert_eq!(counts[0], 0);
+        assert_eq!(counts[1], 79 - 20);
+        assert_eq!(counts[2], 79 - 20);
+        assert_eq!(counts[3..], [79; 7]);
+
+        let easy = easy();
+        let counts = easy.digit_candidate_counts();
+        for num in 1..=9 {
+            assert_eq!(counts[num as usize], easy.candidate_cells(num).len());
+        }
+    }
+
+    #[test]
+    fn from_linear_agrees_with_new() {
+        for (row, col) in iproduct!(0..9, 0..9) {
+            let idx = BoardIdx::new(col, row);
+            assert_eq!(BoardIdx::from_linear(row * 9 + col), idx);
+            assert_eq!(idx.idx, row * 9 + col);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cell index 81 is out of bounds")]
+    fn from_linear_rejects_an_index_past_the_board() {
+        BoardIdx::from_linear(BOARD_CELLS);
+    }
+
+    #[test]
+    fn apply_eliminations_from_copies_pencil_marks() {
+        let mut master = easy();
+        master.freeze();
+        let mut copy = master.clone();
+        techniques::nishio(&mut copy, techniques::NISHIO_DEPTH);
+        let expected = master.total_candidates() - copy.total_candidates();
+        assert!(expected > 0);
+
+        assert_eq!(master.apply_eliminations_from(&copy), expected);
+        assert_eq!(master.to_candidate_grid(), copy.to_candidate_grid());
+        assert_eq!(master.played, easy().played);
+        // They're the player's own eliminations now, so they can be taken back
+        assert!(master.undo_eliminate().is_some());
+    }
+
+    #[test]
+    fn apply_eliminations_from_never_empties_a_cell() {
+        let idx = BoardIdx::new(0, 0);
+        let mut master = Board::new();
+        for num in 3..=9 {
+            master.eliminate(idx, num).unwrap();
+        }
+        let mut other = Board::new();
+        for num in [1, 2] {
+            other.eliminate(idx, num).unwrap();
+        }
+        assert_eq!(master.apply_eliminations_from(&other), 0);
+        assert_eq!(master.candidates(idx), vec![1, 2]);
+    }
+
+    #[test]
+    fn play_cell_tracked_returns_the_candidate_changes() {
+        let mut board = easy();
+        let before = board.clone();
+        let idx = BoardIdx::new(0, 0);
+        let num = solve(&before).unwrap().played_num(idx).unwrap();
+        let removed = board.play_cell_tracked(idx, num);
+
+        let mut changes = Vec::new();
+        for cell in (0..BOARD_CELLS).map(BoardIdx::from_linear) {
+            for candidate in before.candidates(cell) {
+                if !board.get(cell).is_possible(candidate) {
+                    changes.push((cell, candidate));
+                }
+            }
+        }
+        assert_eq!(removed, changes);
+        assert!(removed.contains(&(BoardIdx::new(1, 0), num)));
+        assert!(
+            removed
+                .iter()
+                .all(|&(cell, removed)| cell == idx || removed == num)
+        );
+
+        // Playing it the untracked way ends up the same
+        let mut untracked = before.clone();
+        untracked.play_cell(idx, num);
+        assert_eq!(untracked.to_candidate_grid(), board.to_candidate_grid());
+    }
+
+    /// Every event of a search with `seed`, in the order they came
+    fn seeded_trace(board: &Board, seed: u64) -> (Option<Board>, Vec<SolveEvent>) {
+        let solver = Solver::with_options(SolveOptions {
+            seed: Some(seed),
+            ..SolveOptions::default()
+        });
+        let events = std::sync::Mutex::new(Vec::new());
+        let solution = solver.solve_with_progress(board.clone(), &|event| {
+            events.lock().unwrap().push(event);
+        });
+        (solution, events.into_inner().unwrap())
+    }
+
+    #[test]
+    fn seeded_runs_give_identical_traces() {
+        let extreme: Board = include_str!("../examples/extreme1.txt").parse().unwrap();
+        for board in [Board::new(), extreme] {
+            let (solution, trace) = seeded_trace(&board, 42);
+            assert!(
+                trace
+                    .iter()
+                    .any(|event| matches!(event, SolveEvent::Guess { .. }))
+            );
+            assert_eq!(seeded_trace(&board, 42), (solution, trace));
+        }
+    }
+
+    #[test]
+    fn different_seeds_explore_differently() {
+        let (first, first_trace) = seeded_trace(&Board::new(), 1);
+        let (second, second_trace) = seeded_trace(&Board::new(), 2);
+        assert_ne!(first_trace, second_trace);
+        assert!(first.unwrap().is_solved() && second.unwrap().is_solved());
+    }
+
+    #[test]
+    fn conflicting_peers_finds_every_clash() {
+        let mut board = Board::new();
+        let (row_peer, col_peer, square_peer) = (
+            BoardIdx::new(5, 0),
+            BoardIdx::new(0, 7),
+            BoardIdx::new(1, 1),
+        );
+        board.play_cell(row_peer, 4);
+        board.play_cell(col_peer, 4);
+        board.play_cell(square_peer, 6);
+        let target = BoardIdx::new(0, 0);
+        assert_eq!(board.conflicting_peers(target, 4), vec![col_peer, row_peer]);
+        assert_eq!(board.conflicting_peers(target, 6), vec![square_peer]);
+        assert_eq!(board.conflicting_peers(target, 5), []);
+        // A cell doesn't clash with itself
+        assert_eq!(board.conflicting_peers(row_peer, 4), []);
+    }
+
+    #[test]
+    fn conflicting_peers_includes_constraint_peers() {
+        let mut board = Board::new();
+        board.add_constraint(Constraint::AntiKnight);
+        let knight = BoardIdx::new(1, 2);
+        board.play_cell(knight, 3);
+        assert_eq!(
+            board.conflicting_peers(BoardIdx::new(0, 0), 3),
+            vec![knight]
+        );
+    }
+
+    #[test]
+    fn removal_log_records_a_placements_peers() {
+        let mut board = Board::new();
+        board.play_cell(BoardIdx::new(8, 8), 1);
+        assert_eq!(board.removal_log(), []);
+
+        board.track_provenance();
+        let corner = BoardIdx::new(0, 0);
+        board.play_cell(corner, 5);
+        let expected = board
+            .peers(corner)
+            .map(|cell| Elimination {
+                cell,
+                num: 5,
+                cause: Cause::Placement(corner),
+            })
+            .to_vec();
+        assert_eq!(board.removal_log(), expected);
+
+        // Later moves add to the log, leaving out peers that had already lost the number
+        let other = BoardIdx::new(4, 1);
+        board.play_cell(other, 5);
+        assert_eq!(board.removal_log().len(), 20 + 14);
+        assert!(
+            board.removal_log()[20..]
+                .iter()
+                .all(|removal| removal.cause == Cause::Placement(other))
+        );
+        board.eliminate(BoardIdx::new(8, 0), 2).unwrap();
+        assert_eq!(
+            board.removal_log().last(),
+            Some(&Elimination {
+                cell: BoardIdx::new(8, 0),
+                num: 2,
+                cause: Cause::User,
+            })
+        );
+
+        board.clear_removal_log();
+        assert_eq!(board.removal_log(), []);
+        board.play_cell(BoardIdx::new(8, 4), 9);
+        assert!(!board.removal_log().is_empty());
+    }
+
+    /// Solves `board` with a progress callback, returning the solution and every value it was told
+    fn solve_with_reports(board: Board) -> (Option<Board>, Vec<f64>) {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reports);
+        let solver = Solver::with_options(SolveOptions {
+            progress: Some(Progress::new(move |done| sink.lock().unwrap().push(done))),
+            ..SolveOptions::default()
+        });
+        let solution = solver.solve(board);
+        drop(solver);
+        let reports = Arc::try_unwrap(reports).unwrap().into_inner().unwrap();
+        (solution, reports)
+    }
+
+    #[test]
+    fn progress_is_reported_in_order_up_to_one() {
+        let unsolvable: Board = include_str!("../examples/fail3.txt").parse().unwrap();
+        for board in [unsolvable, Board::new()] {
+            let (_, reports) = solve_with_reports(board);
+            assert!(!reports.is_empty());
+            assert!(reports.iter().all(|done| (0.0..=1.0).contains(done)));
+            assert!(reports.is_sorted(), "{reports:?}");
+            assert_eq!(reports.last(), Some(&1.0));
+        }
+    }
+
+    #[test]
+    fn progress_is_quiet_without_a_guess() {
+        let (solution, reports) = solve_with_reports(easy());
+        assert!(solution.is_some());
+        assert!(reports.is_empty());
+    }
+
+    #[test]
+    fn propagation_solves_medium_puzzles_without_a_guess() {
+        let puzzles = [
+            include_str!("../examples/easy1.txt"),
+            "003020600900305001001806400008102900700000008006708200002609500800203009005010300",
+            "200080300060070084030500209000105408000000000402706000301007040720040060004010003",
+            "000000907000420180000705026100904000050000040000507009920108000034059000507000000",
+            "030050040008010500460000012070502080000603000040109030250000098001020600080060020",
+        ];
+        for puzzle in puzzles {
+            let (solution, stats) = Solver::new().solve_with_stats(puzzle.parse().unwrap());
+            assert!(solution.unwrap().is_solved());
+            assert_eq!(stats.guesses, 0);
+            assert_eq!(stats.nodes, 1);
+        }
+    }
+
+    #[test]
+    fn boards_reached_by_different_solve_orders_are_equal() {
+        let puzzle = easy();
+        let solution = solve(&puzzle).unwrap();
+        let solver_with = |options| Solver::with_options(options).solve(puzzle.clone()).unwrap();
+        let sequential = solver_with(SolveOptions {
+            sequential: true,
+            ..SolveOptions::default()
+        });
+        let seeded = solver_with(SolveOptions {
+            seed: Some(5),
+            ..SolveOptions::default()
+        });
+
+        let cells: Vec<_> = (0..BOARD_CELLS).map(BoardIdx::from_linear).collect();
+        let play_in = |order: &mut dyn Iterator<Item = &BoardIdx>| {
+            let mut board = Board::new();
+            for &idx in order {
+                board.play_cell(idx, solution.played_num(idx).unwrap());
+            }
+            board
+        };
+        let forwards = play_in(&mut cells.iter());
+        let backwards = play_in(&mut cells.iter().rev());
+
+        let boards = [solution.clone(), sequential, seeded, forwards, backwards];
+        assert!(boards.iter().all(|board| *board == solution));
+        assert_eq!(boards.into_iter().collect::<HashSet<_>>().len(), 1);
+        assert_ne!(puzzle, solution);
+    }
+
+    #[test]
+    fn cells_compare_and_hash_by_their_important_bits() {
+        use std::hash::{BuildHasher, RandomState};
+
+        let (cell, stray) = (Cell::fixed(4), Cell(Cell::fixed(4).0 | 1));
+        assert_eq!(cell, stray);
+        let hasher = RandomState::new();
+        assert_eq!(hasher.hash_one(cell), hasher.hash_one(stray));
+        assert_ne!(cell, Cell::fixed(5));
+    }
+
+    #[test]
+    fn unplaying_a_cell_matches_a_board_without_it() {
+        let kept = [
+            (BoardIdx::new(5, 1), 5),
+            (BoardIdx::new(4, 0), 3),
+            (BoardIdx::new(0, 4), 7),
+        ];
+        let mut fresh = Board::new();
+        for &(idx, num) in &kept {
+            fresh.play_cell(idx, num);
+        }
+        let mut board = fresh.clone();
+        let undone = BoardIdx::new(0, 0);
+        board.play_cell(undone, 5);
+
+        assert!(board.unplay_cell(undone));
+        assert_eq!(board.to_candidate_grid(), fresh.to_candidate_grid());
+        assert_eq!(board.played, fresh.played);
+        // The 5 comes back in the row, but not where the other 5 still rules it out
+        assert!(board.get(BoardIdx::new(1, 0)).is_possible(5));
+        assert!(!board.get(BoardIdx::new(1, 1)).is_possible(5));
+        assert!(!board.unplay_cell(undone));
+    }
+
+    #[test]
+    fn unsolved_by_candidates_of_the_easy_puzzle() {
+        let unsolved = easy().unsolved_by_candidates();
+        assert_eq!(unsolved.len(), 43);
+        assert!(unsolved.is_sorted_by_key(|&(_, candidates)| candidates));
+        let bucket = |count: u8| {
+            unsolved
+                .iter()
+                .filter(|&&(_, candidates)| candidates == count)
+                .map(|&(idx, _)| (idx.col, idx.row))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            bucket(2),
+            [
+                (8, 0),
+                (0, 1),
+                (7, 1),
+                (8, 1),
+                (0, 2),
+                (1, 2),
+                (3, 3),
+                (6, 3),
+                (5, 4),
+                (6, 4),
+                (7, 4),
+                (1, 5),
+                (0, 6),
+                (6, 7),
+                (7, 7),
+                (2, 8),
+                (5, 8)
+            ]
+        );
+        assert_eq!(
+            bucket(3),
+            [
+                (0, 0),
+                (1, 0),
+                (7, 0),
+                (6, 2),
+                (4, 5),
+                (6, 5),
+                (1, 6),
+                (0, 8),
+                (1, 8),
+                (8, 8)
+            ]
+        );
+    }
+
+    #[test]
+    fn most_constrained_unit_of_a_partly_filled_board() {
+        let puzzle = easy();
+        assert_eq!(
+            puzzle.most_constrained_unit(),
+            (puzzle.iter_square(SquareIdx::TM), 2)
+        );
+
+        // A finished column is passed over for the squares along it
+        let solution = solve(&puzzle).unwrap();
+        let mut board = Board::new();
+        for idx in board.iter_col(0) {
+            board.play_cell(idx, solution.played_num(idx).unwrap());
+        }
+        assert_eq!(
+            board.most_constrained_unit(),
+            (board.iter_square(SquareIdx::TL), 6)
+        );
+
+        // Once every unit is finished, the first one comes back
+        assert_eq!(solution.most_constrained_unit(), (solution.iter_col(0), 0));
+    }
+
+    #[test]
+    fn most_constrained_unit_breaks_ties_by_unit_order() {
+        let mut board = Board::new();
+        assert_eq!(board.most_constrained_unit(), (board.iter_col(0), 9));
+
+        // Column 5 and the top middle square both have seven left, and columns come first
+        board.play_cell(BoardIdx::new(4, 0), 1);
+        board.play_cell(BoardIdx::new(4, 1), 2);
+        assert_eq!(board.most_constrained_unit(), (board.iter_col(4), 7));
+        board.play_cell(BoardIdx::new(3, 2), 3);
+        assert_eq!(
+            board.most_constrained_unit(),
+            (board.iter_square(SquareIdx::TM), 6)
+        );
+    }
+
+    #[test]
+    fn frozen_givens_refuse_edits() {
+        let mut board = easy();
+        board.freeze();
+        let given = (0..BOARD_CELLS)
+            .map(BoardIdx::from_linear)
+            .find(|&idx| board.is_given(idx))
+            .unwrap();
+        let num = board.played_num(given).unwrap();
+        let before = board.clone();
+
+        assert_eq!(board.eliminate(given, num), Err(MoveError::Frozen(given)));
+        assert_eq!(
+            board.play_checked(given, num % 9 + 1),
+            Err(MoveError::Frozen(given))
+        );
+        assert_eq!(
+            board.play_checked(given, num),
+            Err(MoveError::Frozen(given))
+        );
+        assert_eq!(board.get(given), Cell::fixed(num));
+        assert_eq!(board.to_candidate_grid(), before.to_candidate_grid());
+        assert_eq!(board.undo_eliminate(), None);
+
+        // A cell played after freezing is still open to pencil marks, which just leave it alone
+        let later = (0..BOARD_CELLS)
+            .map(BoardIdx::from_linear)
+            .find(|idx| !board.played.contains(idx))
+            .unwrap();
+        let candidate = board.get(later).possibilities()[0];
+        board.play_checked(later, candidate).unwrap();
+        assert_eq!(board.eliminate(later, candidate), Ok(false));
+        assert!(!board.is_given(later));
+    }
+
+    /// The row-major smallest completion of `grid`, filling each blank cell in turn with the smallest number that
+    /// doesn't clash, with no propagation at all
+    fn naive_smallest(grid: &mut [u8; BOARD_CELLS], from: usize) -> bool {
+        let Some(pos) = (from..BOARD_CELLS).find(|&pos| grid[pos] == 0) else {
+            return true;
+        };
+        let idx = BoardIdx::from_linear(pos);
+        for num in 1..=9 {
+            if Board::new()
+                .peers(idx)
+                .iter()
+                .all(|peer| grid[peer.idx] != num)
+            {
+                grid[pos] = num;
+                if naive_smallest(grid, pos + 1) {
+                    return true;
+                }
+            }
+        }
+        grid[pos] = 0;
+        false
+    }
+
+    #[test]
+    fn lexicographically_smallest_of_an_under_constrained_board() {
+        let sequential = Solver::with_options(SolveOptions {
+            sequential: true,
+            ..SolveOptions::default()
+        });
+        for (idx, num) in [
+            (None, 0),
+            (Some(BoardIdx::new(0, 0)), 9),
+            (Some(BoardIdx::new(4, 4)), 1),
+        ] {
+            let mut board = Board::new();
+            let mut grid = [0; BOARD_CELLS];
+            if let Some(idx) = idx {
+                board.play_cell(idx, num);
+                grid[idx.idx] = num;
+            }
+            assert!(naive_smallest(&mut grid, 0));
+            let expected = grid.iter().join("");
+
+            let smallest = solve_lexicographically_smallest(board.clone()).unwrap();
+            assert_eq!(smallest.to_line(), expected, "clue {idx:?}");
+            for _ in 0..4 {
+                assert_eq!(
+                    Solver::new().lexicographically_smallest(board.clone()),
+                    Some(smallest.clone())
+                );
+            }
+            assert_eq!(sequential.lexicographically_smallest(board), Some(smallest));
+        }
+        assert!(
+            solve_lexicographically_smallest(Board::new())
+                .unwrap()
+                .to_line()
+                .starts_with("123456789456789123789123456")
+        );
+    }
+
+    #[test]
+    fn solve_iterative_matches_solve() {
+        let easter_monster: Board =
+            "1.......2.9.4...5...6...7...5.9.3.......7.......85..4.7.....6...3...9.8...2.....1"
+                .parse()
+                .unwrap();
+        let (_, stats) = solve_with_stats(easter_monster.clone());
+        assert!(
+            stats.guesses > 1,
+            "the Easter Monster takes several guesses"
+        );
+
+        for puzzle in [easy(), easter_monster] {
+            let solution = solve(&puzzle).unwrap();
+            assert_eq!(solve_iterative(puzzle.clone()), Some(solution));
+        }
+        let unsolvable: Board = include_str!("../examples/fail3.txt").parse().unwrap();
+        assert_eq!(solve_iterative(unsolvable), None);
+    }
+
+    #[test]
+    fn cells_only_ever_use_bits_1_to_9() {
+        assert_eq!(Cell::any_possible().0, Cell::IMPORTANT);
+        assert_eq!(Cell::none_possible().0, 0);
+        for num in 1..=9 {
+            assert_eq!(Cell::fixed(num).0, 1 << num);
+        }
+        assert_eq!(
+            Cell(u16::MAX).both_possible(Cell(u16::MAX)).0,
+            Cell::IMPORTANT
+        );
+        let grid = Board::from_candidate_grid(&[[u16::MAX; 9]; 9]);
+        assert!(grid.cells.iter().all(|cell| cell.0 == Cell::IMPORTANT));
+
+        for start in [Cell::any_possible(), Cell::none_possible()] {
+            let mut cell = start;
+            for (num, possible) in iproduct!(1..=9, [true, false, true]) {
+                cell.set_possible(num, possible);
+                assert_eq!(cell.0 & !Cell::IMPORTANT, 0, "{num} set to {possible}");
+            }
+        }
+    }
+
+    #[test]
+    fn eliminating_leaves_bit_0_clear() {
+        let mut board = easy();
+        for (idx, num) in iproduct!(0..BOARD_CELLS, 1..=9) {
+            let idx = BoardIdx::from_linear(idx);
+            if !board.played.contains(&idx) {
+                board.eliminate(idx, num).unwrap();
+            }
+        }
+        assert!(
+            board
+                .cells
+                .iter()
+                .all(|cell| cell.0 & !Cell::IMPORTANT == 0)
+        );
+        board.recompute_candidates();
+        assert!(
+            board
+                .cells
+                .iter()
+                .all(|cell| cell.0 & !Cell::IMPORTANT == 0)
+        );
+    }
+
+    #[test]
+    fn a_cancelled_solve_gives_up() {
+        let puzzle: Board = include_str!("../examples/extreme1.txt").parse().unwrap();
+        let cancelled = AtomicBool::new(true);
+        assert_eq!(solve_with_cancel(puzzle.clone(), &cancelled), None);
+        assert!(solve_with_cancel(puzzle.clone(), &AtomicBool::new(false)).is_some());
+
+        // Past the depth limit the iterative search takes over, and it checks the flag between branches
+        let iterating = Solver::with_options(SolveOptions {
+            max_depth: 0,
+            past_max_depth: DepthLimit::Iterate,
+            ..SolveOptions::default()
+        });
+        assert!(iterating.solve(puzzle.clone()).is_some());
+        let cancel = AtomicBool::new(false);
+        let mut solutions = iterating.solutions_until(Board::new(), &cancel);
+        assert!(solutions.next().is_some());
+        cancel.store(true, Ordering::Relaxed);
+        assert_eq!(solutions.next(), None);
+    }
+
+    #[test]
+    fn cancelling_part_way_through_gives_up() {
+        let puzzle: Board = include_str!("../examples/extreme1.txt").parse().unwrap();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancel);
+        // Sequential, so the first guess's branches go in order. The solution isn't in the first, and the flag goes up
+        // once that one's done
+        let solver = Solver::with_options(SolveOptions {
+            sequential: true,
+            progress: Some(Progress::new(move |_| flag.store(true, Ordering::Relaxed))),
+            ..SolveOptions::default()
+        });
+        assert_eq!(solver.solve_until(puzzle.clone(), &cancel), None);
+        assert!(cancel.load(Ordering::Relaxed));
+        assert!(solver.solve(puzzle).is_some());
+    }
+
+    /// A blank board with `num` written straight into `cells`, without clearing it from their peers
+    fn with_repeats(num: u8, cells: &[BoardIdx]) -> Board {
+        let mut board = Board::new();
+        for &idx in cells {
+            board.set_raw(idx, Cell::fixed(num));
+        }
+        board
+    }
+
+    #[test]
+    fn validate_accepts_a_consistent_board() {
+        assert_eq!(easy().validate(), Ok(()));
+        assert_eq!(solve(&easy()).unwrap().validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_reports_each_repeat_with_its_unit() {
+        let (a, b) = (BoardIdx::new(0, 0), BoardIdx::new(5, 0));
+        assert_eq!(
+            with_repeats(4, &[a, b]).validate(),
+            Err(vec![Conflict::Repeated {
+                unit: Unit::Row(0),
+                num: 4,
+                cells: [a, b]
+            }])
+        );
+
+        let (a, b) = (BoardIdx::new(2, 1), BoardIdx::new(2, 7));
+        assert_eq!(
+            with_repeats(7, &[a, b]).validate(),
+            Err(vec![Conflict::Repeated {
+                unit: Unit::Column(2),
+                num: 7,
+                cells: [a, b]
+            }])
+        );
+
+        let (a, b) = (BoardIdx::new(3, 3), BoardIdx::new(5, 4));
+        assert_eq!(
+            with_repeats(9, &[a, b]).validate(),
+            Err(vec![Conflict::Repeated {
+                unit: Unit::Square(4),
+                num: 9,
+                cells: [a, b]
+            }])
+        );
+    }
+
+    #[test]
+    fn validate_lists_every_problem() {
+        let mut board = with_repeats(1, &[BoardIdx::new(0, 0), BoardIdx::new(0, 1)]);
+        board.set_raw(BoardIdx::new(8, 8), Cell::none_possible());
+        assert_eq!(
+            board.validate(),
+            Err(vec![
+                Conflict::NoCandidates(BoardIdx::new(8, 8)),
+                Conflict::Repeated {
+                    unit: Unit::Column(0),
+                    num: 1,
+                    cells: [BoardIdx::new(0, 0), BoardIdx::new(0, 1)]
+                },
+                Conflict::Repeated {
+                    unit: Unit::Square(0),
+                    num: 1,
+                    cells: [BoardIdx::new(0, 0), BoardIdx::new(0, 1)]
+                },
+            ])
+        );
+    }
+}
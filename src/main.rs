@@ -1,386 +1,511 @@
-use std::{array, collections::HashSet, fs, iter};
-
-use itertools::{Itertools, iproduct};
-use rayon::prelude::*;
-
-/// The nth bit represents if the number `n` is possible in this cell
-#[derive(Clone, Copy, Eq)]
-struct Cell(u16);
-
-impl PartialEq for Cell {
-    fn eq(&self, other: &Self) -> bool {
-        self.important_bits() == other.important_bits()
+//! The command line front end: solves the puzzle files it's given, or whole datasets with `--csv`, `--lines`,
+//! and `--dir`
+
+use std::{
+    borrow::Cow,
+    fs,
+    io::{self, IsTerminal},
+    path::Path,
+    process::ExitCode,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crossword_solver::{
+    Board, Solver, batch, constraint, count_solutions, generate, render::render_side_by_side,
+    samurai, solve_borrowed, solve_with_cancel,
+};
+
+/// The command line arguments, consumed flag by flag
+struct Args(Vec<String>);
+
+impl Args {
+    fn from_env() -> Self {
+        Self(std::env::args().skip(1).collect())
     }
-}
 
-impl std::fmt::Debug for Cell {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.possibilities().iter().join(","))
+    /// Removes `name` if it was passed, returning whether it was
+    fn flag(&mut self, name: &str) -> bool {
+        let len = self.0.len();
+        self.0.retain(|arg| arg != name);
+        self.0.len() != len
     }
-}
 
-impl std::fmt::Display for Cell {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", {
-            match self.possibilities()[..] {
-                [] => "F".into(),
-                [num] => format!("{num}"),
-                _ => "?".into(),
-            }
-        })
-    }
-}
-
-impl Cell {
-    fn fixed(num: u8) -> Self {
-        Self(1 << num)
+    /// Removes `name` and the value following it, erroring if `name` is the last argument
+    fn value(&mut self, name: &str) -> Result<Option<String>, String> {
+        let Some(pos) = self.0.iter().position(|arg| arg == name) else {
+            return Ok(None);
+        };
+        if pos + 1 == self.0.len() {
+            return Err(format!("Expected a value after '{name}'!"));
+        }
+        self.0.remove(pos);
+        Ok(Some(self.0.remove(pos)))
     }
 
-    fn any_possible() -> Self {
-        Self(u16::MAX)
+    /// Like [`Args::value`], but the value has to be a whole number
+    fn number(&mut self, name: &str) -> Result<Option<u64>, String> {
+        self.value(name)?
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("Expected a number after '{name}', not '{value}'!"))
+            })
+            .transpose()
     }
 
-    fn none_possible() -> Self {
-        Self(0)
+    /// Whatever is left once all the flags have been taken out
+    fn positional(self) -> Vec<String> {
+        self.0
     }
+}
 
-    /// The internal representation without the bits that don't carry information
-    ///
-    /// Only the bits `1` through `9` are important
-    fn important_bits(&self) -> u16 {
-        self.0 & 0b1111111110
-    }
+fn main() -> ExitCode {
+    let mut args = Args::from_env();
+    let show_progress = args.flag("--progress");
+    let samurai = args.flag("--samurai");
+    let warn_multiple = args.flag("--warn-multiple");
+    let side_by_side = args.flag("--side-by-side");
+    let oneline = args.flag("--oneline");
+    let count = args.flag("--count");
+    let pretty = args.flag("--pretty");
+    let diagonal = args.flag("--diagonal");
+    let rate = args.flag("--rate");
+    let dir = match args.value("--dir") {
+        Ok(dir) => dir,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(2);
+        }
+    };
+    let csv = match args.value("--csv") {
+        Ok(csv) => csv,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(2);
+        }
+    };
 
-    fn is_possible(&self, num: u8) -> bool {
-        (self.0 >> num) & 1 == 1
-    }
+    // `--batch` is the same thing, under the name other solvers give it
+    let lines = match args.value("--lines").and_then(|lines| match lines {
+        Some(lines) => Ok(Some(lines)),
+        None => args.value("--batch"),
+    }) {
+        Ok(lines) => lines,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(2);
+        }
+    };
+    let with_puzzle = args.flag("--with-puzzle");
+    let output = match args.value("--output") {
+        Ok(output) => output,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(2);
+        }
+    };
+    let clues = match args.number("--clues") {
+        Ok(clues) => clues,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(2);
+        }
+    };
+    let seed = match args.number("--seed") {
+        Ok(seed) => seed,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(2);
+        }
+    };
+    let timeout = match args.number("--timeout") {
+        Ok(timeout) => timeout.map(Duration::from_millis),
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(2);
+        }
+    };
 
-    fn set_possible(&mut self, num: u8, possible: bool) {
-        if possible {
-            self.0 = self.0 | 1 << num
-        } else {
-            self.0 = self.0 & !(1 << num);
+    if let Some(lines) = lines {
+        let Ok(dataset) = fs::read_to_string(&lines) else {
+            eprintln!("Error reading '{lines}'!");
+            return ExitCode::from(2);
+        };
+        let format = match with_puzzle {
+            true => batch::LineFormat::SolutionAndPuzzle,
+            false => batch::LineFormat::Solution,
+        };
+        let report = batch::solve_lines(&dataset, format);
+        for failure in &report.failures {
+            eprintln!("{failure}");
         }
+        print!("{}", report.output);
+        eprintln!("{}", report.stats);
+        return match report.stats.failed {
+            0 => ExitCode::SUCCESS,
+            _ => ExitCode::from(1),
+        };
     }
 
-    /// Which numbers are possible for this cell
-    fn possibilities(&self) -> Vec<u8> {
-        (1u8..10).filter(|&num| self.is_possible(num)).collect()
+    #[cfg(not(feature = "csv"))]
+    if csv.is_some() {
+        eprintln!("This build can't read CSV files, rebuild with the `csv` feature for '--csv'!");
+        return ExitCode::from(2);
     }
-
-    fn num_possibilities(&self) -> usize {
-        self.possibilities().len()
+    #[cfg(feature = "csv")]
+    if let Some(csv) = csv {
+        return match batch::solve_csv(Path::new(&csv)) {
+            Ok(report) => {
+                for failure in &report.failures {
+                    eprintln!("{failure}");
+                }
+                println!("{}", report.stats);
+                if report.failures.is_empty() {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitCode::from(1)
+                }
+            }
+            Err(err) => {
+                eprintln!("Error reading '{csv}': {err}");
+                ExitCode::from(2)
+            }
+        };
     }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum SquareIdx {
-    TL,
-    TM,
-    TR,
-    ML,
-    MM,
-    MR,
-    BL,
-    BM,
-    BR,
-}
-
-impl SquareIdx {
-    fn from_idx(idx: usize) -> Self {
-        use SquareIdx::*;
-        match idx {
-            0 => TL,
-            1 => TM,
-            2 => TR,
-            3 => ML,
-            4 => MM,
-            5 => MR,
-            6 => BL,
-            7 => BM,
-            8 => BR,
-            _ => panic!(),
-        }
+    if let Some(dir) = dir {
+        let print_progress = |progress: batch::BatchProgress| {
+            eprint!(
+                "\r[{}/{}] {}",
+                progress.done, progress.total, progress.stats
+            );
+            if progress.done == progress.total {
+                eprintln!();
+            }
+        };
+        let on_progress: Option<&(dyn Fn(batch::BatchProgress) + Sync)> = match show_progress {
+            true => Some(&print_progress),
+            false => None,
+        };
+
+        return match batch::solve_dir(Path::new(&dir), on_progress) {
+            Ok(report) => {
+                for path in &report.skipped {
+                    eprintln!(
+                        "Skipping '{}', it isn't a recognized puzzle file",
+                        path.display()
+                    );
+                }
+                for (path, result) in &report.results {
+                    match result {
+                        Ok(true) => println!("Solved '{}'", path.display()),
+                        Ok(false) => eprintln!("Failed to solve '{}'", path.display()),
+                        Err(err) => eprintln!("Error processing '{}': {err}", path.display()),
+                    }
+                }
+                println!("{}", report.stats);
+                if report.stats.failed == 0 {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitCode::from(1)
+                }
+            }
+            Err(err) => {
+                eprintln!("Error reading directory '{dir}': {err}");
+                ExitCode::from(2)
+            }
+        };
     }
 
-    fn to_idx(self) -> usize {
-        use SquareIdx::*;
-        match self {
-            TL => 0,
-            TM => 1,
-            TR => 2,
-            ML => 3,
-            MM => 4,
-            MR => 5,
-            BL => 6,
-            BM => 7,
-            BR => 8,
+    let positional = args.positional();
+    if positional.first().is_some_and(|arg| arg == "generate") {
+        // A different puzzle every run, unless a seed is given to get one back
+        let seed = seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+        });
+        let puzzle = generate::generate(&generate::GeneratorOptions {
+            seed,
+            clues: clues.map(|clues| clues as usize),
+            ..Default::default()
+        });
+        match oneline {
+            true => println!("{}", puzzle.to_sudoku_line()),
+            false => print!("{puzzle}"),
         }
+        return ExitCode::SUCCESS;
     }
 
-    fn to_topleft_cell(self) -> BoardIdx {
-        use SquareIdx::*;
-        match self {
-            TL => BoardIdx::new(0, 0),
-            TM => BoardIdx::new(3, 0),
-            TR => BoardIdx::new(6, 0),
-            ML => BoardIdx::new(0, 3),
-            MM => BoardIdx::new(3, 3),
-            MR => BoardIdx::new(6, 3),
-            BL => BoardIdx::new(0, 6),
-            BM => BoardIdx::new(3, 6),
-            BR => BoardIdx::new(6, 6),
-        }
+    let mut filenames = positional
+        .iter()
+        .flat_map(|arg| batch::expand_glob(arg))
+        .collect::<Vec<_>>();
+    let options = FileOptions {
+        samurai,
+        warn_multiple,
+        side_by_side,
+        oneline,
+        count,
+        pretty,
+        diagonal,
+        rate,
+        timeout,
+        output,
+    };
+    if filenames.len() <= 1 {
+        // Piped input is read when there's no file to read instead, but a terminal would just sit waiting
+        let filename = filenames
+            .pop()
+            .unwrap_or_else(|| match io::stdin().is_terminal() {
+                true => "examples/easy1.txt".into(),
+                false => "-".into(),
+            });
+        return solve_puzzle_file(&filename, &options);
     }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct BoardIdx {
-    col: usize,
-    row: usize,
-    idx: usize,
-}
-
-impl BoardIdx {
-    pub fn new(col: usize, row: usize) -> Self {
-        Self {
-            col,
-            row,
-            idx: col + row * 9,
-        }
+    if options.output.is_some() {
+        eprintln!("'--output' only takes a single puzzle!");
+        return ExitCode::from(2);
     }
 
-    #[track_caller]
-    fn square(&self) -> SquareIdx {
-        use SquareIdx::*;
-        match (self.col / 3, self.row / 3) {
-            (0, 0) => TL,
-            (1, 0) => TM,
-            (2, 0) => TR,
-            (0, 1) => ML,
-            (1, 1) => MM,
-            (2, 1) => MR,
-            (0, 2) => BL,
-            (1, 2) => BM,
-            (2, 2) => BR,
-            _ => unreachable!("Invalid board idx!"),
+    let start = Instant::now();
+    let mut stats = batch::BatchStats::default();
+    for filename in &filenames {
+        if !oneline {
+            println!("==> {filename} <==");
+        }
+        match solve_puzzle_file(filename, &options) == ExitCode::SUCCESS {
+            true => stats.solved += 1,
+            false => stats.failed += 1,
         }
+        if !oneline {
+            println!();
+        }
+    }
+    stats.elapsed = start.elapsed();
+    eprintln!("{stats}");
+    match stats.failed {
+        0 => ExitCode::SUCCESS,
+        _ => ExitCode::from(1),
     }
 }
 
-const BOARD_CELLS: usize = 9 * 9;
-
-#[derive(Clone)]
-struct Board {
-    cells: [Cell; BOARD_CELLS],
-    played: HashSet<BoardIdx>,
+/// How many solutions `--count` counts before giving up, since a puzzle with few clues can have billions
+const COUNT_CAP: usize = 10_000;
+
+/// The flags that change how [`solve_puzzle_file`] prints a puzzle and its solution
+struct FileOptions {
+    samurai: bool,
+    warn_multiple: bool,
+    side_by_side: bool,
+    oneline: bool,
+    count: bool,
+    pretty: bool,
+    /// Solve as an X-Sudoku, whatever constraint lines the file has
+    diagonal: bool,
+    /// Print how hard the puzzle is after its solution
+    rate: bool,
+    /// How long a solve gets before it's given up on
+    timeout: Option<Duration>,
+    /// Where the solution is written instead of stdout
+    output: Option<String>,
 }
 
-impl std::fmt::Debug for Board {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut s = String::new();
-        for row in 0..9 {
-            for col in 0..9 {
-                s.push_str(&format!("{:?} ", self.get(BoardIdx::new(col, row))));
+/// Solves the puzzle in `filename`, or on stdin if it's `-`, and prints it the way `options` asks, picking the
+/// matching exit code
+fn solve_puzzle_file(filename: &str, options: &FileOptions) -> ExitCode {
+    let &FileOptions {
+        samurai,
+        warn_multiple,
+        side_by_side,
+        oneline,
+        count,
+        pretty,
+        diagonal,
+        rate,
+        timeout,
+        ref output,
+    } = options;
+    let output = output.as_deref();
+
+    let board_str = if filename == "-" {
+        match io::read_to_string(io::stdin()) {
+            Ok(board_str) => board_str,
+            Err(err) => {
+                eprintln!("Error reading stdin: {err}");
+                return ExitCode::from(2);
             }
-            s.push('\n');
-        }
-
-        write!(f, "{s}")
-    }
-}
-
-impl std::fmt::Display for Board {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut s = String::new();
-        for row in 0..9 {
-            for col in 0..9 {
-                s.push_str(&format!("{} ", self.get(BoardIdx::new(col, row))));
-            }
-            s.push('\n');
         }
+    } else {
+        let Ok(board_str) = fs::read_to_string(filename) else {
+            eprintln!(
+                "Error finding file '{}'!\nMake sure the path is entered correctly and the file exists.",
+                filename
+            );
+            return ExitCode::from(2);
+        };
+        board_str
+    };
 
-        write!(f, "{s}")
+    if samurai {
+        let board = samurai::SamuraiBoard::from_str_lenient(&board_str);
+        println!("{board}");
+        return report(board.solve(), output);
     }
-}
 
-impl Board {
-    fn new() -> Self {
-        Self {
-            cells: array::from_fn(|_| Cell::any_possible()),
-            played: HashSet::new(),
+    let (board_str, constraints) = match constraint::split_constraints(&board_str) {
+        Ok(split) => split,
+        Err(err) => {
+            eprintln!("Error reading '{filename}': {err}");
+            return ExitCode::from(2);
         }
-    }
-
-    fn from_str(board_str: &str) -> Self {
-        let mut board = Board::new();
-
-        let mut cells = board_str
-            .chars()
-            .into_iter()
-            .flat_map(|c| match c.to_ascii_lowercase() {
-                '1' => Some(1i32),
-                '2' => Some(2),
-                '3' => Some(3),
-                '4' => Some(4),
-                '5' => Some(5),
-                '6' => Some(6),
-                '7' => Some(7),
-                '8' => Some(8),
-                '9' => Some(9),
-                'x' => Some(-1),
-                _ => None,
-            })
-            .chain(iter::repeat(-1));
-
-        for row in 0..9 {
-            for col in 0..9 {
-                let idx = BoardIdx::new(col, row);
-                match u8::try_from(cells.next().unwrap()) {
-                    Ok(num) => board.play_cell(idx, num),
-                    Err(_) => (),
-                }
-            }
+    };
+    let mut board: Board = match board_str.parse() {
+        Ok(board) => board,
+        Err(err) => {
+            eprintln!("Error reading '{filename}': {err}");
+            return ExitCode::from(2);
         }
-
-        board
-    }
-
-    fn get(&self, idx: BoardIdx) -> Cell {
-        self.cells[idx.idx]
+    };
+    for constraint in constraints {
+        board.add_constraint(constraint);
     }
-
-    fn get_mut(&mut self, idx: BoardIdx) -> &mut Cell {
-        &mut self.cells[idx.idx]
+    if diagonal {
+        board.add_constraint(constraint::Constraint::Diagonals);
     }
-
-    fn set_raw(&mut self, idx: BoardIdx, cell: Cell) {
-        self.cells[idx.idx] = cell;
+    if board.is_blank() {
+        eprintln!("Note: the puzzle has no givens, so any valid grid solves it");
     }
-
-    fn play_cell(&mut self, idx: BoardIdx, num: u8) {
-        self.played.insert(idx);
-        self.set_raw(idx, Cell::fixed(num));
-        for to_update in self
-            .iter_square(idx.square())
-            .into_iter()
-            .chain(self.iter_col(idx.col))
-            .chain(self.iter_row(idx.row))
-            .unique()
-        {
-            if to_update == idx {
-                continue;
+    // Counting doesn't go through a solve, so it's the one mode the timeout doesn't cover
+    let solution = match count {
+        true => None,
+        false => match solve_within(&board, timeout) {
+            Ok(solution) => solution,
+            Err(TimedOut) => {
+                eprintln!(
+                    "Gave up after {} ms",
+                    timeout.unwrap_or_default().as_millis()
+                );
+                return ExitCode::from(3);
+            }
+        },
+    };
+    let code = if count {
+        match count_solutions(board.clone(), COUNT_CAP) {
+            0 => {
+                println!("0");
+                ExitCode::from(1)
+            }
+            COUNT_CAP => {
+                println!("at least {COUNT_CAP}");
+                ExitCode::SUCCESS
+            }
+            solutions => {
+                println!("{solutions}");
+                ExitCode::SUCCESS
             }
-            self.get_mut(to_update).set_possible(num, false);
         }
-    }
-
-    fn verify(&self) -> Result<(), ()> {
-        for seq in (0..9)
-            .map(|col| self.iter_col(col))
-            .chain((0..9).map(|row| self.iter_row(row)))
-            .chain((0..9).map(|square| self.iter_square(SquareIdx::from_idx(square))))
-        {
-            let mut seen = HashSet::new();
-            for elem in seq {
-                match self.get(elem).possibilities()[..] {
-                    [] => return Err(()),
-                    [num] => {
-                        if seen.insert(num) == false {
-                            return Err(());
-                        }
-                    }
-                    _ => (),
-                }
+    } else if oneline {
+        match solution {
+            Some(solution) => write_solution(&format!("{}\n", solution.to_line()), output),
+            None => {
+                eprintln!("Failed");
+                ExitCode::from(1)
             }
         }
-
-        Ok(())
-    }
-
-    fn iter_square(&self, square: SquareIdx) -> [BoardIdx; 9] {
-        let offset = square.to_topleft_cell();
-        array::from_fn(|idx| {
-            let col = idx / 3;
-            let row = idx % 3;
-            BoardIdx::new(col + offset.col, row + offset.row)
-        })
-    }
-
-    /// 0-9, left to right
-    fn iter_col(&self, col: usize) -> [BoardIdx; 9] {
-        array::from_fn(|row| BoardIdx::new(col, row))
+    } else if side_by_side {
+        match solution {
+            Some(solution) => write_solution(&render_side_by_side(&board, &solution), output),
+            None => {
+                println!("{board}");
+                eprintln!("Failed");
+                ExitCode::from(1)
+            }
+        }
+    } else if pretty {
+        println!("{}", board.to_pretty_string());
+        report(solution.map(|solution| solution.to_pretty_string()), output)
+    } else {
+        println!("{board}");
+        report(solution, output)
+    };
+    if rate
+        && !count
+        && code == ExitCode::SUCCESS
+        && let Some(rating) = board.rate_difficulty()
+    {
+        println!("Difficulty: {rating}");
     }
-
-    /// 0-9, top to bottom
-    fn iter_row(&self, row: usize) -> [BoardIdx; 9] {
-        array::from_fn(|col| BoardIdx::new(col, row))
+    if warn_multiple && Solver::new().solutions_up_to(board, 2).len() > 1 {
+        eprintln!(
+            "Warning: this puzzle has more than one solution, the one above is just the first found"
+        );
     }
+    code
 }
 
-fn solve(mut board: Board) -> Option<Board> {
-    if board.verify().is_err() {
-        return None;
+/// The solve ran out of time before it finished
+struct TimedOut;
+
+/// Solves `board`, giving up once `timeout` has passed, if there is one
+///
+/// A watchdog thread sets the flag the search checks at every step, so it stops soon after, parallel branches included
+fn solve_within(
+    board: &Board,
+    timeout: Option<Duration>,
+) -> Result<Option<Cow<'_, Board>>, TimedOut> {
+    let Some(timeout) = timeout else {
+        return Ok(solve_borrowed(board));
+    };
+    let cancel = AtomicBool::new(false);
+    let (finished, watchdog) = mpsc::channel::<()>();
+    let solution = thread::scope(|scope| {
+        // Woken early, leaving the flag alone, once the solve hangs up its end of the channel
+        let cancel = &cancel;
+        scope.spawn(move || {
+            if watchdog.recv_timeout(timeout) == Err(RecvTimeoutError::Timeout) {
+                cancel.store(true, Ordering::Relaxed);
+            }
+        });
+        let solution = solve_with_cancel(board.clone(), cancel);
+        drop(finished);
+        solution
+    });
+    match (solution, cancel.load(Ordering::Relaxed)) {
+        (Some(solution), _) => Ok(Some(Cow::Owned(solution))),
+        (None, true) => Err(TimedOut),
+        (None, false) => Ok(None),
     }
+}
 
-    let mut least_possibilities_cell = None;
-    let mut least_possibilities = usize::MAX;
-
-    for (col, row) in iproduct!(0..9, 0..9) {
-        let idx = BoardIdx::new(col, row);
-        if board.played.contains(&idx) {
-            continue;
-        }
-
-        let possibilities = board.get(idx).num_possibilities();
-        if possibilities == 0 {
-            return None;
-        }
-        if possibilities == 1 {
-            board.play_cell(idx, board.get(idx).possibilities()[0]);
-            continue;
-        }
-        if least_possibilities > possibilities {
-            least_possibilities = possibilities;
-            least_possibilities_cell = Some(idx);
+/// Prints the outcome of a solve, with the solution going to `output` if given, and picks the matching exit code
+fn report(ret: Option<impl std::fmt::Display>, output: Option<&str>) -> ExitCode {
+    println!("FINISHED!\n======\n");
+    match ret {
+        Some(board) => write_solution(&format!("{board}\n"), output),
+        None => {
+            eprintln!("Failed");
+            ExitCode::from(1)
         }
     }
-
-    let Some(next) = least_possibilities_cell else {
-        // This means all cells are played, thus it's solved
-        return Some(board);
-    };
-
-    let possibilities = board.get(next).possibilities();
-    possibilities.par_iter().find_map_any(|&possibility| {
-        let mut new_board = board.clone();
-        new_board.play_cell(next, possibility);
-
-        solve(new_board)
-    })
 }
 
-fn main() {
-    let filename = std::env::args()
-        .skip(1)
-        .next()
-        .unwrap_or("examples/easy1.txt".into());
-
-    let Ok(board_str) = fs::read_to_string(&filename) else {
-        println!(
-            "Error finding file '{}'!\nMake sure the path is entered correctly and the file exists.",
-            filename
-        );
-        return;
+/// Prints `solution`, or writes it to `output` instead if given, erroring with exit code 2 if that fails
+fn write_solution(solution: &str, output: Option<&str>) -> ExitCode {
+    let Some(output) = output else {
+        print!("{solution}");
+        return ExitCode::SUCCESS;
     };
-
-    let board = Board::from_str(&board_str);
-    println!("{board}");
-    let ret = solve(board);
-
-    println!("FINISHED!\n======\n");
-    match ret {
-        Some(board) => println!("{board}"),
-        None => println!("Failed"),
+    match fs::write(output, solution) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error writing '{output}': {err}");
+            ExitCode::from(2)
+        }
     }
 }
@@ -0,0 +1,178 @@
+//! Cumulative solver metrics in the Prometheus text exposition format
+//!
+//! Counters are plain atomics, so a single [`Metrics`] can live in a `static` and be shared by every thread
+
+use std::{
+    fmt::Write,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{Board, SolveStats, Solver};
+
+/// Upper bounds of the difficulty histogram buckets, measured in search nodes
+const NODE_BUCKETS: [u64; 6] = [1, 10, 100, 1_000, 10_000, 100_000];
+
+/// Running totals over every solve recorded so far
+pub struct Metrics {
+    solved: AtomicU64,
+    failed: AtomicU64,
+    solve_micros: AtomicU64,
+    /// One count per bucket in [`NODE_BUCKETS`], then one for everything bigger
+    node_buckets: [AtomicU64; NODE_BUCKETS.len() + 1],
+    nodes: AtomicU64,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub const fn new() -> Self {
+        Self {
+            solved: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            solve_micros: AtomicU64::new(0),
+            node_buckets: [const { AtomicU64::new(0) }; NODE_BUCKETS.len() + 1],
+            nodes: AtomicU64::new(0),
+        }
+    }
+
+    /// Solves `board` with [`Solver::solve_with_stats`] and records how it went
+    pub fn solve(&self, board: Board) -> Option<Board> {
+        let start = Instant::now();
+        let (solution, stats) = Solver::new().solve_with_stats(board);
+        self.record(&stats, start.elapsed());
+        solution
+    }
+
+    /// Adds one finished solve to the totals; `stats` says whether it found a solution and how hard it was
+    pub fn record(&self, stats: &SolveStats, elapsed: Duration) {
+        match stats.solution_depth {
+            Some(_) => &self.solved,
+            None => &self.failed,
+        }
+        .fetch_add(1, Ordering::Relaxed);
+        self.solve_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+
+        let nodes = stats.nodes as u64;
+        let bucket = NODE_BUCKETS
+            .iter()
+            .position(|&bound| nodes <= bound)
+            .unwrap_or(NODE_BUCKETS.len());
+        self.node_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.nodes.fetch_add(nodes, Ordering::Relaxed);
+    }
+
+    /// Everything recorded so far, ready to be served from a `/metrics` endpoint
+    pub fn render_prometheus(&self) -> String {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        let solved = load(&self.solved);
+        let failed = load(&self.failed);
+        let mut out = String::new();
+
+        // Writing to a `String` can't fail
+        let _ = writeln!(
+            out,
+            "# HELP sudoku_solves_total Puzzles the solver finished, by outcome"
+        );
+        let _ = writeln!(out, "# TYPE sudoku_solves_total counter");
+        let _ = writeln!(out, "sudoku_solves_total{{outcome=\"solved\"}} {solved}");
+        let _ = writeln!(out, "sudoku_solves_total{{outcome=\"failed\"}} {failed}");
+
+        let _ = writeln!(out, "# HELP sudoku_solve_seconds_total Time spent solving");
+        let _ = writeln!(out, "# TYPE sudoku_solve_seconds_total counter");
+        let seconds = load(&self.solve_micros) as f64 / 1e6;
+        let _ = writeln!(out, "sudoku_solve_seconds_total {seconds}");
+
+        let _ = writeln!(
+            out,
+            "# HELP sudoku_search_nodes Search nodes explored per solve, a rough measure of difficulty"
+        );
+        let _ = writeln!(out, "# TYPE sudoku_search_nodes histogram");
+        let mut cumulative = 0;
+        for (bound, bucket) in NODE_BUCKETS.iter().zip(&self.node_buckets) {
+            cumulative += load(bucket);
+            let _ = writeln!(
+                out,
+                "sudoku_search_nodes_bucket{{le=\"{bound}\"}} {cumulative}"
+            );
+        }
+        // Counted from the same loads as the buckets, so a solve recorded meanwhile can't leave a bucket above it
+        cumulative += load(&self.node_buckets[NODE_BUCKETS.len()]);
+        let _ = writeln!(
+            out,
+            "sudoku_search_nodes_bucket{{le=\"+Inf\"}} {cumulative}"
+        );
+        let _ = writeln!(out, "sudoku_search_nodes_sum {}", load(&self.nodes));
+        let _ = writeln!(out, "sudoku_search_nodes_count {cumulative}");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The value of the line for `series` in `rendered`
+    fn value(rendered: &str, series: &str) -> f64 {
+        rendered
+            .lines()
+            .find_map(|line| line.strip_prefix(series)?.strip_prefix(' '))
+            .unwrap_or_else(|| panic!("no {series} in {rendered}"))
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative_up_to_the_count() {
+        let metrics = Metrics::new();
+        for (nodes, solved) in [(1, true), (5, true), (50, false), (200_000, true)] {
+            let stats = SolveStats {
+                nodes,
+                solution_depth: solved.then_some(0),
+                ..SolveStats::default()
+            };
+            metrics.record(&stats, Duration::from_millis(250));
+        }
+        let rendered = metrics.render_prometheus();
+
+        assert_eq!(
+            value(&rendered, "sudoku_solves_total{outcome=\"solved\"}"),
+            3.0
+        );
+        assert_eq!(
+            value(&rendered, "sudoku_solves_total{outcome=\"failed\"}"),
+            1.0
+        );
+        assert_eq!(value(&rendered, "sudoku_solve_seconds_total"), 1.0);
+        let buckets = ["1", "10", "100", "1000", "10000", "100000", "+Inf"].map(|bound| {
+            value(
+                &rendered,
+                &format!("sudoku_search_nodes_bucket{{le=\"{bound}\"}}"),
+            )
+        });
+        assert_eq!(buckets, [1.0, 2.0, 3.0, 3.0, 3.0, 3.0, 4.0]);
+        assert_eq!(value(&rendered, "sudoku_search_nodes_count"), 4.0);
+        assert_eq!(value(&rendered, "sudoku_search_nodes_sum"), 200_056.0);
+    }
+
+    #[test]
+    fn solve_records_the_outcome() {
+        let metrics = Metrics::new();
+        let board: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        assert!(metrics.solve(board).is_some());
+        let rendered = metrics.render_prometheus();
+        assert_eq!(
+            value(&rendered, "sudoku_solves_total{outcome=\"solved\"}"),
+            1.0
+        );
+        assert_eq!(
+            value(&rendered, "sudoku_search_nodes_bucket{le=\"1\"}"),
+            1.0
+        );
+    }
+}
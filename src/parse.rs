@@ -0,0 +1,628 @@
+//! Strict parsing that reports exactly where and why input was rejected
+
+use crate::{BOARD_CELLS, Board, BoardIdx, alphabet::Alphabet};
+
+/// A spot in the parsed input
+///
+/// For single-line input `column` is just the character offset plus one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// Starts at 1
+    pub line: usize,
+    /// Starts at 1, counted in characters
+    pub column: usize,
+    /// Characters from the start of the input, starting at 0
+    pub offset: usize,
+}
+
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Why a puzzle string was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A character that's neither a cell nor whitespace
+    UnexpectedChar { found: char, at: Position },
+    /// A number outside of `0..=9` in [`Board::from_values`] input
+    InvalidValue { found: u8, at: Position },
+    /// The input ran out before all 81 cells were filled
+    TooFewCells { found: usize },
+    /// The input keeps going after the 81st cell; `at` is the first extra cell
+    TooManyCells { at: Position },
+    /// A clue contradicts an earlier clue in the same row, column, or square
+    ConflictingClue {
+        idx: BoardIdx,
+        num: u8,
+        at: Position,
+    },
+    /// The puzzle is blank, so every valid grid solves it
+    NoGivens,
+    /// The puzzle has no solution
+    Unsolvable,
+    /// The puzzle has more than one solution
+    NotUnique,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::UnexpectedChar { found, at } => {
+                write!(f, "unexpected character '{found}' at {at}")
+            }
+            ParseError::InvalidValue { found, at } => {
+                write!(f, "invalid value {found} at {at}, expected 0 through 9")
+            }
+            ParseError::TooFewCells { found } => {
+                write!(f, "expected {BOARD_CELLS} cells but only found {found}")
+            }
+            ParseError::TooManyCells { at } => {
+                write!(f, "more than {BOARD_CELLS} cells, starting at {at}")
+            }
+            ParseError::ConflictingClue { idx, num, at } => write!(
+                f,
+                "the {num} at {at} ({idx}) conflicts with an earlier clue"
+            ),
+            ParseError::NoGivens => write!(f, "the puzzle has no givens"),
+            ParseError::Unsolvable => write!(f, "the puzzle has no solution"),
+            ParseError::NotUnique => write!(f, "the puzzle has more than one solution"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// How forgiving [`Board::parse_with`] is
+///
+/// The default skips stray characters like [`Board::from_str_lenient`], but reads the cells as one stream and only knows `x`
+/// for a blank. [`ParseOptions::strict`] is [`Board::try_from_str`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// The symbols for the numbers; any past the ninth don't stand for anything on a 9x9 board
+    pub alphabet: Alphabet,
+    /// Characters that mark a blank cell, matched ignoring ASCII case
+    pub empty: Vec<char>,
+    /// Error on characters that are neither cells nor whitespace, instead of skipping them
+    pub reject_unknown: bool,
+    /// Require exactly 81 cells, instead of filling in missing ones as blanks and ignoring any past the 81st
+    pub exact_cells: bool,
+    /// Error on a clue that contradicts an earlier one, instead of playing it anyway
+    pub reject_conflicts: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            alphabet: Alphabet::DIGITS,
+            empty: vec!['x'],
+            reject_unknown: false,
+            exact_cells: false,
+            reject_conflicts: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Rejects unknown characters, anything but 81 cells, and conflicting clues
+    pub fn strict() -> Self {
+        Self {
+            reject_unknown: true,
+            exact_cells: true,
+            reject_conflicts: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Characters [`Board::from_str`] takes for a blank cell
+pub(crate) const PASTED_BLANKS: [char; 6] = ['x', 'X', '.', '0', '_', '?'];
+
+/// How the rows of a pasted grid are separated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowLayout {
+    /// One row per line. Lines without any cells, like blank lines and box borders, don't count
+    Lines,
+    /// Rows separated by `/`
+    Slashes,
+    /// No row breaks at all, so every nine cells make a row
+    Unbroken,
+}
+
+impl RowLayout {
+    /// Works out how `board_str` separates its rows
+    ///
+    /// Any `/` means [`RowLayout::Slashes`]. Several lines of cells, none with more than nine, mean
+    /// [`RowLayout::Lines`]. Anything else, like a single line of 81 cells, is [`RowLayout::Unbroken`]
+    pub fn detect(board_str: &str) -> Self {
+        if board_str.contains('/') {
+            return RowLayout::Slashes;
+        }
+        let lines = board_str
+            .lines()
+            .map(|line| pasted_cells(line).len())
+            .filter(|&cells| cells > 0)
+            .collect::<Vec<_>>();
+        match lines.len() > 1 && lines.iter().all(|&cells| cells <= 9) {
+            true => RowLayout::Lines,
+            false => RowLayout::Unbroken,
+        }
+    }
+
+    /// The cells of each row of `board_str`, `None` for a blank, with nothing past the ninth row or ninth cell
+    ///
+    /// A short row is still its own row, so the blanks it leaves off at the end don't shift the rows after it
+    pub(crate) fn rows(self, board_str: &str) -> Vec<Vec<Option<u8>>> {
+        self.located_rows(board_str)
+            .into_iter()
+            .take(9)
+            .map(|row| row.into_iter().take(9).map(|(cell, _)| cell).collect())
+            .collect()
+    }
+
+    /// Like [`RowLayout::rows`], but with where each cell is in `board_str`, and keeping any extra rows and cells
+    fn located_rows(self, board_str: &str) -> Vec<Vec<(Option<u8>, Position)>> {
+        let mut cells = Vec::new();
+        let mut row = Vec::new();
+        let mut at = Position {
+            line: 1,
+            column: 1,
+            offset: 0,
+        };
+        for c in board_str.chars() {
+            let breaks_row = match self {
+                RowLayout::Lines => c == '\n',
+                RowLayout::Slashes => c == '/',
+                RowLayout::Unbroken => false,
+            };
+            if breaks_row {
+                cells.push(std::mem::take(&mut row));
+            } else if let Some(cell) = pasted_cell(c) {
+                row.push((cell, at));
+            }
+
+            if c == '\n' {
+                at.line += 1;
+                at.column = 1;
+            } else {
+                at.column += 1;
+            }
+            at.offset += 1;
+        }
+        cells.push(row);
+
+        let rows: Vec<Vec<_>> = match self {
+            RowLayout::Unbroken => cells.concat().chunks(9).map(<[_]>::to_vec).collect(),
+            _ => cells,
+        };
+        rows.into_iter().filter(|row| !row.is_empty()).collect()
+    }
+}
+
+/// The cells in `text`: a number for each `1`-`9`, `None` for each of [`PASTED_BLANKS`], and nothing for anything else
+fn pasted_cells(text: &str) -> Vec<Option<u8>> {
+    text.chars().filter_map(pasted_cell).collect()
+}
+
+/// What `c` means in a pasted grid: `Some` if it's a cell, holding its number unless it's a blank
+fn pasted_cell(c: char) -> Option<Option<u8>> {
+    match c {
+        '1'..='9' => Some(Some(c as u8 - b'0')),
+        _ if PASTED_BLANKS.contains(&c) => Some(None),
+        _ => None,
+    }
+}
+
+impl std::str::FromStr for Board {
+    type Err = ParseError;
+
+    /// Reads a pasted grid, working out how its rows are laid out with [`RowLayout::detect`], and rejects one that
+    /// doesn't describe exactly 81 cells or has a clue that clashes with an earlier one
+    ///
+    /// Cells are `1`-`9`, or one of `x . 0 _ ?` for a blank, and anything else, like box borders, is skipped.
+    /// Every row must have nine cells. [`Board::from_str_lenient`] takes the same input, but fills in what's missing
+    fn from_str(board_str: &str) -> Result<Self, Self::Err> {
+        let rows = RowLayout::detect(board_str).located_rows(board_str);
+        let found = rows.iter().map(Vec::len).sum();
+        if let Some(row) = rows.get(9) {
+            return Err(ParseError::TooManyCells { at: row[0].1 });
+        }
+        if rows.len() < 9 {
+            return Err(ParseError::TooFewCells { found });
+        }
+
+        let mut board = Board::new();
+        for (row, cells) in rows.iter().enumerate() {
+            if let Some(&(_, at)) = cells.get(9) {
+                return Err(ParseError::TooManyCells { at });
+            }
+            if cells.len() < 9 {
+                return Err(ParseError::TooFewCells { found });
+            }
+            for (col, &(cell, at)) in cells.iter().enumerate() {
+                if let Some(num) = cell {
+                    play_clue(&mut board, BoardIdx::new(col, row), num, at)?;
+                }
+            }
+        }
+        Ok(board)
+    }
+}
+
+impl Board {
+    /// Like [`Board::from_str`](Board#impl-FromStr-for-Board), but reads the cells as one stream without looking for rows, and is pickier about what
+    /// goes between them
+    ///
+    /// Cells are `1`-`9` or `x` for a blank, and only whitespace may separate them.
+    /// Errors point at the first problem in the input
+    pub fn try_from_str(board_str: &str) -> Result<Self, ParseError> {
+        Board::parse_with(board_str, &ParseOptions::strict())
+    }
+
+    /// Like [`Board::try_from_str`], but with cells written in `alphabet` instead of `1`-`9`
+    pub fn try_from_str_in(board_str: &str, alphabet: &Alphabet) -> Result<Self, ParseError> {
+        let options = ParseOptions {
+            alphabet: *alphabet,
+            ..ParseOptions::strict()
+        };
+        Board::parse_with(board_str, &options)
+    }
+
+    /// Parses a board row by row, as forgiving as `options` allows
+    ///
+    /// Cells are read in row-major order, and errors point at the first problem in the input
+    pub fn parse_with(board_str: &str, options: &ParseOptions) -> Result<Self, ParseError> {
+        let mut board = Board::new();
+        let mut filled = 0;
+        let mut at = Position {
+            line: 1,
+            column: 1,
+            offset: 0,
+        };
+        let is_empty = |c: char| {
+            options
+                .empty
+                .iter()
+                .any(|empty| empty.eq_ignore_ascii_case(&c))
+        };
+
+        for c in board_str.chars() {
+            let num = match options.alphabet.value(c).filter(|&num| num <= 9) {
+                Some(num) => Some(num),
+                None if is_empty(c) => None,
+                None => {
+                    if c == '\n' {
+                        at.line += 1;
+                        at.column = 1;
+                    } else if c.is_whitespace() || !options.reject_unknown {
+                        at.column += 1;
+                    } else {
+                        return Err(ParseError::UnexpectedChar {
+                            found: c.to_ascii_lowercase(),
+                            at,
+                        });
+                    }
+                    at.offset += 1;
+                    continue;
+                }
+            };
+
+            if filled == BOARD_CELLS {
+                if options.exact_cells {
+                    return Err(ParseError::TooManyCells { at });
+                }
+                break;
+            }
+            if let Some(num) = num {
+                let idx = BoardIdx::from_linear(filled);
+                match options.reject_conflicts {
+                    true => play_clue(&mut board, idx, num, at)?,
+                    false => board.play_cell(idx, num),
+                }
+            }
+
+            filled += 1;
+            at.column += 1;
+            at.offset += 1;
+        }
+
+        if filled < BOARD_CELLS && options.exact_cells {
+            return Err(ParseError::TooFewCells { found: filled });
+        }
+        Ok(board)
+    }
+
+    /// Builds a board from 81 row-major values, `0` for an empty cell and `1`-`9` for a clue
+    ///
+    /// Values are checked just like [`Board::try_from_str`] checks its cells, treating the slice as a single line
+    pub fn from_values(values: &[u8]) -> Result<Self, ParseError> {
+        let at = |offset| Position {
+            line: 1,
+            column: offset + 1,
+            offset,
+        };
+        if values.len() < BOARD_CELLS {
+            return Err(ParseError::TooFewCells {
+                found: values.len(),
+            });
+        }
+        if values.len() > BOARD_CELLS {
+            return Err(ParseError::TooManyCells {
+                at: at(BOARD_CELLS),
+            });
+        }
+
+        let mut board = Board::new();
+        for (offset, &num) in values.iter().enumerate() {
+            match num {
+                0 => (),
+                1..=9 => play_clue(&mut board, BoardIdx::from_linear(offset), num, at(offset))?,
+                found => {
+                    return Err(ParseError::InvalidValue {
+                        found,
+                        at: at(offset),
+                    });
+                }
+            }
+        }
+        Ok(board)
+    }
+}
+
+impl Board {
+    /// Reads the line format of the `sudoku` crate's `Sudoku::from_str_line`, for exchanging boards with tools built on it
+    ///
+    /// That's exactly 81 cells on one line, `1`-`9` or one of `. 0 _` for a blank, optionally followed by whitespace
+    /// and a comment. Clues are checked against each other like [`Board::try_from_str`] does.
+    /// [`Board::to_sudoku_line`] writes the same format back
+    pub fn from_sudoku_line(line: &str) -> Result<Self, ParseError> {
+        let at = |offset| Position {
+            line: 1,
+            column: offset + 1,
+            offset,
+        };
+        let mut board = Board::new();
+        let mut chars = line.chars().enumerate();
+        for (offset, c) in chars.by_ref().take(BOARD_CELLS) {
+            match c {
+                '.' | '0' | '_' => (),
+                '1'..='9' => play_clue(
+                    &mut board,
+                    BoardIdx::from_linear(offset),
+                    c as u8 - b'0',
+                    at(offset),
+                )?,
+                c if c.is_whitespace() => return Err(ParseError::TooFewCells { found: offset }),
+                found => {
+                    return Err(ParseError::UnexpectedChar {
+                        found,
+                        at: at(offset),
+                    });
+                }
+            }
+        }
+        match chars.next() {
+            None if line.chars().count() < BOARD_CELLS => Err(ParseError::TooFewCells {
+                found: line.chars().count(),
+            }),
+            Some((offset, c)) if !c.is_whitespace() => {
+                Err(ParseError::TooManyCells { at: at(offset) })
+            }
+            _ => Ok(board),
+        }
+    }
+}
+
+/// Plays a clue read from the input at `at`, unless an earlier clue conflicts with it
+fn play_clue(board: &mut Board, idx: BoardIdx, num: u8, at: Position) -> Result<(), ParseError> {
+    // Only an earlier clue among the peers can have ruled the number out
+    if !board.get(idx).is_possible(num) {
+        return Err(ParseError::ConflictingClue { idx, num, at });
+    }
+    board.play_cell(idx, num);
+    Ok(())
+}
+
+/// Whether `s` is a complete, valid grid: 81 digits `1`-`9`, row by row, with every row, column, and square
+/// holding each number once
+///
+/// Whitespace is skipped and anything else makes it invalid, including blanks. Never allocates or builds a [`Board`]
+pub fn is_valid_solution(s: &str) -> bool {
+    let mut rows = [0u16; 9];
+    let mut cols = [0u16; 9];
+    let mut squares = [0u16; 9];
+    let mut filled = 0;
+    for c in s.chars().filter(|c| !c.is_whitespace()) {
+        let Some(num) = c.to_digit(10).filter(|&num| num != 0) else {
+            return false;
+        };
+        if filled == BOARD_CELLS {
+            return false;
+        }
+        let (row, col) = (filled / 9, filled % 9);
+        let square = row / 3 * 3 + col / 3;
+        let bit = 1 << num;
+        if (rows[row] | cols[col] | squares[square]) & bit != 0 {
+            return false;
+        }
+        rows[row] |= bit;
+        cols[col] |= bit;
+        squares[square] |= bit;
+        filled += 1;
+    }
+    filled == BOARD_CELLS
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    const BLANK_ROW: &str = "x x x x x x x x x\n";
+
+    #[test]
+    fn unexpected_char_points_at_its_line_and_column() {
+        let grid = [BLANK_ROW, BLANK_ROW, "x x a x x x x x x\n"].concat() + &BLANK_ROW.repeat(6);
+        assert_eq!(
+            Board::try_from_str(&grid),
+            Err(ParseError::UnexpectedChar {
+                found: 'a',
+                at: Position {
+                    line: 3,
+                    column: 5,
+                    offset: 40,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn single_line_positions_count_characters() {
+        let line = format!("{}?{}", "x".repeat(10), "x".repeat(70));
+        assert_eq!(
+            Board::try_from_str(&line),
+            Err(ParseError::UnexpectedChar {
+                found: '?',
+                at: Position {
+                    line: 1,
+                    column: 11,
+                    offset: 10,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn conflicting_clue_points_at_the_second_clue() {
+        let line = format!("5{}5{}", "x".repeat(3), "x".repeat(76));
+        assert_eq!(
+            Board::try_from_str(&line),
+            Err(ParseError::ConflictingClue {
+                idx: BoardIdx::new(4, 0),
+                num: 5,
+                at: Position {
+                    line: 1,
+                    column: 5,
+                    offset: 4,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn too_many_cells_points_at_the_first_extra() {
+        let grid = BLANK_ROW.repeat(9) + "x";
+        assert_eq!(
+            Board::try_from_str(&grid),
+            Err(ParseError::TooManyCells {
+                at: Position {
+                    line: 10,
+                    column: 1,
+                    offset: 162,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn too_few_cells_counts_what_was_there() {
+        assert_eq!(
+            Board::try_from_str(&BLANK_ROW.repeat(8)),
+            Err(ParseError::TooFewCells { found: 72 })
+        );
+    }
+
+    /// A valid grid, each row the one above shifted along
+    fn pattern_solution() -> String {
+        (0..9)
+            .flat_map(|row| (0..9).map(move |col| (row * 3 + row / 3 + col) % 9 + 1))
+            .map(|num| char::from_digit(num as u32, 10).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn is_valid_solution_accepts_full_grids() {
+        let solution = pattern_solution();
+        assert!(is_valid_solution(&solution));
+        let spaced = (0..9)
+            .map(|row| solution[row * 9..][..9].chars().join(" "))
+            .join("\n");
+        assert!(is_valid_solution(&spaced));
+        let easy: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        assert!(is_valid_solution(
+            &crate::solve(&easy).unwrap().to_sudoku_line()
+        ));
+    }
+
+    #[test]
+    fn is_valid_solution_rejects_incomplete_grids() {
+        let solution = pattern_solution();
+        assert!(!is_valid_solution(""));
+        assert!(!is_valid_solution(&solution[..80]));
+        assert!(!is_valid_solution(&solution.replacen('5', ".", 1)));
+        assert!(!is_valid_solution(&solution.replacen('5', "0", 1)));
+        assert!(!is_valid_solution(&(solution + "1")));
+    }
+
+    #[test]
+    fn is_valid_solution_rejects_conflicts() {
+        let solution = pattern_solution();
+        // Swapping two cells in a row keeps the row whole but breaks both columns
+        let mut swapped = solution.clone().into_bytes();
+        swapped.swap(0, 1);
+        assert!(!is_valid_solution(std::str::from_utf8(&swapped).unwrap()));
+        // The same row twice over repeats every number down the columns
+        assert!(!is_valid_solution(&solution[..9].repeat(9)));
+    }
+
+    /// A puzzle written the way the `sudoku` crate's `to_str_line` writes one
+    const SUDOKU_LINE: &str =
+        "...2...633....54.1..1..398........9....538....3........263..5..5.37....847...1...";
+
+    #[test]
+    fn sudoku_line_round_trip() {
+        let board = Board::from_sudoku_line(SUDOKU_LINE).unwrap();
+        assert_eq!(board.to_sudoku_line(), SUDOKU_LINE);
+        assert_eq!(
+            board.clue_count(),
+            SUDOKU_LINE.matches(|c: char| c.is_ascii_digit()).count()
+        );
+
+        let with_zeros = SUDOKU_LINE.replace('.', "0");
+        let from_zeros = Board::from_sudoku_line(&format!("{with_zeros} a comment")).unwrap();
+        assert_eq!(from_zeros.to_sudoku_line(), SUDOKU_LINE);
+    }
+
+    #[test]
+    fn sudoku_line_is_strict() {
+        assert_eq!(
+            Board::from_sudoku_line(&SUDOKU_LINE[..80]),
+            Err(ParseError::TooFewCells { found: 80 })
+        );
+        assert_eq!(
+            Board::from_sudoku_line(&format!("{} {}", &SUDOKU_LINE[..40], &SUDOKU_LINE[40..])),
+            Err(ParseError::TooFewCells { found: 40 })
+        );
+        assert!(matches!(
+            Board::from_sudoku_line(&SUDOKU_LINE.replacen('.', "x", 1)),
+            Err(ParseError::UnexpectedChar { found: 'x', .. })
+        ));
+        assert_eq!(
+            Board::from_sudoku_line(&format!("{SUDOKU_LINE}1")),
+            Err(ParseError::TooManyCells {
+                at: Position {
+                    line: 1,
+                    column: 82,
+                    offset: 81
+                }
+            })
+        );
+        // Two 2s in the top row
+        assert!(matches!(
+            Board::from_sudoku_line(&SUDOKU_LINE.replacen('.', "2", 1)),
+            Err(ParseError::ConflictingClue { num: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,562 @@
+//! Turning boards into text
+
+use itertools::Itertools;
+
+use crate::{BOARD_CELLS, Board, BoardIdx, Cell, alphabet::Alphabet};
+
+/// Which characters stand in for cells that aren't down to a single number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Drawn for cells with more than one possibility left
+    pub empty: char,
+    /// Drawn for cells with no possibilities left
+    pub contradiction: char,
+    /// The symbols for cells that are down to a single number, which needs at least nine
+    pub alphabet: Alphabet,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            empty: '?',
+            contradiction: 'F',
+            alphabet: Alphabet::DIGITS,
+        }
+    }
+}
+
+impl Cell {
+    /// The character drawn for this cell: its number once it's down to one, otherwise a marker from `options`
+    pub fn symbol(&self, options: &RenderOptions) -> char {
+        match self.possibilities()[..] {
+            [] => options.contradiction,
+            [num] => options.alphabet.symbol(num),
+            _ => options.empty,
+        }
+    }
+}
+
+impl Board {
+    /// The board as nine rows of space-separated cells, drawn with the markers from `options`
+    ///
+    /// This is what `Display` uses, with the default markers
+    pub fn render(&self, options: &RenderOptions) -> String {
+        let mut s = String::new();
+        for row in 0..9 {
+            for col in 0..9 {
+                s.push(self.get(BoardIdx::new(col, row)).symbol(options));
+                s.push(' ');
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Each cell's character, indexed `[row][col]`, so a UI doesn't have to take apart the rendered string
+    ///
+    /// Uses the default markers: `?` for a cell with several candidates left and `F` for one with none
+    pub fn to_char_grid(&self) -> [[char; 9]; 9] {
+        let options = RenderOptions::default();
+        std::array::from_fn(|row| {
+            std::array::from_fn(|col| self.get(BoardIdx::new(col, row)).symbol(&options))
+        })
+    }
+
+    /// The board as one line of 81 characters in row-major order, with the markers of [`Board::to_char_grid`]
+    pub fn to_line(&self) -> String {
+        self.to_char_grid().iter().flatten().collect()
+    }
+
+    /// The board boxed in like [`Board::to_pretty_string`], with rows labeled `A`-`I` down the left and columns
+    /// `1`-`9` across the top, so a cell can be called out chessboard style, like `C7` for row 3, column 7
+    ///
+    /// Cells use the default markers
+    pub fn to_ascii_with_coordinates(&self) -> String {
+        let mut s = String::from("   ");
+        for stack in 0..3 {
+            s.push_str(&format!(
+                " {} {} {}  ",
+                stack * 3 + 1,
+                stack * 3 + 2,
+                stack * 3 + 3
+            ));
+        }
+        s.truncate(s.trim_end().len());
+        s.push('\n');
+        s.push_str(&self.boxed(
+            &RenderOptions::default(),
+            Some(&['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I']),
+        ));
+        s
+    }
+
+    /// The board in the classic newspaper layout, squares boxed in with `+`, `-`, and `|`, and a `.` for every cell
+    /// that isn't down to one number, for reading at a glance
+    ///
+    /// Unlike `Display`, it takes up 13 lines instead of 9
+    pub fn to_pretty_string(&self) -> String {
+        let options = RenderOptions {
+            empty: '.',
+            ..RenderOptions::default()
+        };
+        self.boxed(&options, None)
+    }
+
+    /// The grid drawn with `options`, squares boxed in, and each row starting with its label from `labels` if given
+    fn boxed(&self, options: &RenderOptions, labels: Option<&[char; 9]>) -> String {
+        let margin = match labels {
+            Some(_) => "  ",
+            None => "",
+        };
+        let border = format!("{margin}+{}\n", "-------+".repeat(3));
+        let mut s = border.clone();
+        for row in 0..9 {
+            if let Some(labels) = labels {
+                s.push(labels[row]);
+                s.push(' ');
+            }
+            s.push('|');
+            for col in 0..9 {
+                s.push(' ');
+                s.push(self.get(BoardIdx::new(col, row)).symbol(options));
+                if col % 3 == 2 {
+                    s.push_str(" |");
+                }
+            }
+            s.push('\n');
+            if row % 3 == 2 {
+                s.push_str(&border);
+            }
+        }
+        s
+    }
+
+    /// The board as keycap emoji, like 1️⃣, for pasting into a chat, with squares split apart by ⬛
+    ///
+    /// Built from [`Board::to_char_grid`]; any cell that isn't a single number is a ⬜.
+    /// Chat apps draw emoji at a fixed width, so the grid lines up where plain text might not
+    pub fn to_emoji(&self) -> String {
+        let separator = "⬛".repeat(11);
+        let mut s = String::new();
+        for (row, chars) in self.to_char_grid().iter().enumerate() {
+            if row > 0 && row % 3 == 0 {
+                s.push_str(&separator);
+                s.push('\n');
+            }
+            for (col, &c) in chars.iter().enumerate() {
+                if col > 0 && col % 3 == 0 {
+                    s.push('⬛');
+                }
+                match c {
+                    '1'..='9' => {
+                        s.push(c);
+                        s.push_str("\u{FE0F}\u{20E3}");
+                    }
+                    _ => s.push('⬜'),
+                }
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    /// The played cells as one line of 81 characters, with `.` for every other cell, in the line format of the
+    /// `sudoku` crate that [`Board::from_sudoku_line`] reads
+    ///
+    /// Unlike [`Board::to_line`], a cell that's only been narrowed down to one candidate stays blank
+    pub fn to_sudoku_line(&self) -> String {
+        (0..BOARD_CELLS)
+            .map(BoardIdx::from_linear)
+            .map(|idx| match self.played.contains(&idx) {
+                true => self.get(idx).symbol(&RenderOptions::default()),
+                false => '.',
+            })
+            .collect()
+    }
+
+    /// The board with every unplayed cell drawn as a 3x3 grid of its candidates, each number in its own spot
+    /// like on a phone keypad and `.` where it's been ruled out, and every played cell as just its number in the middle
+    ///
+    /// Squares are boxed in with `+`, `-`, and `|`, and the cells within them are separated by a space or a blank line
+    pub fn render_pencilmarks(&self) -> String {
+        let border = format!("+{}\n", "-------------+".repeat(3));
+        let spacer = format!("|{}\n", "             |".repeat(3));
+        let mut s = border.clone();
+        for row in 0..9 {
+            for line in 0..3 {
+                s.push('|');
+                for col in 0..9 {
+                    let idx = BoardIdx::new(col, row);
+                    s.push(' ');
+                    for spot in 0..3 {
+                        let num = (line * 3 + spot + 1) as u8;
+                        s.push(match self.played.contains(&idx) {
+                            true if line == 1 && spot == 1 => {
+                                self.get(idx).symbol(&RenderOptions::default())
+                            }
+                            true => ' ',
+                            false if self.get(idx).is_possible(num) => char::from(b'0' + num),
+                            false => '.',
+                        });
+                    }
+                    if col % 3 == 2 {
+                        s.push_str(" |");
+                    }
+                }
+                s.push('\n');
+            }
+            s.push_str(match row % 3 == 2 {
+                true => &border,
+                false => &spacer,
+            });
+        }
+        s
+    }
+
+    /// The board as an undirected Graphviz DOT graph: one node per cell and an edge between every pair of peers
+    ///
+    /// Played cells are labelled with their number and shaded, the rest with their position. Cells related only by
+    /// the board's constraints, like a knight's move apart, are joined with dashed edges
+    pub fn to_dot(&self) -> String {
+        self.dot_graph(None)
+    }
+
+    /// Like [`Board::to_dot`], but only draws the edges from `idx` to its peers, with `idx` outlined
+    pub fn to_dot_around(&self, idx: BoardIdx) -> String {
+        self.dot_graph(Some(idx))
+    }
+
+    fn dot_graph(&self, highlight: Option<BoardIdx>) -> String {
+        let node = |idx: BoardIdx| format!("r{}c{}", idx.row + 1, idx.col + 1);
+        let cells = (0..81).map(BoardIdx::from_linear);
+
+        let mut s = String::from("graph sudoku {\n    node [shape=box];\n");
+        for idx in cells.clone() {
+            let mut attributes = match self.played.contains(&idx) {
+                true => format!(
+                    "label=\"{}\", style=filled",
+                    self.get(idx).symbol(&RenderOptions::default())
+                ),
+                false => format!("label=\"{idx}\""),
+            };
+            if highlight == Some(idx) {
+                attributes.push_str(", penwidth=3");
+            }
+            s.push_str(&format!("    {} [{attributes}];\n", node(idx)));
+        }
+
+        for idx in cells {
+            if highlight.is_some_and(|highlight| highlight != idx) {
+                continue;
+            }
+            let peers = self.peers(idx);
+            let constraint_peers = self.constraint_peers(idx);
+            let edges = peers
+                .iter()
+                .map(|&peer| (peer, ""))
+                .chain(
+                    constraint_peers
+                        .iter()
+                        .unique()
+                        .filter(|peer| !peers.contains(peer))
+                        .map(|&peer| (peer, " [style=dashed]")),
+                )
+                // Each edge once, from its lower cell, unless only one cell's edges are drawn
+                .filter(|&(peer, _)| highlight.is_some() || peer.idx > idx.idx);
+            for (peer, style) in edges {
+                s.push_str(&format!("    {} -- {}{style};\n", node(idx), node(peer)));
+            }
+        }
+        s.push_str("}\n");
+        s
+    }
+}
+
+/// The puzzle and its solution next to each other, row by row, for the terminal
+///
+/// Only the puzzle's played cells count as givens, so numbers it has merely narrowed down are left out of it.
+/// The solution marks the givens with a `*` after them, so the filled-in cells are the unmarked ones.
+/// Both grids are drawn with the default markers
+pub fn render_side_by_side(puzzle: &Board, solution: &Board) -> String {
+    let options = RenderOptions::default();
+    let mut s = String::new();
+    for row in 0..9 {
+        for col in 0..9 {
+            let idx = BoardIdx::new(col, row);
+            s.push(match puzzle.played.contains(&idx) {
+                true => puzzle.get(idx).symbol(&options),
+                false => options.empty,
+            });
+            s.push(' ');
+        }
+        s.push_str("| ");
+        for col in 0..9 {
+            let idx = BoardIdx::new(col, row);
+            s.push(solution.get(idx).symbol(&options));
+            s.push(match puzzle.played.contains(&idx) {
+                true => '*',
+                false => ' ',
+            });
+        }
+        // Don't leave the trailing marker space at the end of the line
+        s.truncate(s.trim_end().len());
+        s.push('\n');
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::{Board, BoardIdx, Cell};
+
+    fn easy() -> Board {
+        include_str!("../examples/easy1.txt").parse().unwrap()
+    }
+
+    #[test]
+    fn pretty_string() {
+        let expected = "+-------+-------+-------+
+| . . 1 | 2 7 6 | 9 . . |
+| . 4 9 | 1 3 8 | . . . |
+| . . 7 | 9 4 5 | . 1 3 |
++-------+-------+-------+
+| 3 7 4 | . 2 9 | . 6 1 |
+| 1 8 5 | 3 6 . | . . 9 |
+| 9 . 2 | 5 . 4 | . 7 8 |
++-------+-------+-------+
+| . . 6 | 7 . 1 | . . 2 |
+| 2 1 8 | 6 9 3 | . . 5 |
+| . . . | . . . | . . . |
++-------+-------+-------+
+";
+        assert_eq!(easy().to_pretty_string(), expected);
+    }
+
+    #[test]
+    fn ascii_with_coordinates() {
+        let expected = "    1 2 3   4 5 6   7 8 9
+  +-------+-------+-------+
+A | ? ? 1 | 2 7 6 | 9 ? ? |
+B | ? 4 9 | 1 3 8 | ? ? ? |
+C | ? ? 7 | 9 4 5 | ? 1 3 |
+  +-------+-------+-------+
+D | 3 7 4 | ? 2 9 | ? 6 1 |
+E | 1 8 5 | 3 6 ? | ? ? 9 |
+F | 9 ? 2 | 5 ? 4 | ? 7 8 |
+  +-------+-------+-------+
+G | ? ? 6 | 7 ? 1 | ? ? 2 |
+H | 2 1 8 | 6 9 3 | ? ? 5 |
+I | ? ? ? | ? ? ? | ? ? ? |
+  +-------+-------+-------+
+";
+        assert_eq!(easy().to_ascii_with_coordinates(), expected);
+    }
+
+    #[test]
+    fn coordinates_line_up_with_the_cells() {
+        let mut board = Board::new();
+        board.play_cell(BoardIdx::new(6, 4), 8);
+        let rendered = board.to_ascii_with_coordinates();
+        let lines = rendered.lines().collect_vec();
+        let row = lines.iter().find(|line| line.starts_with('E')).unwrap();
+        assert_eq!(row.find('8'), lines[0].find('7'));
+        assert_eq!(row.matches('8').count(), 1);
+    }
+
+    #[test]
+    fn pencilmarks() {
+        let expected = "+-------------+-------------+-------------+
+| ... ..3     |         ... |     ... ... |
+| .56 .56  1  |  2   7  ..6 |  9  45. 4.6 |
+| .8. ...     |         ... |     .8. ... |
+|             |             |             |
+| ...         |             | .2. .2. ... |
+| .56  4   9  |  1   3   8  | .56 .5. ..6 |
+| ...         |             | 7.. ... 7.. |
+|             |             |             |
+| ... .2.     | ...         | .2.         |
+| ..6 ..6  7  | ...  4   5  | ..6  1   3  |
+| .8. ...     | ..9         | .8.         |
++-------------+-------------+-------------+
+|         ... | ...         | ...         |
+|  3   7  4.. | .5.  2   9  | 45.  6   1  |
+|         ... | .8.         | ...         |
+|             |             |             |
+|             |     ... ... | .2. .2.     |
+|  1   8   5  |  3  ..6 ..6 | 4.. 4..  9  |
+|             |     ... 7.. | ... ...     |
+|             |             |             |
+|     .2. .2. | ... 1..     | .23         |
+|  9  ..6 ... | .5. .56  4  | .5.  7   8  |
+|     ... ... | ... ...     | ...         |
++-------------+-------------+-------------+
+| ... ..3     |     1.. 1.. | 1.3 ..3     |
+| 45. .5.  6  |  7  .5. ... | 4.. 4..  2  |
+| ... ..9     |     .89 ... | .8. .89     |
+|             |             |             |
+|             |     ...     | ... ...     |
+|  2   1   8  |  6  ...  3  | 4.. 4..  5  |
+|             |     ..9     | 7.. ..9     |
+|             |             |             |
+| ... ..3 ..3 | ... 1.. 12. | 1.3 ..3 ... |
+| 45. .5. 4.. | 45. .5. ... | 4.6 4.. 4.6 |
+| 7.. ..9 ... | .89 .89 ... | 78. .89 7.. |
++-------------+-------------+-------------+
+";
+        assert_eq!(easy().render_pencilmarks(), expected);
+    }
+
+    #[test]
+    fn pencilmarks_of_a_played_and_an_emptied_cell() {
+        let mut board = Board::new();
+        board.play_cell(BoardIdx::new(0, 0), 5);
+        board.set_raw(BoardIdx::new(1, 0), Cell::none_possible());
+        let top = board.render_pencilmarks().lines().take(4).join("\n");
+        let expected = "+-------------+-------------+-------------+
+|     ... 123 | 123 123 123 | 123 123 123 |
+|  5  ... 4.6 | 4.6 4.6 4.6 | 4.6 4.6 4.6 |
+|     ... 789 | 789 789 789 | 789 789 789 |";
+        assert_eq!(top, expected);
+    }
+
+    #[test]
+    fn display() {
+        let expected = "? ? 1 2 7 6 9 ? ? 
+? 4 9 1 3 8 ? ? ? 
+? ? 7 9 4 5 ? 1 3 
+3 7 4 ? 2 9 ? 6 1 
+1 8 5 3 6 ? ? ? 9 
+9 ? 2 5 ? 4 ? 7 8 
+? ? 6 7 ? 1 ? ? 2 
+2 1 8 6 9 3 ? ? 5 
+? ? ? ? ? ? ? ? ? 
+";
+        assert_eq!(easy().to_string(), expected);
+    }
+
+    #[test]
+    fn sudoku_line() {
+        assert_eq!(
+            easy().to_sudoku_line(),
+            "..127.9...49138.....7.45.1337..29.611853....99....4.78..67....22186.3..5........."
+        );
+    }
+
+    #[test]
+    fn side_by_side_marks_the_givens() {
+        let puzzle = easy();
+        let solution = crate::solve(&puzzle).unwrap();
+        let expected = "? ? 1 2 7 ? 9 ? ? | 5 3 1*2*7*6 9*8 4
+? 4 9 1 3 8 ? ? ? | 6 4*9*1*3*8*2 5 7
+? ? 7 ? 4 5 ? 1 3 | 8 2 7*9 4*5*6 1*3*
+3 7 ? ? 2 9 ? 6 1 | 3*7*4 8 2*9*5 6*1*
+1 8 5 3 ? ? ? ? 9 | 1*8*5*3*6 7 4 2 9*
+9 ? ? ? ? 4 ? 7 8 | 9*6 2 5 1 4*3 7*8*
+? ? 6 7 ? ? ? ? 2 | 4 9 6*7*5 1 8 3 2*
+2 1 8 6 ? 3 ? ? 5 | 2*1*8*6*9 3*7 4 5*
+? ? ? ? ? ? ? ? ? | 7 5 3 4 8 2 1 9 6
+";
+        assert_eq!(super::render_side_by_side(&puzzle, &solution), expected);
+    }
+
+    #[test]
+    fn emoji_of_a_solved_board() {
+        let expected = "5️⃣3️⃣1️⃣⬛2️⃣7️⃣6️⃣⬛9️⃣8️⃣4️⃣
+6️⃣4️⃣9️⃣⬛1️⃣3️⃣8️⃣⬛2️⃣5️⃣7️⃣
+8️⃣2️⃣7️⃣⬛9️⃣4️⃣5️⃣⬛6️⃣1️⃣3️⃣
+⬛⬛⬛⬛⬛⬛⬛⬛⬛⬛⬛
+3️⃣7️⃣4️⃣⬛8️⃣2️⃣9️⃣⬛5️⃣6️⃣1️⃣
+1️⃣8️⃣5️⃣⬛3️⃣6️⃣7️⃣⬛4️⃣2️⃣9️⃣
+9️⃣6️⃣2️⃣⬛5️⃣1️⃣4️⃣⬛3️⃣7️⃣8️⃣
+⬛⬛⬛⬛⬛⬛⬛⬛⬛⬛⬛
+4️⃣9️⃣6️⃣⬛7️⃣5️⃣1️⃣⬛8️⃣3️⃣2️⃣
+2️⃣1️⃣8️⃣⬛6️⃣9️⃣3️⃣⬛7️⃣4️⃣5️⃣
+7️⃣5️⃣3️⃣⬛4️⃣8️⃣2️⃣⬛1️⃣9️⃣6️⃣
+";
+        let solved = crate::solve(&easy()).unwrap();
+        assert_eq!(solved.to_emoji(), expected);
+    }
+
+    #[test]
+    fn emoji_leaves_open_cells_white() {
+        let mut board = Board::new();
+        board.play_cell(BoardIdx::new(0, 0), 5);
+        let first_line = board.to_emoji().lines().next().unwrap().to_string();
+        assert_eq!(first_line, "5\u{FE0F}\u{20E3}⬜⬜⬛⬜⬜⬜⬛⬜⬜⬜");
+    }
+
+    /// The `(from, to, dashed)` edges of a DOT graph, with each pair's lower cell first
+    fn dot_edges(dot: &str) -> Vec<(String, String, bool)> {
+        dot.lines()
+            .filter_map(|line| {
+                let (from, rest) = line.trim().split_once(" -- ")?;
+                let to = rest.trim_end_matches(';').split(' ').next()?;
+                let (from, to) = (from.min(to), from.max(to));
+                Some((from.into(), to.into(), rest.contains("style=dashed")))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dot_graph_draws_each_peer_pair_once() {
+        let dot = Board::new().to_dot();
+        assert_eq!(
+            dot.lines().filter(|line| line.contains(" [label=")).count(),
+            81
+        );
+        let edges = dot_edges(&dot);
+        assert_eq!(edges.len(), 81 * 20 / 2);
+        assert_eq!(edges.iter().unique().count(), edges.len());
+        assert!(edges.iter().all(|&(_, _, dashed)| !dashed));
+
+        let played = easy().to_dot();
+        assert!(played.contains("    r1c3 [label=\"1\", style=filled];\n"));
+        assert!(played.contains("    r1c1 [label=\"R1C1\"];\n"));
+    }
+
+    #[test]
+    fn dot_graph_dashes_constraint_only_peers() {
+        let mut board = Board::new();
+        board.add_constraint(crate::constraint::Constraint::AntiKnight);
+        let knight_pairs_across_squares = (0..81)
+            .map(BoardIdx::from_linear)
+            .tuple_combinations()
+            .filter(|(a, b)| {
+                let moves = (a.row.abs_diff(b.row), a.col.abs_diff(b.col));
+                matches!(moves, (1, 2) | (2, 1)) && a.square() != b.square()
+            })
+            .count();
+
+        let edges = dot_edges(&board.to_dot());
+        assert_eq!(edges.iter().unique().count(), edges.len());
+        let dashed = edges.iter().filter(|&&(_, _, dashed)| dashed).count();
+        assert_eq!(dashed, knight_pairs_across_squares);
+        assert_eq!(edges.len() - dashed, 810);
+        assert!(
+            edges.contains(&("r1c2".into(), "r2c4".into(), true)),
+            "a knight's move into the next square"
+        );
+        assert!(
+            edges.contains(&("r1c1".into(), "r2c3".into(), false)),
+            "a knight's move inside the square is already a peer"
+        );
+    }
+
+    #[test]
+    fn dot_graph_around_a_cell_draws_only_its_edges() {
+        let dot = Board::new().to_dot_around(BoardIdx::new(4, 4));
+        assert_eq!(
+            dot.lines().filter(|line| line.contains(" [label=")).count(),
+            81
+        );
+        assert!(dot.contains("    r5c5 [label=\"R5C5\", penwidth=3];\n"));
+        let lines = dot
+            .lines()
+            .filter(|line| line.contains(" -- "))
+            .collect_vec();
+        assert_eq!(lines.len(), 20);
+        assert!(lines.iter().all(|line| line.trim().starts_with("r5c5 -- ")));
+    }
+}
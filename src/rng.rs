@@ -0,0 +1,35 @@
+//! A small seeded random number generator, so generated puzzles can be reproduced exactly
+
+/// SplitMix64: tiny, fast, and good enough for shuffling cells. Not for anything security related
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// The same seed always gives the same sequence, on every platform
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`, which must not be 0
+    pub fn below(&mut self, bound: usize) -> usize {
+        // Widening first keeps the bias too small to matter for the bounds used here
+        ((self.next_u64() as u128 * bound as u128) >> 64) as usize
+    }
+
+    /// Puts `items` in a uniformly random order
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, self.below(idx + 1));
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Samurai Sudoku: five 9x9 grids in an X shape, where the middle grid shares each corner box with an outer grid
+
+use crate::{Board, BoardIdx, Cell};
+
+/// Side length of the whole Samurai layout, in cells
+const SAMURAI_SIZE: usize = 21;
+
+/// Where each grid's top left cell sits in the layout, as `(col, row)`
+///
+/// In order: top left, top right, middle, bottom left, bottom right
+pub const GRID_OFFSETS: [(usize, usize); 5] = [(0, 0), (12, 0), (6, 6), (0, 12), (12, 12)];
+
+/// The grid in the middle, which shares a box with each of the others
+const MIDDLE: usize = 2;
+
+/// Five linked grids, indexed like [`GRID_OFFSETS`]
+#[derive(Clone)]
+pub struct SamuraiBoard {
+    pub grids: [Board; 5],
+}
+
+impl std::fmt::Display for SamuraiBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut s = String::new();
+        for row in 0..SAMURAI_SIZE {
+            let mut line = String::new();
+            for col in 0..SAMURAI_SIZE {
+                match Self::cell_at(col, row) {
+                    Some((grid, idx)) => line.push_str(&format!("{} ", self.grids[grid].get(idx))),
+                    None => line.push_str("  "),
+                }
+            }
+            s.push_str(line.trim_end());
+            s.push('\n');
+        }
+
+        write!(f, "{s}")
+    }
+}
+
+impl Default for SamuraiBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SamuraiBoard {
+    pub fn new() -> Self {
+        Self {
+            grids: std::array::from_fn(|_| Board::new()),
+        }
+    }
+
+    /// Parses the standard layout: 21 lines of 21 characters, one per position
+    ///
+    /// Within the five grids, `1`-`9` is a clue and anything else is an empty cell.
+    /// Positions outside of every grid are ignored, as are missing characters at the end of a line
+    pub fn from_str_lenient(board_str: &str) -> Self {
+        let mut board = Self::new();
+        for (row, line) in board_str.lines().take(SAMURAI_SIZE).enumerate() {
+            for (col, c) in line.chars().take(SAMURAI_SIZE).enumerate() {
+                if let (Some((grid, idx)), Some(num)) = (Self::cell_at(col, row), c.to_digit(10))
+                    && num != 0
+                {
+                    board.play_cell(grid, idx, num as u8);
+                }
+            }
+        }
+        board
+    }
+
+    /// The grid and cell at a layout position, picking the middle grid for shared cells
+    fn cell_at(col: usize, row: usize) -> Option<(usize, BoardIdx)> {
+        [MIDDLE, 0, 1, 3, 4].into_iter().find_map(|grid| {
+            let (col_offset, row_offset) = GRID_OFFSETS[grid];
+            let col = col.checked_sub(col_offset).filter(|&col| col < 9)?;
+            let row = row.checked_sub(row_offset).filter(|&row| row < 9)?;
+            Some((grid, BoardIdx::new(col, row)))
+        })
+    }
+
+    /// The same cell seen from the other grid sharing it, if it's in one of the shared boxes
+    fn linked(grid: usize, idx: BoardIdx) -> Option<(usize, BoardIdx)> {
+        let (col_offset, row_offset) = GRID_OFFSETS[grid];
+        let (col, row) = (idx.col + col_offset, idx.row + row_offset);
+        [MIDDLE, 0, 1, 3, 4]
+            .into_iter()
+            .filter(|&other| other != grid && (other == MIDDLE || grid == MIDDLE))
+            .find_map(|other| {
+                let (col_offset, row_offset) = GRID_OFFSETS[other];
+                let col = col.checked_sub(col_offset).filter(|&col| col < 9)?;
+                let row = row.checked_sub(row_offset).filter(|&row| row < 9)?;
+                Some((other, BoardIdx::new(col, row)))
+            })
+    }
+
+    /// Plays `num` in a grid, and in the grid sharing that cell if there is one
+    pub fn play_cell(&mut self, grid: usize, idx: BoardIdx, num: u8) {
+        self.grids[grid].play_cell(idx, num);
+        if let Some((other, linked)) = Self::linked(grid, idx) {
+            self.grids[other].play_cell(linked, num);
+        }
+    }
+
+    /// Narrows each shared cell down to the candidates both of its grids still allow
+    fn sync_shared(&mut self) {
+        for grid in [0, 1, 3, 4] {
+            for (col, row) in itertools::iproduct!(0..9, 0..9) {
+                let idx = BoardIdx::new(col, row);
+                let Some((middle, linked)) = Self::linked(grid, idx) else {
+                    continue;
+                };
+                let both = Cell(
+                    self.grids[grid].get(idx).important_bits()
+                        & self.grids[middle].get(linked).important_bits(),
+                );
+                self.grids[grid].set_raw(idx, both);
+                self.grids[middle].set_raw(linked, both);
+            }
+        }
+    }
+
+    /// Solves all five grids together, with the same naked singles and guessing as [`crate::solve`]
+    ///
+    /// A Samurai can need a few hundred guesses deep, too deep for the stacks of rayon's worker threads,
+    /// so this searches on the calling thread
+    pub fn solve(mut self) -> Option<Self> {
+        let least_possibilities_cell = loop {
+            self.sync_shared();
+            if self.grids.iter().any(|grid| grid.verify().is_err()) {
+                return None;
+            }
+
+            let mut played_any = false;
+            let mut least_possibilities = usize::MAX;
+            let mut least_possibilities_cell = None;
+            for grid in 0..5 {
+                for (col, row) in itertools::iproduct!(0..9, 0..9) {
+                    let idx = BoardIdx::new(col, row);
+                    if self.grids[grid].played.contains(&idx) {
+                        continue;
+                    }
+
+                    let cell = self.grids[grid].get(idx);
+                    match cell.num_possibilities() {
+                        0 => return None,
+                        1 => {
+                            self.play_cell(grid, idx, cell.possibilities()[0]);
+                            played_any = true;
+                        }
+                        possibilities if possibilities < least_possibilities => {
+                            least_possibilities = possibilities;
+                            least_possibilities_cell = Some((grid, idx));
+                        }
+                        _ => (),
+                    }
+                }
+            }
+
+            // Playing can change the candidates of cells already looked at, so only trust a quiet pass
+            if !played_any {
+                break least_possibilities_cell;
+            }
+        };
+
+        let Some((grid, next)) = least_possibilities_cell else {
+            // This means all cells are played, thus it's solved
+            return Some(self);
+        };
+
+        let possibilities = self.grids[grid].get(next).possibilities();
+        possibilities.into_iter().find_map(|possibility| {
+            let mut new_board = self.clone();
+            new_board.play_cell(grid, next, possibility);
+            new_board.solve()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The cells of grid `grid` that are shared with another grid, which are all in one of its corner boxes
+    fn shared_cells(grid: usize) -> Vec<(BoardIdx, (usize, BoardIdx))> {
+        itertools::iproduct!(0..9, 0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+            .filter_map(|idx| Some((idx, SamuraiBoard::linked(grid, idx)?)))
+            .collect()
+    }
+
+    #[test]
+    fn each_outer_grid_shares_one_corner_box_with_the_middle() {
+        let corners = [(6, 6), (0, 6), (6, 0), (0, 0)];
+        for (grid, (col, row)) in [0, 1, 3, 4].into_iter().zip(corners) {
+            let shared = shared_cells(grid);
+            assert_eq!(shared.len(), 9);
+            for (idx, (other, linked)) in shared {
+                assert_eq!(other, MIDDLE);
+                assert!((col..col + 3).contains(&idx.col) && (row..row + 3).contains(&idx.row));
+                assert_eq!(SamuraiBoard::linked(MIDDLE, linked), Some((grid, idx)));
+            }
+        }
+        assert_eq!(shared_cells(MIDDLE).len(), 36);
+    }
+
+    #[test]
+    fn playing_a_shared_cell_plays_it_in_both_grids() {
+        let mut board = SamuraiBoard::new();
+        board.play_cell(0, BoardIdx::new(7, 8), 4);
+        assert_eq!(board.grids[MIDDLE].played_num(BoardIdx::new(1, 2)), Some(4));
+        board.play_cell(MIDDLE, BoardIdx::new(8, 8), 6);
+        assert_eq!(board.grids[4].played_num(BoardIdx::new(2, 2)), Some(6));
+    }
+
+    #[test]
+    fn the_example_solves_with_agreeing_corners() {
+        let board = SamuraiBoard::from_str_lenient(include_str!("../examples/samurai1.txt"));
+        let solution = board.clone().solve().unwrap();
+        for (grid, (puzzle, solved)) in board.grids.iter().zip(&solution.grids).enumerate() {
+            assert!(solved.is_solved());
+            for idx in puzzle.played.iter() {
+                assert_eq!(solved.played_num(*idx), puzzle.played_num(*idx));
+            }
+            for (idx, (other, linked)) in shared_cells(grid) {
+                assert_eq!(solved.get(idx), solution.grids[other].get(linked));
+            }
+        }
+    }
+}
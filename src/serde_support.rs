@@ -0,0 +1,154 @@
+//! The shapes types take when serialized with the `serde` feature, where they differ from the types themselves
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{BOARD_CELLS, Board, BoardIdx, Cell};
+
+/// A [`BoardIdx`] as written out, without the linear index, which is worked out again on the way back in
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Coords {
+    col: usize,
+    row: usize,
+}
+
+impl From<BoardIdx> for Coords {
+    fn from(idx: BoardIdx) -> Self {
+        Coords {
+            col: idx.col,
+            row: idx.row,
+        }
+    }
+}
+
+impl TryFrom<Coords> for BoardIdx {
+    type Error = String;
+
+    fn try_from(Coords { col, row }: Coords) -> Result<Self, Self::Error> {
+        match col < 9 && row < 9 {
+            true => Ok(BoardIdx::new(col, row)),
+            false => Err(format!("cell ({col}, {row}) is off the board")),
+        }
+    }
+}
+
+/// A [`Board`] as written out: every cell's candidates in row-major order, with bit `n` set when `n` is possible,
+/// and the cells that are played
+///
+/// That's the exact solver state, pencil marks included. Constraints, givens, and undo history are left out,
+/// like [`Board::to_state`] leaves them out
+#[derive(Serialize, Deserialize)]
+pub(crate) struct BoardState {
+    candidates: Vec<u16>,
+    played: Vec<BoardIdx>,
+}
+
+impl From<Board> for BoardState {
+    fn from(board: Board) -> Self {
+        let mut played: Vec<BoardIdx> = board.played.into_iter().collect();
+        played.sort_by_key(|idx| idx.idx);
+        BoardState {
+            candidates: board.cells.iter().map(Cell::important_bits).collect(),
+            played,
+        }
+    }
+}
+
+impl TryFrom<BoardState> for Board {
+    type Error = String;
+
+    /// Rejects anything but 81 masks using bits `1` through `9`, and a played cell with other than one candidate
+    fn try_from(BoardState { candidates, played }: BoardState) -> Result<Self, Self::Error> {
+        if candidates.len() != BOARD_CELLS {
+            return Err(format!(
+                "expected {BOARD_CELLS} cells but found {}",
+                candidates.len()
+            ));
+        }
+        let mut board = Board::new();
+        for (idx, &mask) in candidates.iter().enumerate() {
+            if mask & !Cell::IMPORTANT != 0 {
+                return Err(format!(
+                    "cell {idx} has candidate mask {mask:#x} outside 1-9"
+                ));
+            }
+            board.set_raw(BoardIdx::from_linear(idx), Cell(mask));
+        }
+        for idx in played {
+            if board.get(idx).num_possibilities() != 1 {
+                return Err(format!(
+                    "cell {idx} is played but doesn't have a single candidate"
+                ));
+            }
+            board.played.insert(idx);
+        }
+        Ok(board)
+    }
+}
+
+/// An `Option<Board>` as its [`Board::to_line`], for a solved board, which has no pencil marks worth keeping
+pub(crate) mod solution_line {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        board: &Option<Board>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        board.as_ref().map(Board::to_line).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Board>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|line| line.parse().map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_board_round_trips_with_its_pencil_marks() {
+        let mut board: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let (marked, num) = (0..BOARD_CELLS)
+            .map(BoardIdx::from_linear)
+            .find_map(|idx| match board.get(idx).possibilities()[..] {
+                [num, _, ..] => Some((idx, num)),
+                _ => None,
+            })
+            .unwrap();
+        board.cells[marked.idx].set_possible(num, false);
+        let json = serde_json::to_string(&board).unwrap();
+        let back: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, board);
+        assert_eq!(back.played, board.played);
+        assert!(!back.get(marked).is_possible(num));
+        assert_eq!(back.to_state(), board.to_state());
+    }
+
+    #[test]
+    fn a_played_cell_needs_a_single_candidate() {
+        let mut candidates = vec![Cell::IMPORTANT; BOARD_CELLS];
+        let played = r#"[{"col":1,"row":0}]"#;
+        let json =
+            |candidates: &[u16]| format!(r#"{{"candidates":{candidates:?},"played":{played}}}"#);
+        assert!(serde_json::from_str::<Board>(&json(&candidates)).is_err());
+
+        candidates[1] = 1 << 4;
+        let board: Board = serde_json::from_str(&json(&candidates)).unwrap();
+        assert_eq!(board.played_num(BoardIdx::new(1, 0)), Some(4));
+    }
+
+    #[test]
+    fn malformed_masks_are_rejected() {
+        let too_few = format!(r#"{{"candidates":{:?},"played":[]}}"#, [0u16; 80]);
+        assert!(serde_json::from_str::<Board>(&too_few).is_err());
+
+        let mut candidates = [Cell::IMPORTANT; BOARD_CELLS];
+        candidates[0] |= 1;
+        let stray_bit = format!(r#"{{"candidates":{candidates:?},"played":[]}}"#);
+        assert!(serde_json::from_str::<Board>(&stray_bit).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! Portable SIMD versions of the hottest candidate updates, for solving big datasets
+//!
+//! Needs a nightly compiler for `std::simd`. Without the `simd` feature the board falls back to
+//! updating its peers one cell at a time
+
+use std::simd::Simd;
+
+use crate::{BOARD_CELLS, BoardIdx, Cell};
+
+/// How many cells are updated at once
+const LANES: usize = 16;
+
+/// Cells that fit in whole chunks of [`LANES`]; the rest are updated one at a time
+const CHUNKED_CELLS: usize = BOARD_CELLS / LANES * LANES;
+
+/// For every cell, all ones in each of its 20 peers and zero everywhere else
+///
+/// Built once at compile time, so taking a number out of every peer is a handful of AND-NOTs
+static PEER_MASKS: [[u16; BOARD_CELLS]; BOARD_CELLS] = peer_masks();
+
+const fn peer_masks() -> [[u16; BOARD_CELLS]; BOARD_CELLS] {
+    let mut masks = [[0; BOARD_CELLS]; BOARD_CELLS];
+    let mut cell = 0;
+    while cell < BOARD_CELLS {
+        let mut peer = 0;
+        while peer < BOARD_CELLS {
+            let (col, row) = (cell % 9, cell / 9);
+            let (peer_col, peer_row) = (peer % 9, peer / 9);
+            let same_square = col / 3 == peer_col / 3 && row / 3 == peer_row / 3;
+            if peer != cell && (col == peer_col || row == peer_row || same_square) {
+                masks[cell][peer] = u16::MAX;
+            }
+            peer += 1;
+        }
+        cell += 1;
+    }
+    masks
+}
+
+/// Removes `num` from the candidates of every usual peer of `idx`, as [`Board::peers`] lists them
+pub(crate) fn eliminate_from_peers(cells: &mut [Cell; BOARD_CELLS], idx: BoardIdx, num: u8) {
+    let mask = &PEER_MASKS[idx.idx];
+    let bit = Simd::splat(1 << num);
+    for start in (0..CHUNKED_CELLS).step_by(LANES) {
+        let lanes = Simd::<u16, LANES>::from_array(std::array::from_fn(|i| cells[start + i].0));
+        let peers = Simd::from_slice(&mask[start..start + LANES]);
+        let updated = (lanes & !(peers & bit)).to_array();
+        for (cell, bits) in cells[start..start + LANES].iter_mut().zip(updated) {
+            cell.0 = bits;
+        }
+    }
+    for cell in CHUNKED_CELLS..BOARD_CELLS {
+        cells[cell].0 &= !(mask[cell] & (1 << num));
+    }
+}
+
+/// Whether `peer` is one of the usual peers of `idx`
+pub(crate) fn is_usual_peer(idx: BoardIdx, peer: BoardIdx) -> bool {
+    PEER_MASKS[idx.idx][peer.idx] != 0
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test;
+
+    use itertools::Itertools;
+    use test::Bencher;
+
+    use super::*;
+    use crate::{Board, rng::Rng};
+
+    /// What the board does without the `simd` feature: one peer at a time
+    fn eliminate_one_by_one(cells: &mut [Cell; BOARD_CELLS], idx: BoardIdx, num: u8) {
+        for peer in Board::new().peers(idx) {
+            cells[peer.idx].set_possible(num, false);
+        }
+    }
+
+    /// Boards part way through being filled in, so the peers have a mix of candidates
+    fn sample_cells() -> Vec<[Cell; BOARD_CELLS]> {
+        let mut rng = Rng::new(7);
+        (0..8)
+            .map(|fill| Board::quasi_random_fill(fill * 6, &mut rng).cells)
+            .collect()
+    }
+
+    #[test]
+    fn matches_the_scalar_elimination() {
+        for cells in sample_cells() {
+            for (idx, num) in (0..BOARD_CELLS).flat_map(|idx| (1..=9).map(move |num| (idx, num))) {
+                let idx = BoardIdx::from_linear(idx);
+                let (mut simd, mut scalar) = (cells, cells);
+                eliminate_from_peers(&mut simd, idx, num);
+                eliminate_one_by_one(&mut scalar, idx, num);
+                assert_eq!(simd, scalar, "eliminating {num} from the peers of {idx}");
+            }
+        }
+    }
+
+    #[test]
+    fn usual_peers_are_the_boards_peers() {
+        for idx in (0..BOARD_CELLS).map(BoardIdx::from_linear) {
+            let peers = Board::new().peers(idx);
+            for other in (0..BOARD_CELLS).map(BoardIdx::from_linear) {
+                assert_eq!(is_usual_peer(idx, other), peers.contains(&other));
+            }
+        }
+    }
+
+    /// The 1,000 generated puzzles in `examples/datasets/generated1000.txt`, with their clues propagated
+    fn dataset() -> Vec<Board> {
+        include_str!("../examples/datasets/generated1000.txt")
+            .lines()
+            .map(|line| line.parse().unwrap())
+            .collect()
+    }
+
+    #[bench]
+    fn bench_eliminate_simd(b: &mut Bencher) {
+        let cells = dataset().into_iter().map(|board| board.cells).collect_vec();
+        b.iter(|| {
+            for mut cells in cells.iter().copied() {
+                for idx in (0..BOARD_CELLS).map(BoardIdx::from_linear) {
+                    eliminate_from_peers(&mut cells, idx, (idx.idx % 9 + 1) as u8);
+                }
+                test::black_box(cells);
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_eliminate_scalar(b: &mut Bencher) {
+        let cells = dataset().into_iter().map(|board| board.cells).collect_vec();
+        let peers = (0..BOARD_CELLS)
+            .map(|idx| Board::new().peers(BoardIdx::from_linear(idx)))
+            .collect::<Vec<_>>();
+        b.iter(|| {
+            for mut cells in cells.iter().copied() {
+                for (idx, peers) in peers.iter().enumerate() {
+                    let num = (idx % 9 + 1) as u8;
+                    for peer in peers {
+                        cells[peer.idx].set_possible(num, false);
+                    }
+                }
+                test::black_box(cells);
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_solve_dataset(b: &mut Bencher) {
+        let puzzles = dataset();
+        b.iter(|| {
+            for puzzle in &puzzles {
+                test::black_box(crate::solve(puzzle));
+            }
+        });
+    }
+}
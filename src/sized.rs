@@ -0,0 +1,479 @@
+//! Sudoku in other sizes, like 4x4 and 16x16, where the boxes are `N` by `N` cells instead of 3 by 3
+//!
+//! [`Board`] stays the 9x9 board the rest of the crate is built around. [`SizedBoard`] is a plainer board for
+//! any box size, solved with naked and hidden singles and guessing, and [`AnyBoard`] picks between them by
+//! how many cells a puzzle has
+
+use std::str::FromStr;
+
+use crate::{
+    Board,
+    alphabet::Alphabet,
+    parse::{PASTED_BLANKS, ParseError},
+};
+
+/// A grid with `N` by `N` boxes, so `N * N` cells a side and numbers from `1` to `N * N`
+///
+/// `N` can be at most 5, since past 25 the numbers would run into `X` in [`Alphabet::for_side`], and `X` always
+/// marks a blank. Each cell's candidates are kept in a `u64` like [`crate::Cell`] keeps them in a `u16`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizedBoard<const N: usize> {
+    /// Bit `n` of each cell is set while `n` is possible, row-major
+    cells: Vec<u64>,
+    played: Vec<bool>,
+}
+
+/// Why a puzzle string couldn't be read as a [`SizedBoard`] or [`AnyBoard`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SizedParseError {
+    /// The puzzle didn't have one cell for every position of the grid
+    WrongCellCount { expected: usize, found: usize },
+    /// A symbol for a number bigger than the grid holds, like `G` in a 4x4
+    OutOfRange { found: char },
+    /// A clue contradicts an earlier clue in the same row, column, or box; `col` and `row` are 0-based
+    ConflictingClue { col: usize, row: usize, num: u8 },
+    /// What [`Board::from_str`](Board#impl-FromStr-for-Board) had against an 81-cell puzzle
+    Standard(ParseError),
+}
+
+impl std::fmt::Display for SizedParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SizedParseError::WrongCellCount { expected, found } => {
+                write!(f, "expected {expected} cells but found {found}")
+            }
+            SizedParseError::OutOfRange { found } => {
+                write!(f, "'{found}' is too big a number for the grid")
+            }
+            SizedParseError::ConflictingClue { col, row, num } => write!(
+                f,
+                "the {num} at R{}C{} conflicts with an earlier clue",
+                row + 1,
+                col + 1
+            ),
+            SizedParseError::Standard(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for SizedParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SizedParseError::Standard(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// What `c` means in a pasted grid written in `alphabet`: `Some` if it's a cell, holding its symbol unless it's a blank
+///
+/// Symbols of the alphabet come first, then blanks, which are the same as [`Board`]'s. Any other letter or digit is
+/// a cell too, for the parser to report as out of range. Anything else, like box borders, is skipped
+fn sized_cell(c: char, alphabet: &Alphabet) -> Option<Option<char>> {
+    match c {
+        _ if alphabet.value(c).is_some() => Some(Some(c)),
+        _ if PASTED_BLANKS.contains(&c) => Some(None),
+        _ if c.is_ascii_alphanumeric() => Some(Some(c)),
+        _ => None,
+    }
+}
+
+impl<const N: usize> std::fmt::Display for SizedBoard<N> {
+    /// Like [`Board`]: one row per line, `?` for a cell with several candidates left and `F` for one with none.
+    /// Numbers are written in [`Alphabet::for_side`]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render_in(&Self::alphabet()))
+    }
+}
+
+impl<const N: usize> Default for SizedBoard<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> SizedBoard<N> {
+    /// Cells along each side, which is also the biggest number
+    pub const SIDE: usize = N * N;
+    /// Cells in the whole grid
+    pub const CELLS: usize = Self::SIDE * Self::SIDE;
+
+    /// An empty grid, every number possible everywhere
+    pub fn new() -> Self {
+        const {
+            assert!(
+                N >= 1 && N <= 5,
+                "a SizedBoard needs a symbol for every number, so N can be at most 5"
+            )
+        };
+        let all = ((1u64 << Self::SIDE) - 1) << 1;
+        Self {
+            cells: vec![all; Self::CELLS],
+            played: vec![false; Self::CELLS],
+        }
+    }
+
+    /// The usual symbols for this size, from [`Alphabet::for_side`]
+    fn alphabet() -> Alphabet {
+        Alphabet::for_side(Self::SIDE).expect("every supported size has a symbol for each number")
+    }
+
+    /// Like `Display`, but with the numbers written in `alphabet`, which needs a symbol for every number
+    pub fn render_in(&self, alphabet: &Alphabet) -> String {
+        let mut s = String::new();
+        for row in 0..Self::SIDE {
+            for col in 0..Self::SIDE {
+                s.push(match self.candidates(col, row)[..] {
+                    [] => 'F',
+                    [num] => alphabet.symbol(num),
+                    _ => '?',
+                });
+                s.push(' ');
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Reads the cells like `str::parse` does, but with the numbers written in `alphabet`, like [`Alphabet::HEX`]
+    /// for a 16x16 grid
+    ///
+    /// A symbol of the alphabet is never a blank, so with [`Alphabet::HEX`] a `0` stands for 1
+    pub fn parse_in(s: &str, alphabet: &Alphabet) -> Result<Self, SizedParseError> {
+        let cells = s
+            .chars()
+            .filter_map(|c| sized_cell(c, alphabet))
+            .collect::<Vec<_>>();
+        if cells.len() != Self::CELLS {
+            return Err(SizedParseError::WrongCellCount {
+                expected: Self::CELLS,
+                found: cells.len(),
+            });
+        }
+        let mut board = Self::new();
+        for (idx, cell) in cells.into_iter().enumerate() {
+            let Some(c) = cell else {
+                continue;
+            };
+            let Some(num) = alphabet.value(c).filter(|&num| num as usize <= Self::SIDE) else {
+                return Err(SizedParseError::OutOfRange { found: c });
+            };
+            let (col, row) = (idx % Self::SIDE, idx / Self::SIDE);
+            if board.cells[idx] >> num & 1 == 0 {
+                return Err(SizedParseError::ConflictingClue { col, row, num });
+            }
+            board.play_cell(col, row, num);
+        }
+        Ok(board)
+    }
+
+    /// The number in a cell, if it's been played
+    pub fn get(&self, col: usize, row: usize) -> Option<u8> {
+        let idx = row * Self::SIDE + col;
+        self.played[idx].then(|| self.cells[idx].trailing_zeros() as u8)
+    }
+
+    /// The numbers still possible in a cell, in order
+    pub fn candidates(&self, col: usize, row: usize) -> Vec<u8> {
+        let bits = self.cells[row * Self::SIDE + col];
+        (1..=Self::SIDE as u8)
+            .filter(|num| bits >> num & 1 == 1)
+            .collect()
+    }
+
+    /// Whether every cell has been played
+    pub fn is_solved(&self) -> bool {
+        self.played.iter().all(|&played| played)
+    }
+
+    /// Plays `num` in a cell, removing it from the candidates of the cell's row, column, and box
+    pub fn play_cell(&mut self, col: usize, row: usize, num: u8) {
+        let idx = row * Self::SIDE + col;
+        self.cells[idx] = 1 << num;
+        self.played[idx] = true;
+        for peer in Self::peers(idx) {
+            self.cells[peer] &= !(1 << num);
+        }
+    }
+
+    /// The cells sharing a row, column, or box with `idx`, not counting `idx` itself
+    fn peers(idx: usize) -> impl Iterator<Item = usize> {
+        let (col, row) = (idx % Self::SIDE, idx / Self::SIDE);
+        let (box_col, box_row) = (col - col % N, row - row % N);
+        let row_cells = (0..Self::SIDE).map(move |col| row * Self::SIDE + col);
+        let col_cells = (0..Self::SIDE).map(move |row| row * Self::SIDE + col);
+        let box_cells =
+            (0..Self::SIDE).map(move |i| (box_row + i / N) * Self::SIDE + box_col + i % N);
+        row_cells
+            .chain(col_cells)
+            .chain(box_cells)
+            .filter(move |&peer| peer != idx)
+    }
+
+    /// Every row, then every column, then every box, each as its cells
+    fn units() -> Vec<Vec<usize>> {
+        let rows =
+            (0..Self::SIDE).map(|row| (0..Self::SIDE).map(|col| row * Self::SIDE + col).collect());
+        let cols =
+            (0..Self::SIDE).map(|col| (0..Self::SIDE).map(|row| row * Self::SIDE + col).collect());
+        let boxes = (0..Self::SIDE).map(|b| {
+            let (box_col, box_row) = (b % N * N, b / N * N);
+            (0..Self::SIDE)
+                .map(|i| (box_row + i / N) * Self::SIDE + box_col + i % N)
+                .collect()
+        });
+        rows.chain(cols).chain(boxes).collect()
+    }
+
+    /// A solution, or `None` if there's none
+    ///
+    /// Plays naked and hidden singles until there are none, then guesses on the cell with the fewest candidates,
+    /// on the calling thread
+    pub fn solve(&self) -> Option<Self> {
+        self.clone().solve_with(&Self::units())
+    }
+
+    fn solve_with(mut self, units: &[Vec<usize>]) -> Option<Self> {
+        loop {
+            let mut played_any = false;
+            for idx in 0..Self::CELLS {
+                let bits = self.cells[idx];
+                // A played cell emptied by a peer playing the same number shows up here too
+                if bits == 0 {
+                    return None;
+                }
+                if !self.played[idx] && bits.count_ones() == 1 {
+                    self.play_cell(
+                        idx % Self::SIDE,
+                        idx / Self::SIDE,
+                        bits.trailing_zeros() as u8,
+                    );
+                    played_any = true;
+                }
+            }
+            for unit in units {
+                for num in 1..=Self::SIDE as u8 {
+                    let mut places = unit.iter().filter(|&&idx| self.cells[idx] >> num & 1 == 1);
+                    match (places.next(), places.next()) {
+                        (None, _) => return None,
+                        (Some(&idx), None) if !self.played[idx] => {
+                            self.play_cell(idx % Self::SIDE, idx / Self::SIDE, num);
+                            played_any = true;
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            // Playing can change the candidates of cells already looked at, so only trust a quiet pass
+            if !played_any {
+                break;
+            }
+        }
+
+        let Some(next) = (0..Self::CELLS)
+            .filter(|&idx| !self.played[idx])
+            .min_by_key(|&idx| self.cells[idx].count_ones())
+        else {
+            // This means all cells are played, thus it's solved
+            return Some(self);
+        };
+        let (col, row) = (next % Self::SIDE, next / Self::SIDE);
+        self.candidates(col, row).into_iter().find_map(|num| {
+            let mut new_board = self.clone();
+            new_board.play_cell(col, row, num);
+            new_board.solve_with(units)
+        })
+    }
+}
+
+impl<const N: usize> FromStr for SizedBoard<N> {
+    type Err = SizedParseError;
+
+    /// Reads the cells in row-major order, ignoring how they're laid out in lines
+    ///
+    /// Numbers are `1`-`9`, then `A` for 10 and so on, ignoring case, and blanks are the same as [`Board`]'s.
+    /// Anything else, like box borders, is skipped
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_in(s, &Self::alphabet())
+    }
+}
+
+/// A puzzle of any of the common sizes, worked out from how many cells it has
+#[derive(Clone)]
+pub enum AnyBoard {
+    /// 16 cells
+    Four(SizedBoard<2>),
+    /// 81 cells, read and solved as a [`Board`]
+    Nine(Box<Board>),
+    /// 256 cells
+    Sixteen(SizedBoard<4>),
+    /// 625 cells
+    TwentyFive(SizedBoard<5>),
+}
+
+impl std::fmt::Display for AnyBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnyBoard::Four(board) => write!(f, "{board}"),
+            AnyBoard::Nine(board) => write!(f, "{board}"),
+            AnyBoard::Sixteen(board) => write!(f, "{board}"),
+            AnyBoard::TwentyFive(board) => write!(f, "{board}"),
+        }
+    }
+}
+
+impl AnyBoard {
+    /// A solution, or `None` if there's none
+    pub fn solve(&self) -> Option<Self> {
+        match self {
+            AnyBoard::Four(board) => board.solve().map(AnyBoard::Four),
+            AnyBoard::Nine(board) => {
+                crate::solve(board).map(|board| AnyBoard::Nine(Box::new(board)))
+            }
+            AnyBoard::Sixteen(board) => board.solve().map(AnyBoard::Sixteen),
+            AnyBoard::TwentyFive(board) => board.solve().map(AnyBoard::TwentyFive),
+        }
+    }
+}
+
+impl FromStr for AnyBoard {
+    type Err = SizedParseError;
+
+    /// Counts the cells like [`SizedBoard::from_str`] does, then reads the puzzle as a board of that size
+    ///
+    /// An 81-cell puzzle goes through [`Board::from_str`](Board#impl-FromStr-for-Board) instead, which is stricter
+    /// about layout
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let found = s
+            .chars()
+            .filter_map(|c| sized_cell(c, &Alphabet::DIGITS))
+            .count();
+        match found {
+            16 => s.parse().map(AnyBoard::Four),
+            81 => s
+                .parse()
+                .map(|board| AnyBoard::Nine(Box::new(board)))
+                .map_err(SizedParseError::Standard),
+            256 => s.parse().map(AnyBoard::Sixteen),
+            625 => s.parse().map(AnyBoard::TwentyFive),
+            // Whichever size it's closest to, as the likeliest one meant
+            _ => Err(SizedParseError::WrongCellCount {
+                expected: match found {
+                    0..=48 => 16,
+                    49..=168 => 81,
+                    169..=440 => 256,
+                    _ => 625,
+                },
+                found,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_grid_round_trips() {
+        let solution = SizedBoard::<4>::new().solve().unwrap();
+        let hex = solution.render_in(&Alphabet::HEX);
+        assert!(hex.starts_with("0 1 2 3 4 5 6 7 8 9 A B C D E F \n"));
+        assert_eq!(
+            SizedBoard::<4>::parse_in(&hex, &Alphabet::HEX).unwrap(),
+            solution
+        );
+
+        // Blank out the diagonal, which only its rows can fill back in, and it solves back to the same grid
+        let puzzle = hex
+            .split(' ')
+            .enumerate()
+            .map(|(idx, cell)| match idx % 17 {
+                0 => cell.replace(|c: char| c.is_ascii_alphanumeric(), "."),
+                _ => cell.to_owned(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let puzzle = SizedBoard::<4>::parse_in(&puzzle, &Alphabet::HEX).unwrap();
+        assert_ne!(puzzle, solution);
+        assert_eq!(puzzle.solve().unwrap().render_in(&Alphabet::HEX), hex);
+    }
+
+    #[test]
+    fn hex_symbols_past_the_grid_are_out_of_range() {
+        let mut cells = ".".repeat(16);
+        cells.replace_range(0..1, "5");
+        assert_eq!(
+            SizedBoard::<2>::parse_in(&cells, &Alphabet::HEX),
+            Err(SizedParseError::OutOfRange { found: '5' })
+        );
+    }
+
+    #[test]
+    fn four_by_four_uses_digits() {
+        let board: SizedBoard<2> = "1234 34.. .... ....".parse().unwrap();
+        assert_eq!(board.get(1, 1), Some(4));
+        assert_eq!(
+            board.solve().unwrap().to_string(),
+            "1 2 3 4 \n3 4 1 2 \n2 1 4 3 \n4 3 2 1 \n"
+        );
+    }
+
+    #[test]
+    fn the_biggest_size_reads_its_last_symbol() {
+        let mut cells = ".".repeat(SizedBoard::<5>::CELLS);
+        cells.replace_range(0..1, "P");
+        let board: SizedBoard<5> = cells.parse().unwrap();
+        assert_eq!(board.get(0, 0), Some(25));
+        assert_eq!(board.candidates(24, 0), (1..=24).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn x_is_a_blank_at_every_size() {
+        assert_eq!(
+            "x".repeat(SizedBoard::<5>::CELLS).parse::<SizedBoard<5>>(),
+            Ok(SizedBoard::new())
+        );
+        assert_eq!(
+            "X".repeat(SizedBoard::<4>::CELLS).parse::<SizedBoard<4>>(),
+            Ok(SizedBoard::new())
+        );
+    }
+
+    #[test]
+    fn any_board_goes_by_the_cell_count() {
+        let four: AnyBoard = "1234 34.. .... ....".parse().unwrap();
+        assert!(matches!(four, AnyBoard::Four(_)));
+        assert_eq!(
+            four.solve().unwrap().to_string(),
+            "1 2 3 4 \n3 4 1 2 \n2 1 4 3 \n4 3 2 1 \n"
+        );
+
+        let nine: AnyBoard = include_str!("../examples/easy1.txt").parse().unwrap();
+        let AnyBoard::Nine(board) = &nine else {
+            panic!("81 cells is a standard board");
+        };
+        assert_eq!(board.clue_count(), 38);
+
+        let sixteen: AnyBoard = ".".repeat(256).parse().unwrap();
+        assert!(matches!(sixteen, AnyBoard::Sixteen(_)));
+
+        let mut cells = ".".repeat(625);
+        cells.replace_range(0..1, "P");
+        let AnyBoard::TwentyFive(board) = cells.parse().unwrap() else {
+            panic!("625 cells is a 25x25 board");
+        };
+        assert_eq!(board.get(0, 0), Some(25));
+    }
+
+    #[test]
+    fn any_board_rejects_other_counts_with_the_nearest_size() {
+        for (found, expected) in [(15, 16), (100, 81), (300, 256), (500, 625), (700, 625)] {
+            assert!(matches!(
+                ".".repeat(found).parse::<AnyBoard>(),
+                Err(SizedParseError::WrongCellCount { expected: e, found: f })
+                    if (e, f) == (expected, found)
+            ));
+        }
+    }
+}
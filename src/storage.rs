@@ -0,0 +1,408 @@
+//! Storing boards, either as files or as compact bytes
+
+use std::{fs, io, path::Path};
+
+use itertools::Itertools;
+
+use crate::{
+    BOARD_CELLS, Board, BoardIdx, Cell, NEVER_CANCELLED, Solutions, Solver, parse::ParseError,
+    render::RenderOptions, solve,
+};
+
+/// Bytes in [`Board::to_bytes`] output: two cells per byte, with the last byte's high half unused
+pub const PACKED_LEN: usize = BOARD_CELLS.div_ceil(2);
+
+/// Why [`Board::load`] couldn't produce a board
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// The file was read but doesn't hold a valid board
+    Parse(ParseError),
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "couldn't read the board: {err}"),
+            LoadError::Parse(err) => write!(f, "couldn't parse the board: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Io(err) => Some(err),
+            LoadError::Parse(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> Self {
+        LoadError::Io(err)
+    }
+}
+
+impl From<ParseError> for LoadError {
+    fn from(err: ParseError) -> Self {
+        LoadError::Parse(err)
+    }
+}
+
+impl Board {
+    /// Reads a board saved by [`Board::save`], or anything else [`Board::from_str`](Board#impl-FromStr-for-Board)
+    /// accepts, like a grid pasted with `.` or `0` for blanks
+    pub fn load(path: impl AsRef<Path>) -> Result<Board, LoadError> {
+        let board_str = fs::read_to_string(path)?;
+        Ok(board_str.parse()?)
+    }
+
+    /// Writes the board as nine rows of cells, with `x` for every cell not yet down to one number
+    ///
+    /// Only the numbers survive a round trip through [`Board::load`], not eliminated candidates
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let options = RenderOptions {
+            empty: 'x',
+            ..RenderOptions::default()
+        };
+        fs::write(path, self.render(&options))
+    }
+
+    /// The played numbers packed four bits per cell, row-major, with `0` for a cell that isn't played
+    ///
+    /// Cell `i` goes in the low half of byte `i / 2` when `i` is even and the high half when it's odd.
+    /// Only played numbers are kept; remaining candidates are recomputed from them by [`Board::from_bytes`].
+    /// A played cell that a clash has left with no candidates has no number to keep, so it's written as `0` too
+    pub fn to_bytes(&self) -> [u8; PACKED_LEN] {
+        let mut bytes = [0; PACKED_LEN];
+        for idx in 0..BOARD_CELLS {
+            if let Some(num) = self.played_num(BoardIdx::from_linear(idx)) {
+                bytes[idx / 2] |= num << (idx % 2 * 4);
+            }
+        }
+        bytes
+    }
+
+    /// Unpacks [`Board::to_bytes`] output, checking it like [`Board::from_values`] does
+    ///
+    /// Error positions count cells, not bytes
+    pub fn from_bytes(bytes: &[u8; PACKED_LEN]) -> Result<Board, ParseError> {
+        let values: Vec<u8> = (0..BOARD_CELLS)
+            .map(|idx| bytes[idx / 2] >> (idx % 2 * 4) & 0xf)
+            .collect();
+        Board::from_values(&values)
+    }
+}
+
+/// Why [`Board::from_state`] or [`Solver::resume`] couldn't read a saved state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateError {
+    /// A cell that's neither candidate digits, `-` for none left, nor one digit and a `*` for a played cell
+    InvalidCell { line: usize, found: String },
+    /// The grid didn't hold exactly 81 cells
+    WrongCellCount { found: usize },
+    /// A line after the grid that isn't a branch like `branch R1C2=3 R4C5=6`
+    InvalidBranch { line: usize },
+}
+
+impl std::fmt::Display for StateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateError::InvalidCell { line, found } => {
+                write!(f, "invalid cell '{found}' on line {line}")
+            }
+            StateError::WrongCellCount { found } => {
+                write!(f, "expected {BOARD_CELLS} cells but found {found}")
+            }
+            StateError::InvalidBranch { line } => write!(f, "invalid branch on line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+/// Lines in the grid part of [`Board::to_state`] output
+const STATE_GRID_LINES: usize = 9;
+
+impl Board {
+    /// Every cell's candidates, with played cells marked, so a solve can stop and pick up later where it left off
+    ///
+    /// Nine lines of nine cells separated by spaces. A cell is its candidate digits in order, `-` if it has none,
+    /// or its number and a `*` once it's played, which is `-*` if it's been emptied since. Constraints aren't
+    /// included
+    pub fn to_state(&self) -> String {
+        let mut s = String::new();
+        for row in 0..9 {
+            let mut cells = (0..9).map(|col| {
+                let idx = BoardIdx::new(col, row);
+                let possibilities = self.get(idx).possibilities();
+                match (self.played.contains(&idx), &possibilities[..]) {
+                    (true, [num]) => format!("{num}*"),
+                    (true, []) => "-*".to_string(),
+                    (_, []) => "-".to_string(),
+                    _ => possibilities.iter().join(""),
+                }
+            });
+            s.push_str(&cells.join(" "));
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Reads [`Board::to_state`] output back, with exactly the same candidates and played cells
+    pub fn from_state(state: &str) -> Result<Board, StateError> {
+        let lines = state.lines().take(STATE_GRID_LINES).enumerate();
+        let cells: Vec<_> = lines
+            .flat_map(|(line, cells)| cells.split_whitespace().map(move |cell| (line + 1, cell)))
+            .collect();
+        if cells.len() != BOARD_CELLS {
+            return Err(StateError::WrongCellCount { found: cells.len() });
+        }
+
+        let mut board = Board::new();
+        for (idx, (line, cell)) in cells.into_iter().enumerate() {
+            let idx = BoardIdx::from_linear(idx);
+            let invalid = || StateError::InvalidCell {
+                line,
+                found: cell.to_string(),
+            };
+            let (digits, played) = match cell.strip_suffix('*') {
+                Some("-") => ("", true),
+                Some(num) if num.len() == 1 => (num, true),
+                Some(_) => return Err(invalid()),
+                None if cell == "-" => ("", false),
+                None => (cell, false),
+            };
+            let mut candidates = Cell::none_possible();
+            for c in digits.chars() {
+                let num = c
+                    .to_digit(10)
+                    .filter(|num| (1..=9).contains(num))
+                    .ok_or_else(invalid)?;
+                candidates.set_possible(num as u8, true);
+            }
+            board.set_raw(idx, candidates);
+            if played {
+                board.played.insert(idx);
+            }
+        }
+        Ok(board)
+    }
+}
+
+impl Solutions<'_> {
+    /// Where the enumeration is up to, so [`Solver::resume`] can carry on with the solutions not yet returned
+    ///
+    /// The base board as [`Board::to_state`] writes it, then one `branch` line per branch still to explore,
+    /// listing the moves it plays on top of the base, with the next branch to explore last
+    pub fn checkpoint(&self) -> String {
+        let mut s = self.base.to_state();
+        for branch in &self.pending {
+            s.push_str("branch");
+            for (idx, num) in branch {
+                s.push_str(&format!(" {idx}={num}"));
+            }
+            s.push('\n');
+        }
+        s
+    }
+}
+
+impl Solver {
+    /// Picks up an enumeration from a [`Solutions::checkpoint`], yielding the solutions it hadn't gotten to yet
+    pub fn resume(&self, checkpoint: &str) -> Result<Solutions<'_>, StateError> {
+        let base = Board::from_state(checkpoint)?;
+        let pending = checkpoint
+            .lines()
+            .enumerate()
+            .skip(STATE_GRID_LINES)
+            .map(|(line, branch)| {
+                let moves = branch.strip_prefix("branch").map(|moves| {
+                    moves
+                        .split_whitespace()
+                        .map(parse_move)
+                        .collect::<Option<Vec<_>>>()
+                });
+                moves
+                    .flatten()
+                    .ok_or(StateError::InvalidBranch { line: line + 1 })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Solutions {
+            solver: self,
+            base,
+            pending,
+            cancel: &NEVER_CANCELLED,
+        })
+    }
+}
+
+/// Reads a move like `R1C2=3`, as [`BoardIdx`] displays its cell
+fn parse_move(text: &str) -> Option<(BoardIdx, u8)> {
+    let (cell, num) = text.split_once('=')?;
+    let (row, col) = cell.strip_prefix('R')?.split_once('C')?;
+    let (row, col, num) = (
+        row.parse::<usize>().ok()?,
+        col.parse::<usize>().ok()?,
+        num.parse().ok()?,
+    );
+    let in_range = (1..=9).contains(&row) && (1..=9).contains(&col) && (1..=9).contains(&num);
+    in_range.then(|| (BoardIdx::new(col - 1, row - 1), num))
+}
+
+/// Solves the board saved in `state` by [`Board::to_state`], as if the solve had never stopped
+pub fn solve_resume(state: &str) -> Result<Option<Board>, StateError> {
+    Ok(solve(&Board::from_state(state)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn easy() -> Board {
+        include_str!("../examples/easy1.txt").parse().unwrap()
+    }
+
+    /// A path under the system's temp dir, unique to `name` and this process
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("crossword_solver-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = temp_path("round_trip.txt");
+        let board = easy();
+        board.save(&path).unwrap();
+        let loaded = Board::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        // Every cell that was down to one number comes back as a clue
+        assert_eq!(loaded.to_sudoku_line(), board.to_line().replace('?', "."));
+    }
+
+    #[test]
+    fn load_takes_pasted_blanks() {
+        let path = temp_path("pasted.txt");
+        fs::write(&path, easy().to_sudoku_line()).unwrap();
+        let loaded = Board::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.to_sudoku_line(), easy().to_sudoku_line());
+    }
+
+    #[test]
+    fn load_reports_a_missing_file() {
+        assert!(matches!(
+            Board::load(temp_path("missing.txt")),
+            Err(LoadError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let puzzle = easy();
+        let unpacked = Board::from_bytes(&puzzle.to_bytes()).unwrap();
+        assert_eq!(unpacked.to_sudoku_line(), puzzle.to_sudoku_line());
+        assert_eq!(unpacked.to_line(), puzzle.to_line());
+
+        let solution = solve(&puzzle).unwrap();
+        let unpacked = Board::from_bytes(&solution.to_bytes()).unwrap();
+        assert_eq!(unpacked.to_line(), solution.to_line());
+    }
+
+    #[test]
+    fn bytes_put_cells_in_nibbles() {
+        let mut board = Board::new();
+        board.play_cell(BoardIdx::from_linear(0), 3);
+        board.play_cell(BoardIdx::from_linear(1), 7);
+        board.play_cell(BoardIdx::from_linear(80), 9);
+        let bytes = board.to_bytes();
+        assert_eq!(bytes[0], 0x73);
+        assert_eq!(bytes[PACKED_LEN - 1], 0x09);
+        assert!(bytes[1..PACKED_LEN - 1].iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn bytes_leave_out_an_emptied_cell() {
+        let mut board = Board::new();
+        let idx = BoardIdx::from_linear(0);
+        board.play_cell(idx, 3);
+        board.set_raw(idx, Cell::none_possible());
+        assert_eq!(board.to_bytes(), [0; PACKED_LEN]);
+    }
+
+    #[test]
+    fn state_round_trip() {
+        let mut board = easy();
+        board.set_raw(BoardIdx::from_linear(80), Cell::none_possible());
+        let emptied = BoardIdx::new(2, 0);
+        assert!(board.played.contains(&emptied));
+        board.set_raw(emptied, Cell::none_possible());
+        assert_eq!(board.to_state().split_whitespace().nth(2), Some("-*"));
+        let restored = Board::from_state(&board.to_state()).unwrap();
+        assert_eq!(restored.to_candidate_grid(), board.to_candidate_grid());
+        assert_eq!(restored.played, board.played);
+    }
+
+    #[test]
+    fn resume_matches_a_fresh_solve() {
+        let puzzle: Board = include_str!("../examples/extreme1.txt").parse().unwrap();
+        let solution = solve(&puzzle).unwrap();
+        // Part way through, with a few more cells filled in than the puzzle gives
+        let mut partway = puzzle.clone();
+        let unplayed = (0..BOARD_CELLS)
+            .map(BoardIdx::from_linear)
+            .filter(|&idx| !puzzle.played.contains(&idx));
+        for idx in unplayed.take(5) {
+            partway.play_cell(idx, solution.get(idx).possibilities()[0]);
+        }
+        let resumed = solve_resume(&partway.to_state()).unwrap().unwrap();
+        assert_eq!(resumed.to_line(), solution.to_line());
+    }
+
+    #[test]
+    fn resumed_enumeration_matches_an_uninterrupted_one() {
+        let solver = Solver::new();
+        let uninterrupted: Vec<_> = solver.solutions(Board::new()).take(5).collect();
+
+        let mut solutions = solver.solutions(Board::new());
+        let first: Vec<_> = solutions.by_ref().take(2).collect();
+        let rest: Vec<_> = solver
+            .resume(&solutions.checkpoint())
+            .unwrap()
+            .take(3)
+            .collect();
+
+        let lines = |boards: &[Board]| boards.iter().map(Board::to_line).collect::<Vec<_>>();
+        assert_eq!(lines(&[first, rest].concat()), lines(&uninterrupted));
+    }
+
+    #[test]
+    fn state_errors() {
+        let state = Board::new().to_state();
+        assert_eq!(
+            Board::from_state(&state.replacen("123456789", "1234x", 1)),
+            Err(StateError::InvalidCell {
+                line: 1,
+                found: "1234x".to_string()
+            })
+        );
+        assert_eq!(
+            Board::from_state(&state.replacen("123456789", "12*", 1)),
+            Err(StateError::InvalidCell {
+                line: 1,
+                found: "12*".to_string()
+            })
+        );
+        assert_eq!(
+            Board::from_state(&state.replacen("123456789 ", "", 1)),
+            Err(StateError::WrongCellCount { found: 80 })
+        );
+        assert!(matches!(
+            Solver::new().resume(&format!("{state}branch R0C1=2\n")),
+            Err(StateError::InvalidBranch { line: 10 })
+        ));
+    }
+}
@@ -0,0 +1,1872 @@
+//! Logical techniques that eliminate candidates without guessing
+//!
+//! Each technique takes the board and returns whether it changed anything
+
+use std::collections::HashMap;
+
+use itertools::{Itertools, iproduct};
+
+use crate::{Board, BoardIdx, Cause, Elimination, SolveStats, Solver, SquareIdx, TooDeep, Unit};
+
+/// How many rounds of singles [`Technique::Nishio`] plays after each trial placement
+pub const NISHIO_DEPTH: usize = 4;
+
+/// One of the techniques in this module, for picking which to run at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Technique {
+    NakedPair,
+    NakedQuad,
+    HiddenQuad,
+    Skyscraper,
+    TwoStringKite,
+    SimpleColoring,
+    UniqueRectangle,
+    BugPlusOne,
+    Nishio,
+}
+
+impl Technique {
+    /// Every technique, simplest first
+    pub const ALL: [Technique; 9] = [
+        Technique::NakedPair,
+        Technique::NakedQuad,
+        Technique::HiddenQuad,
+        Technique::Skyscraper,
+        Technique::TwoStringKite,
+        Technique::SimpleColoring,
+        Technique::UniqueRectangle,
+        Technique::BugPlusOne,
+        Technique::Nishio,
+    ];
+
+    /// The function implementing this technique
+    pub fn function(self) -> fn(&mut Board) -> bool {
+        match self {
+            Technique::NakedPair => naked_pair,
+            Technique::NakedQuad => naked_quad,
+            Technique::HiddenQuad => hidden_quad,
+            Technique::Skyscraper => skyscraper,
+            Technique::TwoStringKite => two_string_kite,
+            Technique::SimpleColoring => simple_coloring,
+            Technique::UniqueRectangle => unique_rectangle,
+            Technique::BugPlusOne => bug_plus_one,
+            Technique::Nishio => |board| nishio(board, NISHIO_DEPTH),
+        }
+    }
+
+    /// What the technique is called, as it reads in a sentence
+    pub fn name(self) -> &'static str {
+        match self {
+            Technique::NakedPair => "a naked pair",
+            Technique::NakedQuad => "a naked quad",
+            Technique::HiddenQuad => "a hidden quad",
+            Technique::Skyscraper => "a skyscraper",
+            Technique::TwoStringKite => "a two-string kite",
+            Technique::SimpleColoring => "simple coloring",
+            Technique::UniqueRectangle => "a unique rectangle",
+            Technique::BugPlusOne => "BUG+1",
+            Technique::Nishio => "Nishio",
+        }
+    }
+}
+
+/// The least advanced reasoning that can fill a cell, from easiest to hardest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Difficulty {
+    /// The cell has a single candidate left
+    NakedSingle,
+    /// Some unit has nowhere else for one of the cell's candidates
+    HiddenSingle,
+    /// A single appears once this technique, and every simpler one, has done all it can
+    Technique(Technique),
+    /// None of the techniques get it down to a single, so it takes a guess
+    Guess,
+}
+
+/// A newspaper-style label for a whole puzzle, from [`Board::rate_difficulty`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rating {
+    /// Singles all the way through
+    Easy,
+    /// Singles and naked pairs
+    Medium,
+    /// Some technique past naked pairs, but no guessing
+    Hard,
+    /// Logic gets stuck somewhere, so it takes a guess
+    Expert,
+}
+
+impl From<Difficulty> for Rating {
+    fn from(difficulty: Difficulty) -> Self {
+        match difficulty {
+            Difficulty::NakedSingle | Difficulty::HiddenSingle => Rating::Easy,
+            Difficulty::Technique(Technique::NakedPair) => Rating::Medium,
+            Difficulty::Technique(_) => Rating::Hard,
+            Difficulty::Guess => Rating::Expert,
+        }
+    }
+}
+
+impl std::fmt::Display for Rating {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Rating::Easy => "Easy",
+            Rating::Medium => "Medium",
+            Rating::Hard => "Hard",
+            Rating::Expert => "Expert",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// Points charged for a hint, by what it takes to find, for games that score players on how much help they took
+///
+/// The defaults grow with [`Difficulty`]; a game can tune them by changing any field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HintCosts {
+    pub naked_single: u32,
+    pub hidden_single: u32,
+    /// Indexed in [`Technique::ALL`] order
+    pub techniques: [u32; Technique::ALL.len()],
+    pub guess: u32,
+}
+
+impl Default for HintCosts {
+    fn default() -> Self {
+        Self {
+            naked_single: 1,
+            hidden_single: 2,
+            techniques: [4, 5, 6, 7, 7, 8, 8, 8, 12],
+            guess: 20,
+        }
+    }
+}
+
+impl HintCosts {
+    /// What a hint needing `difficulty` costs
+    pub fn cost(&self, difficulty: Difficulty) -> u32 {
+        match difficulty {
+            Difficulty::NakedSingle => self.naked_single,
+            Difficulty::HiddenSingle => self.hidden_single,
+            Difficulty::Technique(technique) => {
+                let position = Technique::ALL
+                    .iter()
+                    .position(|&other| other == technique)
+                    .expect("every technique is in `ALL`");
+                self.techniques[position]
+            }
+            Difficulty::Guess => self.guess,
+        }
+    }
+}
+
+impl Board {
+    /// Runs `technique` once, returning whether it changed anything
+    pub fn apply_technique(&mut self, technique: Technique) -> bool {
+        technique.function()(self)
+    }
+
+    /// Fills in every cell that's down to one candidate right now, as one batch, returning how many were filled
+    ///
+    /// Cells that only become singles because of this batch are left for the next call. Two singles that rule each
+    /// other out can't both go in, so only the first in row-major order is filled and the other is left empty
+    pub fn fill_naked_singles_once(&mut self) -> usize {
+        let singles = iproduct!(0..9, 0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+            .filter(|idx| !self.played.contains(idx))
+            .filter_map(|idx| match self.get(idx).possibilities()[..] {
+                [num] => Some((idx, num)),
+                _ => None,
+            })
+            .collect_vec();
+        let mut filled = 0;
+        for (idx, num) in singles {
+            if self.get(idx).is_possible(num) {
+                self.play_cell(idx, num);
+                filled += 1;
+            }
+        }
+        filled
+    }
+
+    /// How many unplayed cells could be filled right now by a naked or hidden single, without filling any
+    ///
+    /// A quick read on difficulty before grading: easy puzzles tend to open with plenty of singles, hard ones with few.
+    /// A cell that's both kinds of single counts once
+    pub fn available_singles(&self) -> usize {
+        let units = self.units();
+        iproduct!(0..9, 0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+            .filter(|idx| !self.played.contains(idx))
+            .filter(|&idx| single_at(self, &units, idx).is_some())
+            .count()
+    }
+
+    /// How far logic alone gets: this board with singles played and every technique run until nothing changes
+    ///
+    /// A puzzle that doesn't need guessing comes back solved. Uniqueness techniques are included, so on a puzzle
+    /// with several solutions the result may have ruled some of them out
+    pub fn logical_closure(&self) -> Board {
+        let mut board = self.clone();
+        let units = board.units();
+        loop {
+            if board.fill_naked_singles_once() > 0 {
+                continue;
+            }
+            let hidden = iproduct!(0..9, 0..9)
+                .map(|(row, col)| BoardIdx::new(col, row))
+                .filter(|idx| !board.played.contains(idx))
+                .find_map(|idx| single_at(&board, &units, idx).map(|(num, _)| (idx, num)));
+            if let Some((idx, num)) = hidden {
+                board.play_cell(idx, num);
+                continue;
+            }
+            if !Technique::ALL
+                .into_iter()
+                .any(|technique| board.apply_technique(technique))
+            {
+                return board;
+            }
+        }
+    }
+
+    /// Solves with logic as far as it goes, guessing only where it gets stuck, and gives up with [`TooDeep`] rather
+    /// than go more than `max_guesses` guesses deep
+    ///
+    /// The logic is [`Board::logical_closure`], run again after every guess, so a budget of 0 asks whether the puzzle
+    /// needs no guessing at all, 1 whether a single bifurcation does it, and `usize::MAX` never gives up. Guesses are
+    /// counted along one line of trial, so trying each candidate of a cell in turn is still one guess deep.
+    /// `Ok(None)` means there's no solution, taking for granted, like the uniqueness techniques do, that there's at most one
+    pub fn solve_within_guesses(&self, max_guesses: usize) -> Result<Option<Board>, TooDeep> {
+        let board = self.logical_closure();
+        if board
+            .verify()
+            .and_then(|()| board.verify_constraints())
+            .is_err()
+        {
+            return Ok(None);
+        }
+        let Some(next) = iproduct!(0..9, 0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+            .filter(|idx| !board.played.contains(idx))
+            .min_by_key(|&idx| board.get(idx).num_possibilities())
+        else {
+            return Ok(Some(board));
+        };
+        if max_guesses == 0 {
+            return Err(TooDeep);
+        }
+
+        let mut too_deep = false;
+        for num in board.get(next).possibilities() {
+            let mut guess = board.clone();
+            guess.play_cell(next, num);
+            match guess.solve_within_guesses(max_guesses - 1) {
+                Ok(Some(solution)) => return Ok(Some(solution)),
+                Ok(None) => {}
+                Err(TooDeep) => too_deep = true,
+            }
+        }
+        match too_deep {
+            true => Err(TooDeep),
+            false => Ok(None),
+        }
+    }
+
+    /// The unplayed cell that needs the most advanced reasoning to fill, with what it needs, or `None` if every cell is played
+    ///
+    /// Each technique is run until it stops making progress on top of all the simpler ones.
+    /// Ties go to the first cell in row-major order
+    pub fn hardest_cell(&self) -> Option<(BoardIdx, Difficulty)> {
+        let mut stages = Vec::new();
+        let mut board = self.clone();
+        for technique in Technique::ALL {
+            while board.apply_technique(technique) {}
+            stages.push((technique, board.clone()));
+        }
+
+        let units = self.units();
+        let difficulty = |idx: BoardIdx| {
+            if self.get(idx).num_possibilities() == 1 {
+                return Difficulty::NakedSingle;
+            }
+            if single_at(self, &units, idx).is_some() {
+                return Difficulty::HiddenSingle;
+            }
+            stages
+                .iter()
+                .find(|(_, board)| single_at(board, &units, idx).is_some())
+                .map_or(Difficulty::Guess, |&(technique, _)| {
+                    Difficulty::Technique(technique)
+                })
+        };
+
+        iproduct!(0..9, 0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+            .filter(|idx| !self.played.contains(idx))
+            .map(|idx| (idx, difficulty(idx)))
+            .min_by_key(|&(_, difficulty)| std::cmp::Reverse(difficulty))
+    }
+}
+
+/// Everything about solving a puzzle that a frontend shows, from [`Board::solve_report`]
+///
+/// With the `serde` feature it serializes, with the solution written as its [`Board::to_line`]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolveReport {
+    /// `None` if there's no solution
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::solution_line"))]
+    pub solution: Option<Board>,
+    /// [`Board::solution_path`], empty if there's no solution. On a puzzle with several, it may end in a different one
+    pub steps: Vec<(BoardIdx, u8, Difficulty)>,
+    /// How the search went, from [`Solver::solve_with_stats`]
+    pub stats: SolveStats,
+    /// The hardest of the steps, or `None` if there are none
+    pub difficulty: Option<Difficulty>,
+    /// Whether [`SolveReport::solution`] is the only one
+    pub unique: bool,
+}
+
+/// One entry in [`Board::solve_and_explain`]'s walk through a solve
+enum Step {
+    /// `num` goes in `idx`; `unit` is where it was a hidden single
+    Place {
+        idx: BoardIdx,
+        num: u8,
+        difficulty: Difficulty,
+        unit: Option<Unit>,
+    },
+    /// `technique` was run with nothing else left, and removed these candidates
+    Apply {
+        technique: Technique,
+        eliminations: Vec<Elimination>,
+    },
+}
+
+impl Board {
+    /// Every placement needed to solve the board, in order, each with what it took to find
+    ///
+    /// Singles are played as soon as there are any. When there aren't, the simplest technique that makes progress
+    /// is run, and the next placement is credited to the hardest technique used since the one before it.
+    /// When nothing helps, the most constrained cell is guessed, always correctly: the solution is worked out first.
+    /// Returns `None` if there's no solution
+    pub fn solution_path(&self) -> Option<Vec<(BoardIdx, u8, Difficulty)>> {
+        let steps = self.logical_steps()?;
+        Some(
+            steps
+                .into_iter()
+                .filter_map(|step| match step {
+                    Step::Place {
+                        idx,
+                        num,
+                        difficulty,
+                        ..
+                    } => Some((idx, num, difficulty)),
+                    Step::Apply { .. } => None,
+                })
+                .collect(),
+        )
+    }
+
+    /// How hard the puzzle is overall, going by the hardest step of [`Board::solution_path`]
+    ///
+    /// Costs as much as a full [`Board::solution_path`]. A finished board is [`Rating::Easy`], and one with no
+    /// solution gets `None`
+    pub fn rate_difficulty(&self) -> Option<Rating> {
+        let hardest = self
+            .solution_path()?
+            .into_iter()
+            .map(|(_, _, difficulty)| difficulty)
+            .max();
+        Some(hardest.map_or(Rating::Easy, Rating::from))
+    }
+
+    /// What the next placement of [`Board::solution_path`] costs with the default [`HintCosts`]
+    ///
+    /// Returns `None` if the board is finished or has no solution
+    pub fn hint_cost(&self) -> Option<u32> {
+        self.hint_cost_with(&HintCosts::default())
+    }
+
+    /// Like [`Board::hint_cost`], but with a game's own `costs`
+    pub fn hint_cost_with(&self, costs: &HintCosts) -> Option<u32> {
+        let &(_, _, difficulty) = self.solution_path()?.first()?;
+        Some(costs.cost(difficulty))
+    }
+
+    /// The solution, the steps to it, search stats, difficulty, and uniqueness all in one go, for a frontend to
+    /// render from
+    ///
+    /// Costs a solve, a grade, and a uniqueness check, each done separately
+    pub fn solve_report(&self) -> SolveReport {
+        let (solution, stats) = Solver::new().solve_with_stats(self.clone());
+        let steps = self.solution_path().unwrap_or_default();
+        SolveReport {
+            difficulty: steps.iter().map(|&(_, _, difficulty)| difficulty).max(),
+            unique: solution.is_some() && self.has_unique_solution(),
+            solution,
+            steps,
+            stats,
+        }
+    }
+
+    /// [`Board::solution_path`] written out as numbered steps, one per line, for someone learning to solve by hand
+    ///
+    /// Each technique gets its own line listing what it removed, ahead of the placements it led to.
+    /// Returns `None` if there's no solution
+    pub fn solve_and_explain(&self) -> Option<String> {
+        let lines = self.logical_steps()?.into_iter().map(|step| match step {
+            Step::Place {
+                idx,
+                num,
+                difficulty,
+                unit,
+            } => {
+                let reason = match (difficulty, unit) {
+                    (Difficulty::Guess, _) => {
+                        return format!("Guess {num} at {idx} (no logical move available).");
+                    }
+                    (_, Some(unit)) => format!("hidden single in {unit}"),
+                    (_, None) => "naked single".to_string(),
+                };
+                format!("Place {num} at {idx} ({reason}).")
+            }
+            Step::Apply {
+                technique,
+                eliminations,
+            } => {
+                let removed = eliminations
+                    .iter()
+                    .map(|elimination| format!("{} from {}", elimination.num, elimination.cell))
+                    .join(", ");
+                format!("Use {}, removing {removed}.", technique.name())
+            }
+        });
+        Some(
+            lines
+                .enumerate()
+                .map(|(i, line)| format!("{}. {line}\n", i + 1))
+                .collect(),
+        )
+    }
+
+    /// The steps behind [`Board::solution_path`], including the techniques run between placements
+    fn logical_steps(&self) -> Option<Vec<Step>> {
+        let solution = Solver::new().solutions_up_to(self.clone(), 1).pop()?;
+        let answer = |idx: BoardIdx| solution.get(idx).possibilities()[0];
+        let units = self.units();
+        let mut board = self.clone();
+        board.provenance = None;
+        let mut steps = Vec::new();
+        let mut used: Option<Technique> = None;
+
+        loop {
+            let unplayed = iproduct!(0..9, 0..9)
+                .map(|(row, col)| BoardIdx::new(col, row))
+                .filter(|idx| !board.played.contains(idx))
+                .collect_vec();
+            if unplayed.is_empty() {
+                return Some(steps);
+            }
+
+            let naked = unplayed
+                .iter()
+                .find_map(|&idx| match board.get(idx).possibilities()[..] {
+                    [num] => Some((idx, num, None)),
+                    _ => None,
+                });
+            let single = naked.or_else(|| {
+                unplayed.iter().find_map(|&idx| {
+                    single_at(&board, &units, idx).map(|(num, unit)| (idx, num, unit))
+                })
+            });
+            if let Some((idx, num, unit)) = single {
+                let difficulty = match unit {
+                    Some(_) => Difficulty::HiddenSingle,
+                    None => Difficulty::NakedSingle,
+                };
+                board.play_cell(idx, num);
+                steps.push(Step::Place {
+                    idx,
+                    num,
+                    difficulty: used.take().map_or(difficulty, Difficulty::Technique),
+                    unit,
+                });
+                continue;
+            }
+
+            // Uniqueness techniques can go wrong on a puzzle with several solutions, so only keep what agrees with ours
+            let progress = Technique::ALL.into_iter().find_map(|technique| {
+                let mut trial = board.clone();
+                trial.track_provenance();
+                let agrees = trial.apply_technique(technique)
+                    && unplayed
+                        .iter()
+                        .all(|&idx| trial.get(idx).is_possible(answer(idx)));
+                agrees.then_some((technique, trial))
+            });
+            if let Some((technique, mut trial)) = progress {
+                let eliminations = trial.provenance.take().unwrap_or_default();
+                steps.push(Step::Apply {
+                    technique,
+                    eliminations,
+                });
+                board = trial;
+                used = used.max(Some(technique));
+                continue;
+            }
+
+            let guess = *unplayed
+                .iter()
+                .min_by_key(|&&idx| board.get(idx).num_possibilities())
+                .expect("there's an unplayed cell");
+            board.play_cell(guess, answer(guess));
+            steps.push(Step::Place {
+                idx: guess,
+                num: answer(guess),
+                difficulty: Difficulty::Guess,
+                unit: None,
+            });
+            used = None;
+        }
+    }
+}
+
+/// The next move a person could work out from the candidates, from [`Board::next_hint`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hint {
+    /// `idx` has one candidate left, `num`
+    NakedSingle { idx: BoardIdx, num: u8 },
+    /// `idx` is the only place left for `num` in `unit`
+    HiddenSingle { idx: BoardIdx, num: u8, unit: Unit },
+    /// No single is available, but `technique` crosses off these candidates
+    Technique {
+        technique: Technique,
+        eliminations: Vec<Elimination>,
+    },
+}
+
+impl std::fmt::Display for Hint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hint::NakedSingle { idx, num } => write!(f, "Place {num} at {idx} (naked single)."),
+            Hint::HiddenSingle { idx, num, unit } => {
+                write!(f, "Place {num} at {idx} (hidden single in {unit}).")
+            }
+            Hint::Technique {
+                technique,
+                eliminations,
+            } => {
+                let removed = eliminations
+                    .iter()
+                    .map(|elimination| format!("{} from {}", elimination.num, elimination.cell))
+                    .join(", ");
+                write!(f, "Use {}, removing {removed}.", technique.name())
+            }
+        }
+    }
+}
+
+impl Board {
+    /// The simplest move that can be worked out from the candidates as they stand, without changing the board
+    ///
+    /// Naked singles come first, then hidden singles, then the first of [`Technique::ALL`] that removes anything.
+    /// Ties go to the first cell in row-major order. Returns `None` once only a guess would get any further,
+    /// or the board is finished. Nothing is checked against the solution, so on a board with a wrong number
+    /// played the hint can follow from that mistake
+    pub fn next_hint(&self) -> Option<Hint> {
+        let units = self.units();
+        let unplayed = iproduct!(0..9, 0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+            .filter(|idx| !self.played.contains(idx))
+            .collect_vec();
+
+        let naked = unplayed
+            .iter()
+            .find_map(|&idx| match self.get(idx).possibilities()[..] {
+                [num] => Some(Hint::NakedSingle { idx, num }),
+                _ => None,
+            });
+        let hidden = || {
+            unplayed
+                .iter()
+                .find_map(|&idx| match single_at(self, &units, idx)? {
+                    (num, Some(unit)) => Some(Hint::HiddenSingle { idx, num, unit }),
+                    (_, None) => None,
+                })
+        };
+        let technique = || {
+            Technique::ALL.into_iter().find_map(|technique| {
+                let mut trial = self.clone();
+                // Started afresh, so what the board already had recorded isn't passed off as part of the hint
+                trial.provenance = Some(Vec::new());
+                trial.apply_technique(technique).then(|| Hint::Technique {
+                    technique,
+                    eliminations: trial.provenance.take().unwrap_or_default(),
+                })
+            })
+        };
+        naked.or_else(hidden).or_else(technique)
+    }
+}
+
+/// A number forced into a cell because copies of it in nearby rows and columns block every other spot in its square,
+/// from [`Board::intersection_hint`]
+///
+/// This is a hidden single in a square seen the way a beginner spots it, so it's worded spatially
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntersectionHint {
+    pub idx: BoardIdx,
+    pub num: u8,
+    /// Rows holding `num` that block part of the square, 0-based
+    pub rows: Vec<usize>,
+    /// Columns holding `num` that block what the rows don't, 0-based
+    pub cols: Vec<usize>,
+}
+
+impl std::fmt::Display for IntersectionHint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let square = Unit::Square(self.idx.square().to_idx());
+        let lines = [("row", &self.rows), ("column", &self.cols)]
+            .into_iter()
+            .filter(|(_, lines)| !lines.is_empty())
+            .map(|(kind, lines)| {
+                let numbers = lines
+                    .iter()
+                    .map(|line| (line + 1).to_string())
+                    .collect_vec();
+                match &numbers[..] {
+                    [only] => format!("{kind} {only}"),
+                    [rest @ .., last] => format!("{kind}s {} and {last}", rest.join(", ")),
+                    [] => unreachable!("empty lists are filtered out"),
+                }
+            })
+            .collect_vec();
+        if lines.is_empty() {
+            return write!(
+                f,
+                "{} is the only empty cell left in {square}, so it takes the {}",
+                self.idx, self.num
+            );
+        }
+        write!(
+            f,
+            "The {}s in {} leave only {} for a {} in {square}",
+            self.num,
+            lines.join(" and "),
+            self.idx,
+            self.num
+        )
+    }
+}
+
+impl Board {
+    /// The first number, by square and then by number, that only fits one cell of its square because the same
+    /// number already played in the crossing rows and columns rules out all the others
+    ///
+    /// Only played cells count as blockers, not pencil marks, so every hint can be checked against the grid alone
+    pub fn intersection_hint(&self) -> Option<IntersectionHint> {
+        let value = |idx: BoardIdx| {
+            self.played
+                .contains(&idx)
+                .then(|| self.get(idx).possibilities()[0])
+        };
+        let holds =
+            |cells: [BoardIdx; 9], num: u8| cells.into_iter().any(|idx| value(idx) == Some(num));
+
+        iproduct!(0..9, 1..=9).find_map(|(square, num)| {
+            let cells = self.iter_square(SquareIdx::from_idx(square));
+            if holds(cells, num) {
+                return None;
+            }
+            let blocked_by_row = |idx: BoardIdx| holds(self.iter_row(idx.row), num);
+            let blocked_by_col = |idx: BoardIdx| holds(self.iter_col(idx.col), num);
+            let open = cells
+                .into_iter()
+                .filter(|idx| value(*idx).is_none())
+                .collect_vec();
+            let [idx] = open
+                .iter()
+                .copied()
+                .filter(|&idx| !blocked_by_row(idx) && !blocked_by_col(idx))
+                .collect_vec()[..]
+            else {
+                return None;
+            };
+            if !self.get(idx).is_possible(num) {
+                return None;
+            }
+
+            let others = open.iter().copied().filter(|&other| other != idx);
+            let rows = others
+                .clone()
+                .filter(|&other| blocked_by_row(other))
+                .map(|other| other.row)
+                .unique()
+                .sorted()
+                .collect();
+            let cols = others
+                .filter(|&other| !blocked_by_row(other))
+                .map(|other| other.col)
+                .unique()
+                .sorted()
+                .collect();
+            Some(IntersectionHint {
+                idx,
+                num,
+                rows,
+                cols,
+            })
+        })
+    }
+}
+
+/// The number `idx` must hold because it's down to one candidate, or it's the only spot left for that number
+/// in one of its units, along with that unit for a hidden single
+fn single_at(board: &Board, units: &[[BoardIdx; 9]], idx: BoardIdx) -> Option<(u8, Option<Unit>)> {
+    let cell = board.get(idx);
+    if let [num] = cell.possibilities()[..] {
+        return Some((num, None));
+    }
+    units
+        .iter()
+        .enumerate()
+        .filter(|(_, unit)| unit.contains(&idx))
+        .find_map(|(unit_idx, unit)| {
+            let num = cell.possibilities().into_iter().find(|&num| {
+                unit.iter().all(|&other| {
+                    other == idx
+                        || board.played.contains(&other)
+                        || !board.get(other).is_possible(num)
+                })
+            })?;
+            Some((num, Some(Unit::from_idx(unit_idx))))
+        })
+}
+
+/// Pairs of unplayed cells that are the only two places `num` can go in some unit
+fn conjugate_pairs(board: &Board, num: u8) -> Vec<(BoardIdx, BoardIdx)> {
+    conjugate_pairs_in(board, num, &board.units())
+}
+
+/// Like [`conjugate_pairs`], but only looking at `units`
+fn conjugate_pairs_in(
+    board: &Board,
+    num: u8,
+    units: &[[BoardIdx; 9]],
+) -> Vec<(BoardIdx, BoardIdx)> {
+    units
+        .iter()
+        .filter_map(|unit| match board.cells_with_candidate(unit, num)[..] {
+            [a, b] => Some((a, b)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Removes `num` from every unplayed cell other than `a` and `b` that sees both of them, returning whether any went
+fn eliminate_seen_by_both(
+    board: &mut Board,
+    num: u8,
+    a: BoardIdx,
+    b: BoardIdx,
+    cause: Cause,
+) -> bool {
+    let mut changed = false;
+    for idx in board.candidate_cells(num) {
+        if idx != a && idx != b && idx.sees(a) && idx.sees(b) {
+            changed |= board.eliminate_candidate(idx, num, cause);
+        }
+    }
+    changed
+}
+
+/// Two conjugate pairs for the same number, `first` and `second`, where `first.0` and `second.0` see each other
+///
+/// At most one of those two holds the number, so one of the pairs' other ends does, and any cell seeing both
+/// can't. Tries every orientation of the pairs, passing the ends that see each other to `linked` to decide
+/// whether they form the pattern being looked for
+fn linked_pairs(
+    board: &mut Board,
+    num: u8,
+    first: (BoardIdx, BoardIdx),
+    second: (BoardIdx, BoardIdx),
+    linked: impl Fn(BoardIdx, BoardIdx) -> bool,
+    cause: Cause,
+) -> bool {
+    let cells = [first.0, first.1, second.0, second.1];
+    if cells.iter().tuple_combinations().any(|(a, b)| a == b) {
+        return false;
+    }
+    let mut changed = false;
+    for ((base, end), (other_base, other_end)) in iproduct!(
+        [(first.0, first.1), (first.1, first.0)],
+        [(second.0, second.1), (second.1, second.0)]
+    ) {
+        if linked(base, other_base) {
+            changed |= eliminate_seen_by_both(board, num, end, other_end, cause);
+        }
+    }
+    changed
+}
+
+/// The unplayed cells of `unit`
+fn open_cells(board: &Board, unit: &[BoardIdx; 9]) -> Vec<BoardIdx> {
+    unit.iter()
+        .copied()
+        .filter(|idx| !board.played.contains(idx))
+        .collect()
+}
+
+/// Naked subsets of any size
+///
+/// When `size` unplayed cells in a unit have only `size` candidates between them, those numbers have to go in
+/// those cells, so they come out of every other cell in the unit
+fn naked_subset(board: &mut Board, size: usize, cause: Cause) -> bool {
+    let mut changed = false;
+
+    for unit in board.units() {
+        let open = open_cells(board, &unit);
+        let small = open
+            .iter()
+            .copied()
+            .filter(|&idx| board.get(idx).num_possibilities() <= size)
+            .collect_vec();
+        for subset in small.into_iter().combinations(size) {
+            let union = subset
+                .iter()
+                .fold(0, |union, &idx| union | board.get(idx).important_bits());
+            if union.count_ones() as usize != size {
+                continue;
+            }
+            for &idx in open.iter().filter(|idx| !subset.contains(idx)) {
+                for num in (1..=9).filter(|num| union >> num & 1 == 1) {
+                    changed |= board.eliminate_candidate(idx, num, cause);
+                }
+            }
+        }
+    }
+
+    changed
+}
+
+/// Hidden subsets of any size
+///
+/// When `size` numbers can only go in the same `size` cells of a unit, those cells have to hold them,
+/// so every other candidate comes out of those cells
+fn hidden_subset(board: &mut Board, size: usize, cause: Cause) -> bool {
+    let mut changed = false;
+
+    for unit in board.units() {
+        // Fixed up front, which is fine: removals only ever shrink where a number can go
+        let places: [Vec<BoardIdx>; 10] =
+            std::array::from_fn(|num| board.cells_with_candidate(&unit, num as u8));
+        let rare = (1..=9)
+            .filter(|&num| (1..=size).contains(&places[num as usize].len()))
+            .collect_vec();
+        for nums in rare.into_iter().combinations(size) {
+            let cells = nums
+                .iter()
+                .flat_map(|&num| &places[num as usize])
+                .copied()
+                .unique()
+                .collect_vec();
+            if cells.len() != size {
+                continue;
+            }
+            for &idx in &cells {
+                for num in board.get(idx).possibilities() {
+                    if !nums.contains(&num) {
+                        changed |= board.eliminate_candidate(idx, num, cause);
+                    }
+                }
+            }
+        }
+    }
+
+    changed
+}
+
+/// Naked Pair: two cells in a unit limited to the same two candidates
+pub fn naked_pair(board: &mut Board) -> bool {
+    naked_subset(board, 2, Cause::NakedPair)
+}
+
+/// Naked Quad: four cells in a unit limited to the same four candidates
+pub fn naked_quad(board: &mut Board) -> bool {
+    naked_subset(board, 4, Cause::NakedQuad)
+}
+
+/// Hidden Quad: four numbers limited to the same four cells of a unit
+pub fn hidden_quad(board: &mut Board) -> bool {
+    hidden_subset(board, 4, Cause::HiddenQuad)
+}
+
+/// Hidden singles: every number left with only one place in some unit is played there
+///
+/// Unlike the other techniques this plays cells rather than just crossing off candidates
+pub fn hidden_singles(board: &mut Board) -> bool {
+    let mut changed = false;
+    for unit in board.units() {
+        for num in 1..=9 {
+            // Looked up fresh, since a cell played earlier in the pass drops out of the places left
+            if let [idx] = board.cells_with_candidate(&unit, num)[..] {
+                board.play_cell(idx, num);
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// [`hidden_singles`] and [`naked_pair`] run over and over until neither finds anything more, returning whether
+/// either did
+///
+/// This is the logic [`Solver`] tries before every guess. Naked singles are left out, since the solver plays those itself
+pub fn propagate(board: &mut Board) -> bool {
+    let mut changed = false;
+    while hidden_singles(board) | naked_pair(board) {
+        changed = true;
+    }
+    changed
+}
+
+/// Skyscraper
+///
+/// Two rows each have just two places for a number, and one place in each shares a column. Both of those can't
+/// hold the number, so one of the other two places does, and any cell seeing both of them can't.
+/// The same goes with rows and columns swapped
+pub fn skyscraper(board: &mut Board) -> bool {
+    let units = board.units();
+    let mut changed = false;
+    for num in 1..=9 {
+        let rows = conjugate_pairs_in(board, num, &units[9..18]);
+        for (first, second) in rows.into_iter().tuple_combinations() {
+            changed |= linked_pairs(
+                board,
+                num,
+                first,
+                second,
+                |a, b| a.col == b.col,
+                Cause::Skyscraper,
+            );
+        }
+        let cols = conjugate_pairs_in(board, num, &units[..9]);
+        for (first, second) in cols.into_iter().tuple_combinations() {
+            changed |= linked_pairs(
+                board,
+                num,
+                first,
+                second,
+                |a, b| a.row == b.row,
+                Cause::Skyscraper,
+            );
+        }
+    }
+    changed
+}
+
+/// Two-String Kite
+///
+/// A row and a column each have just two places for a number, and one place from each shares a square.
+/// Both of those can't hold the number, so one of the other two places does, and any cell seeing both of
+/// them can't
+pub fn two_string_kite(board: &mut Board) -> bool {
+    let units = board.units();
+    let mut changed = false;
+    for num in 1..=9 {
+        let rows = conjugate_pairs_in(board, num, &units[9..18]);
+        let cols = conjugate_pairs_in(board, num, &units[..9]);
+        for (row, col) in iproduct!(rows, cols) {
+            changed |= linked_pairs(
+                board,
+                num,
+                row,
+                col,
+                |a, b| a.square() == b.square(),
+                Cause::TwoStringKite,
+            );
+        }
+    }
+    changed
+}
+
+/// Simple Coloring (single chains)
+///
+/// For each number, cells linked by conjugate pairs form chains alternating between two colors,
+/// exactly one of which holds the number. If two cells of the same color see each other, that color is
+/// false everywhere. Otherwise, any cell outside the chain that sees both colors can't hold the number.
+pub fn simple_coloring(board: &mut Board) -> bool {
+    let mut changed = false;
+
+    for num in 1..=9 {
+        let mut links: HashMap<BoardIdx, Vec<BoardIdx>> = HashMap::new();
+        for (a, b) in conjugate_pairs(board, num) {
+            links.entry(a).or_default().push(b);
+            links.entry(b).or_default().push(a);
+        }
+
+        let mut colors: HashMap<BoardIdx, bool> = HashMap::new();
+        let starts = links
+            .keys()
+            .copied()
+            .sorted_by_key(|idx| idx.idx)
+            .collect_vec();
+        for start in starts {
+            if colors.contains_key(&start) {
+                continue;
+            }
+
+            // Color this chain by walking it breadth first
+            let mut chain = vec![start];
+            colors.insert(start, true);
+            let mut next = 0;
+            while let Some(&idx) = chain.get(next) {
+                next += 1;
+                for &linked in &links[&idx] {
+                    if !colors.contains_key(&linked) {
+                        colors.insert(linked, !colors[&idx]);
+                        chain.push(linked);
+                    }
+                }
+            }
+
+            let (on, off): (Vec<_>, Vec<_>) = chain.iter().partition(|idx| colors[idx]);
+            let wrapped = [&on, &off].into_iter().find(|color| {
+                color
+                    .iter()
+                    .tuple_combinations()
+                    .any(|(a, b): (&BoardIdx, &BoardIdx)| a.sees(*b))
+            });
+
+            if let Some(false_color) = wrapped {
+                for &idx in false_color {
+                    changed |= board.eliminate_candidate(idx, num, Cause::SimpleColoring);
+                }
+                continue;
+            }
+
+            for idx in board.candidate_cells(num) {
+                if chain.contains(&idx) {
+                    continue;
+                }
+                if on.iter().any(|cell| cell.sees(idx)) && off.iter().any(|cell| cell.sees(idx)) {
+                    changed |= board.eliminate_candidate(idx, num, Cause::SimpleColoring);
+                }
+            }
+        }
+    }
+
+    changed
+}
+
+/// The corners of every rectangle spanning two rows, two columns, and exactly two boxes, the shape a swap of two
+/// numbers needs to keep every unit valid
+fn rectangles() -> impl Iterator<Item = [BoardIdx; 4]> {
+    iproduct!(
+        (0..9).tuple_combinations::<(usize, usize)>(),
+        (0..9).tuple_combinations::<(usize, usize)>()
+    )
+    // Exactly one of the rows and columns start a new band or stack, otherwise it's one box or four
+    .filter(|&((r1, r2), (c1, c2))| (r1 / 3 == r2 / 3) != (c1 / 3 == c2 / 3))
+    .map(|((r1, r2), (c1, c2))| {
+        [
+            BoardIdx::new(c1, r1),
+            BoardIdx::new(c2, r1),
+            BoardIdx::new(c1, r2),
+            BoardIdx::new(c2, r2),
+        ]
+    })
+}
+
+impl Board {
+    /// Whether the board is free of the basic deadly pattern: a rectangle like [`unique_rectangle`] looks at,
+    /// with all four corners unplayed and down to the same two candidates
+    ///
+    /// Those two numbers could go either way round, so a board with one can't have a unique solution, and
+    /// uniqueness techniques aren't safe on it
+    pub fn is_deadly_pattern_free(&self) -> bool {
+        !rectangles().any(|corners| {
+            let pair = self.get(corners[0]);
+            pair.num_possibilities() == 2
+                && corners
+                    .iter()
+                    .all(|idx| !self.played.contains(idx) && self.get(*idx) == pair)
+        })
+    }
+}
+
+/// Unique Rectangle, type 1
+///
+/// Four cells spanning two rows, two columns, and two boxes can't all be limited to the same two candidates,
+/// because those could be swapped to give a second solution. So when three corners hold just that pair,
+/// the fourth corner must be one of its other candidates.
+///
+/// This assumes the puzzle has exactly one solution; on a puzzle with several it can remove real solutions
+pub fn unique_rectangle(board: &mut Board) -> bool {
+    let mut changed = false;
+
+    for corners in rectangles() {
+        if corners.iter().any(|idx| board.played.contains(idx)) {
+            continue;
+        }
+
+        let Some(pair) = corners
+            .iter()
+            .map(|&idx| board.get(idx))
+            .find(|cell| cell.num_possibilities() == 2)
+        else {
+            continue;
+        };
+        let (exact, rest): (Vec<_>, Vec<_>) =
+            corners.into_iter().partition(|&idx| board.get(idx) == pair);
+        let [roof] = rest[..] else {
+            continue;
+        };
+        debug_assert_eq!(exact.len(), 3);
+
+        let nums = pair.possibilities();
+        if nums.iter().all(|&num| board.get(roof).is_possible(num)) {
+            for num in nums {
+                changed |= board.eliminate_candidate(roof, num, Cause::UniqueRectangle);
+            }
+        }
+    }
+
+    changed
+}
+
+/// Bivalue Universal Grave + 1
+///
+/// If every unsolved cell has two candidates except one with three, and each candidate shows up exactly twice in
+/// every unit once that cell's extra candidate is ignored, the board would otherwise have two solutions.
+/// So the odd cell must take its extra candidate, and its other two are removed.
+///
+/// This assumes the puzzle has exactly one solution; on a puzzle with several it can remove real solutions
+pub fn bug_plus_one(board: &mut Board) -> bool {
+    let unsolved = board.unsolved_by_candidates();
+    let (odd, bivalue) = match unsolved.split_last() {
+        Some((&(odd, 3), rest)) => (odd, rest),
+        _ => return false,
+    };
+    if bivalue.iter().any(|&(_, candidates)| candidates != 2) {
+        return false;
+    }
+
+    let units = board.units();
+    let odd_candidates = board.get(odd).possibilities();
+    let grave_without = |extra: u8| {
+        units.iter().all(|unit| {
+            (1..=9).all(|num| {
+                let count = unit
+                    .iter()
+                    .filter(|&&idx| {
+                        !board.played.contains(&idx)
+                            && board.get(idx).is_possible(num)
+                            && !(idx == odd && num == extra)
+                    })
+                    .count();
+                count == 0 || count == 2
+            })
+        })
+    };
+    let Ok(extra) = odd_candidates
+        .iter()
+        .copied()
+        .filter(|&extra| grave_without(extra))
+        .exactly_one()
+    else {
+        return false;
+    };
+
+    for num in odd_candidates {
+        if num != extra {
+            board.eliminate_candidate(odd, num, Cause::BugPlusOne);
+        }
+    }
+    true
+}
+
+/// Whether `board` has an empty cell, or a unit with nowhere left for some number
+fn is_contradiction(board: &Board, units: &[[BoardIdx; 9]]) -> bool {
+    let every_number = 0b1111111110;
+    board.cells.iter().any(|cell| cell.important_bits() == 0)
+        || units.iter().any(|unit| {
+            let candidates = unit.iter().fold(0, |candidates, &idx| {
+                candidates | board.get(idx).important_bits()
+            });
+            candidates != every_number
+        })
+}
+
+/// Nishio, a bounded trial and error
+///
+/// Each candidate of every cell left with two is played on a copy of the board, followed by up to `depth` rounds
+/// of naked and hidden singles. If that runs into a contradiction the candidate can't be right, so it's removed.
+/// A larger `depth` finds more, but each trial costs more
+pub fn nishio(board: &mut Board, depth: usize) -> bool {
+    let mut changed = false;
+    let units = board.units();
+
+    let bivalue = iproduct!(0..9, 0..9)
+        .map(|(row, col)| BoardIdx::new(col, row))
+        .filter(|idx| !board.played.contains(idx) && board.get(*idx).num_possibilities() == 2)
+        .collect_vec();
+    for idx in bivalue {
+        for num in board.get(idx).possibilities() {
+            // An earlier removal can leave this cell down to one candidate, which mustn't be tried away
+            if board.get(idx).num_possibilities() < 2 {
+                break;
+            }
+            let mut trial = board.clone();
+            trial.provenance = None;
+            trial.play_cell(idx, num);
+            if trial_contradicts(&mut trial, &units, depth) {
+                changed |= board.eliminate_candidate(idx, num, Cause::Nishio);
+            }
+        }
+    }
+
+    changed
+}
+
+/// Plays up to `depth` rounds of singles on `trial`, returning whether it hits a contradiction along the way
+fn trial_contradicts(trial: &mut Board, units: &[[BoardIdx; 9]], depth: usize) -> bool {
+    for _ in 0..depth {
+        if is_contradiction(trial, units) {
+            return true;
+        }
+        let singles = iproduct!(0..9, 0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+            .filter(|idx| !trial.played.contains(idx))
+            .filter_map(|idx| single_at(trial, units, idx).map(|(num, _)| (idx, num)))
+            .collect_vec();
+        if singles.is_empty() {
+            return false;
+        }
+        for (idx, num) in singles {
+            if trial.get(idx).is_possible(num) {
+                trial.play_cell(idx, num);
+            }
+        }
+    }
+    is_contradiction(trial, units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A board with every candidate left except at `cells`, which get just the numbers given
+    fn with_candidates(cells: &[(BoardIdx, &[u8])]) -> Board {
+        let mut grid = Board::new().to_candidate_grid();
+        for &(idx, nums) in cells {
+            grid[idx.row][idx.col] = nums.iter().map(|&num| 1 << num).sum();
+        }
+        Board::from_candidate_grid(&grid)
+    }
+
+    #[test]
+    fn unique_rectangle_clears_the_pair_from_the_fourth_corner() {
+        let roof = BoardIdx::new(3, 1);
+        let mut board = with_candidates(&[
+            (BoardIdx::new(0, 0), &[1, 2]),
+            (BoardIdx::new(3, 0), &[1, 2]),
+            (BoardIdx::new(0, 1), &[1, 2]),
+            (roof, &[1, 2, 7]),
+        ]);
+        assert!(unique_rectangle(&mut board));
+        assert_eq!(board.candidates(roof), vec![7]);
+        assert!(!unique_rectangle(&mut board));
+    }
+
+    #[test]
+    fn unique_rectangle_needs_two_boxes() {
+        // All four corners in the top left box, which isn't the deadly pattern
+        let roof = BoardIdx::new(1, 1);
+        let mut board = with_candidates(&[
+            (BoardIdx::new(0, 0), &[1, 2]),
+            (BoardIdx::new(1, 0), &[1, 2]),
+            (BoardIdx::new(0, 1), &[1, 2]),
+            (roof, &[1, 2, 7]),
+        ]);
+        assert!(!unique_rectangle(&mut board));
+        assert_eq!(board.candidates(roof), vec![1, 2, 7]);
+    }
+
+    #[test]
+    fn naked_quad_leaves_a_single() {
+        let row = |col| BoardIdx::new(col, 0);
+        let mut board = with_candidates(&[
+            (row(0), &[1, 2]),
+            (row(1), &[2, 3]),
+            (row(2), &[3, 4]),
+            (row(3), &[1, 4]),
+            (row(4), &[1, 2, 3, 4, 5]),
+        ]);
+        assert!(naked_quad(&mut board));
+        assert_eq!(board.candidates(row(4)), vec![5]);
+        assert_eq!(board.candidates(row(8)), vec![5, 6, 7, 8, 9]);
+        assert_eq!(board.candidates(row(0)), vec![1, 2]);
+    }
+
+    #[test]
+    fn hidden_quad_clears_the_other_candidates() {
+        let row = |col| BoardIdx::new(col, 0);
+        let rest: &[u8] = &[5, 6, 7, 8, 9];
+        let mut board = with_candidates(&[
+            (row(0), &[1, 2, 7]),
+            (row(1), &[2, 3, 8, 9]),
+            (row(2), &[3, 4, 5]),
+            (row(3), &[1, 4, 6]),
+            (row(4), rest),
+            (row(5), rest),
+            (row(6), rest),
+            (row(7), rest),
+            (row(8), rest),
+        ]);
+        assert!(hidden_quad(&mut board));
+        assert_eq!(board.candidates(row(0)), vec![1, 2]);
+        assert_eq!(board.candidates(row(1)), vec![2, 3]);
+        assert_eq!(board.candidates(row(2)), vec![3, 4]);
+        assert_eq!(board.candidates(row(3)), vec![1, 4]);
+        assert!(!hidden_quad(&mut board));
+    }
+
+    #[test]
+    fn quads_keep_the_solution() {
+        for puzzle in [
+            include_str!("../examples/easy1.txt"),
+            include_str!("../examples/extreme1.txt"),
+        ] {
+            let mut board: Board = puzzle.parse().unwrap();
+            let solution = crate::solve(&board).unwrap();
+            while naked_quad(&mut board) | hidden_quad(&mut board) {}
+            for idx in (0..81).map(BoardIdx::from_linear) {
+                let num = solution.get(idx).possibilities()[0];
+                assert!(
+                    board.get(idx).is_possible(num),
+                    "{num} was taken out of {idx}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fill_naked_singles_once_fills_one_batch() {
+        let mut board: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let singles = (0..81)
+            .map(BoardIdx::from_linear)
+            .filter(|idx| !board.played.contains(idx) && board.get(*idx).num_possibilities() == 1)
+            .collect_vec();
+        assert!(singles.len() > 1);
+        let played = board.played.len();
+
+        assert_eq!(board.fill_naked_singles_once(), singles.len());
+        assert_eq!(board.played.len(), played + singles.len());
+        assert!(singles.iter().all(|idx| board.played.contains(idx)));
+        assert!(board.verify_detailed().is_ok());
+    }
+
+    #[test]
+    fn fill_naked_singles_once_skips_a_clashing_single() {
+        let (first, second) = (BoardIdx::new(0, 0), BoardIdx::new(5, 0));
+        let mut board = with_candidates(&[(first, &[5]), (second, &[5])]);
+        assert_eq!(board.fill_naked_singles_once(), 1);
+        assert!(board.played.contains(&first));
+        assert!(!board.played.contains(&second));
+        assert_eq!(board.get(second).num_possibilities(), 0);
+    }
+
+    #[test]
+    fn nishio_removes_a_candidate_that_empties_a_peer() {
+        let (cell, peer) = (BoardIdx::new(0, 0), BoardIdx::new(8, 0));
+        let mut board = with_candidates(&[(cell, &[1, 2]), (peer, &[1])]);
+        assert!(nishio(&mut board, 0));
+        assert_eq!(board.candidates(cell), vec![2]);
+        assert_eq!(board.candidates(peer), vec![1]);
+    }
+
+    #[test]
+    fn nishio_makes_progress_on_the_extreme_example() {
+        let mut board: Board = include_str!("../examples/extreme1.txt").parse().unwrap();
+        let solution = crate::solve(&board).unwrap();
+        let before = board.total_candidates();
+        assert!(nishio(&mut board, NISHIO_DEPTH));
+        assert!(board.total_candidates() < before);
+        for idx in (0..81).map(BoardIdx::from_linear) {
+            let num = solution.get(idx).possibilities()[0];
+            assert!(
+                board.get(idx).is_possible(num),
+                "{num} was taken out of {idx}"
+            );
+        }
+    }
+
+    #[test]
+    fn a_naked_single_costs_less_than_a_technique() {
+        let costs = HintCosts::default();
+        let single = costs.cost(Difficulty::NakedSingle);
+        assert!(single < costs.cost(Difficulty::HiddenSingle));
+        for technique in Technique::ALL {
+            assert!(
+                single < costs.cost(Difficulty::Technique(technique)),
+                "{technique:?}"
+            );
+        }
+        assert!(
+            costs.cost(Difficulty::Technique(Technique::Nishio)) < costs.cost(Difficulty::Guess)
+        );
+    }
+
+    #[test]
+    fn hint_cost_charges_for_the_next_step() {
+        let board: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let (_, _, difficulty) = board.solution_path().unwrap()[0];
+        assert_eq!(difficulty, Difficulty::NakedSingle);
+        assert_eq!(board.hint_cost(), Some(HintCosts::default().naked_single));
+
+        let costs = HintCosts {
+            naked_single: 10,
+            ..HintCosts::default()
+        };
+        assert_eq!(board.hint_cost_with(&costs), Some(10));
+
+        let solved = crate::solve(&board).unwrap();
+        assert_eq!(solved.hint_cost(), None);
+    }
+
+    #[test]
+    fn deadly_pattern_across_two_boxes() {
+        let corners = [
+            BoardIdx::new(0, 0),
+            BoardIdx::new(3, 0),
+            BoardIdx::new(0, 1),
+            BoardIdx::new(3, 1),
+        ];
+        let deadly = with_candidates(&corners.map(|idx| (idx, &[1, 2][..])));
+        assert!(!deadly.is_deadly_pattern_free());
+
+        // One corner with a third candidate breaks the pattern
+        let mut broken = corners.map(|idx| (idx, &[1, 2][..]));
+        broken[3].1 = &[1, 2, 7];
+        assert!(with_candidates(&broken).is_deadly_pattern_free());
+
+        // All in one box isn't deadly, since the box would need both numbers twice
+        let one_box = [
+            BoardIdx::new(0, 0),
+            BoardIdx::new(1, 0),
+            BoardIdx::new(0, 1),
+            BoardIdx::new(1, 1),
+        ];
+        assert!(with_candidates(&one_box.map(|idx| (idx, &[1, 2][..]))).is_deadly_pattern_free());
+        assert!(Board::new().is_deadly_pattern_free());
+    }
+
+    #[test]
+    fn logical_closure_solves_the_easy_puzzle_but_not_the_hard_one() {
+        let easy: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let closure = easy.logical_closure();
+        assert!(closure.is_solved());
+        assert_eq!(closure.to_line(), crate::solve(&easy).unwrap().to_line());
+        assert!(!easy.is_solved());
+
+        // "Easter Monster", which none of the techniques here make a dent in
+        let hard: Board =
+            "1.......2.9.4...5...6...7...5.9.3.......7.......85..4.7.....6...3...9.8...2.....1"
+                .parse()
+                .unwrap();
+        let closure = hard.logical_closure();
+        assert!(!closure.is_solved());
+        assert!(hard.has_unique_solution());
+        assert_eq!(closure.played, hard.played);
+        let solution = crate::solve(&hard).unwrap();
+        for idx in (0..81).map(BoardIdx::from_linear) {
+            assert!(
+                closure
+                    .get(idx)
+                    .is_possible(solution.get(idx).possibilities()[0])
+            );
+        }
+    }
+
+    /// A board with every candidate left, except that `num` can only go in `cells` within `rows` and `cols`
+    fn only_in(num: u8, cells: &[BoardIdx], rows: &[usize], cols: &[usize]) -> Board {
+        let mut grid = Board::new().to_candidate_grid();
+        for (row, col) in iproduct!(0..9, 0..9) {
+            let restricted = rows.contains(&row) || cols.contains(&col);
+            if restricted && !cells.contains(&BoardIdx::new(col, row)) {
+                grid[row][col] &= !(1 << num);
+            }
+        }
+        Board::from_candidate_grid(&grid)
+    }
+
+    /// The cells that lost `num` between `before` and `after`
+    fn lost(before: &Board, after: &Board, num: u8) -> Vec<BoardIdx> {
+        (0..81)
+            .map(BoardIdx::from_linear)
+            .filter(|&idx| before.get(idx).is_possible(num) && !after.get(idx).is_possible(num))
+            .collect()
+    }
+
+    #[test]
+    fn skyscraper_clears_cells_seeing_both_ends() {
+        // Rows 1 and 6 can only take a 1 in column 1 and one other column each, at R1C5 and R6C4
+        let cells = [
+            BoardIdx::new(0, 0),
+            BoardIdx::new(4, 0),
+            BoardIdx::new(0, 5),
+            BoardIdx::new(3, 5),
+        ];
+        let before = only_in(1, &cells, &[0, 5], &[]);
+        let mut board = before.clone();
+        assert!(skyscraper(&mut board));
+        let expected = [(3, 1), (3, 2), (4, 3), (4, 4)].map(|(col, row)| BoardIdx::new(col, row));
+        assert_eq!(lost(&before, &board, 1), expected);
+        assert!(!skyscraper(&mut board));
+    }
+
+    #[test]
+    fn two_string_kite_clears_the_cell_seeing_both_strings() {
+        // Row 1 takes a 1 at R1C2 or R1C7, column 1 at R3C1 or R8C1, and R1C2 and R3C1 share a square
+        let cells = [
+            BoardIdx::new(1, 0),
+            BoardIdx::new(6, 0),
+            BoardIdx::new(0, 2),
+            BoardIdx::new(0, 7),
+        ];
+        let before = only_in(1, &cells, &[0], &[0]);
+        let mut board = before.clone();
+        assert!(two_string_kite(&mut board));
+        assert_eq!(lost(&before, &board, 1), [BoardIdx::new(6, 7)]);
+        assert!(!two_string_kite(&mut board));
+    }
+
+    #[test]
+    fn available_singles_of_an_easy_and_a_hard_puzzle() {
+        let easy: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let hard: Board =
+            "1.......2.9.4...5...6...7...5.9.3.......7.......85..4.7.....6...3...9.8...2.....1"
+                .parse()
+                .unwrap();
+        assert_eq!(easy.available_singles(), 20);
+        assert_eq!(hard.available_singles(), 0);
+    }
+
+    #[test]
+    fn available_singles_counts_a_cell_once() {
+        // The last cell of the row is a naked single and a hidden single in its row
+        let mut board = Board::new();
+        for col in 0..8 {
+            board.play_cell(BoardIdx::new(col, 0), col as u8 + 1);
+        }
+        assert_eq!(board.available_singles(), 1);
+        assert_eq!(Board::new().available_singles(), 0);
+    }
+
+    #[test]
+    fn solve_within_guesses_at_budgets_zero_one_and_unlimited() {
+        let easy: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let solution = crate::solve(&easy).unwrap();
+        assert_eq!(easy.solve_within_guesses(0), Ok(Some(solution)));
+
+        // Easter Monster with three more givens from its solution, which logic gets stuck on but one guess finishes
+        let one_guess: Board =
+            "174..5..2.9.4...5...6...7...5.9.3.......7.......85..4.7.....6...3...9.8...2.....1"
+                .parse()
+                .unwrap();
+        let solution = crate::solve(&one_guess).unwrap();
+        assert_eq!(one_guess.solve_within_guesses(0), Err(TooDeep));
+        assert_eq!(
+            one_guess.solve_within_guesses(1),
+            Ok(Some(solution.clone()))
+        );
+        assert_eq!(
+            one_guess.solve_within_guesses(usize::MAX),
+            Ok(Some(solution))
+        );
+
+        let hard: Board =
+            "1.......2.9.4...5...6...7...5.9.3.......7.......85..4.7.....6...3...9.8...2.....1"
+                .parse()
+                .unwrap();
+        assert_eq!(hard.solve_within_guesses(1), Err(TooDeep));
+        assert_eq!(
+            hard.solve_within_guesses(usize::MAX),
+            Ok(Some(crate::solve(&hard).unwrap()))
+        );
+
+        let unsolvable: Board = include_str!("../examples/fail3.txt").parse().unwrap();
+        assert_eq!(unsolvable.solve_within_guesses(usize::MAX), Ok(None));
+    }
+
+    #[test]
+    fn solve_report_of_the_easy_puzzle() {
+        let easy: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let report = easy.solve_report();
+        assert_eq!(report.solution, Some(crate::solve(&easy).unwrap()));
+        assert_eq!(report.steps, easy.solution_path().unwrap());
+        assert_eq!(report.steps.len(), 81 - easy.played.len());
+        assert_eq!(report.difficulty, Some(Difficulty::NakedSingle));
+        assert!(report.unique);
+        assert_eq!(
+            report.stats,
+            SolveStats {
+                nodes: 1,
+                solution_depth: Some(0),
+                propagated: 43,
+                ..SolveStats::default()
+            }
+        );
+
+        let unsolvable: Board = include_str!("../examples/fail3.txt").parse().unwrap();
+        let report = unsolvable.solve_report();
+        assert_eq!(report.solution, None);
+        assert!(report.steps.is_empty());
+        assert_eq!(report.difficulty, None);
+        assert!(!report.unique);
+        assert_eq!(report.stats.solution_depth, None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn solve_report_round_trips_through_json() {
+        let easy: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let report = easy.solve_report();
+        let json = serde_json::to_string(&report).unwrap();
+        let solution = report.solution.as_ref().unwrap().to_line();
+        assert!(json.contains(&format!("\"solution\":\"{solution}\"")));
+        assert!(json.contains("\"difficulty\":\"NakedSingle\""));
+
+        let back: SolveReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.solution, report.solution);
+        assert_eq!(back.steps, report.steps);
+        assert_eq!(back.stats, report.stats);
+        assert_eq!(back.difficulty, report.difficulty);
+        assert_eq!(back.unique, report.unique);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn a_cell_off_the_board_does_not_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<BoardIdx>(r#"{"col":4,"row":7}"#).unwrap(),
+            BoardIdx::new(4, 7)
+        );
+        assert!(serde_json::from_str::<BoardIdx>(r#"{"col":9,"row":0}"#).is_err());
+    }
+
+    #[test]
+    fn propagate_clears_a_naked_pair_from_its_units() {
+        let pair = [BoardIdx::new(0, 0), BoardIdx::new(1, 0)];
+        let mut board = with_candidates(&[(pair[0], &[1, 2]), (pair[1], &[1, 2])]);
+        assert!(propagate(&mut board));
+        for idx in [BoardIdx::new(5, 0), BoardIdx::new(2, 2)] {
+            assert!(!board.get(idx).is_possible(1) && !board.get(idx).is_possible(2));
+        }
+        assert!(board.get(BoardIdx::new(0, 5)).is_possible(1));
+        assert!(!propagate(&mut board));
+    }
+
+    #[test]
+    fn propagate_plays_a_hidden_single() {
+        let others: Vec<_> = (0..9)
+            .filter(|&col| col != 4)
+            .map(|col| (BoardIdx::new(col, 4), &[1, 2, 3, 4, 6, 7, 8, 9][..]))
+            .collect();
+        let mut board = with_candidates(&others);
+        assert!(propagate(&mut board));
+        assert_eq!(board.played_num(BoardIdx::new(4, 4)), Some(5));
+        assert!(!board.get(BoardIdx::new(4, 0)).is_possible(5));
+    }
+
+    #[test]
+    fn simple_coloring_clears_the_color_that_sees_itself() {
+        // A 1 goes at R1C1 or R1C5, R1C5 or R5C5, R5C5 or R5C2, and R5C2 or R2C2, so R1C1, R5C5 and R2C2 all
+        // take the same color, and R1C1 and R2C2 share a square
+        let cells = [
+            BoardIdx::new(0, 0),
+            BoardIdx::new(4, 0),
+            BoardIdx::new(4, 4),
+            BoardIdx::new(1, 4),
+            BoardIdx::new(1, 1),
+        ];
+        let before = only_in(1, &cells, &[0, 4], &[1, 4]);
+        let mut board = before.clone();
+        assert!(simple_coloring(&mut board));
+        let expected = [(0, 0), (1, 1), (4, 4)].map(|(col, row)| BoardIdx::new(col, row));
+        assert_eq!(lost(&before, &board, 1), expected);
+        for idx in [cells[1], cells[3]] {
+            assert!(board.get(idx).is_possible(1));
+        }
+    }
+
+    /// The easy puzzle's solution with the deadly rectangle in rows 4 and 5, columns 3 and 7, left unplayed with
+    /// just its 4 and 5, and each of `extra` added to its cell
+    fn grave_plus(extra: &[(BoardIdx, u8)]) -> Board {
+        let easy: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let mut board = crate::solve(&easy).unwrap();
+        for (col, row) in iproduct!([2, 6], [3, 4]) {
+            let idx = BoardIdx::new(col, row);
+            assert!([4, 5].contains(&board.played_num(idx).unwrap()));
+            board.played.remove(&idx);
+            board.cells[idx.idx] = crate::Cell::fixed(4);
+            board.cells[idx.idx].set_possible(5, true);
+        }
+        for &(idx, num) in extra {
+            board.cells[idx.idx].set_possible(num, true);
+        }
+        board
+    }
+
+    #[test]
+    fn bug_plus_one_plays_the_odd_candidate() {
+        let odd = BoardIdx::new(2, 3);
+        let mut board = grave_plus(&[(odd, 9)]);
+        assert!(bug_plus_one(&mut board));
+        assert_eq!(board.candidates(odd), [9]);
+        for (col, row) in [(6, 3), (2, 4), (6, 4)] {
+            assert_eq!(board.candidates(BoardIdx::new(col, row)), [4, 5]);
+        }
+    }
+
+    #[test]
+    fn bug_plus_one_needs_a_single_odd_cell() {
+        let before = grave_plus(&[(BoardIdx::new(2, 3), 9), (BoardIdx::new(6, 4), 8)]);
+        let mut board = before.clone();
+        assert!(!bug_plus_one(&mut board));
+        assert_eq!(board.to_candidate_grid(), before.to_candidate_grid());
+
+        // Without the extra candidate it's a grave with two solutions, and no cell to break it with
+        let mut board = grave_plus(&[]);
+        assert!(!bug_plus_one(&mut board));
+    }
+
+    #[test]
+    fn solve_and_explain_numbers_each_placement_of_the_easy_puzzle() {
+        let board: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let explanation = board.solve_and_explain().unwrap();
+        let lines = explanation.lines().collect_vec();
+        assert_eq!(
+            lines[..3],
+            [
+                "1. Place 6 at R1C6 (naked single).",
+                "2. Place 4 at R1C9 (naked single).",
+                "3. Place 9 at R3C4 (naked single).",
+            ]
+        );
+        assert_eq!(lines.len(), 43);
+        for (i, line) in lines.iter().enumerate() {
+            assert!(line.starts_with(&format!("{}. Place ", i + 1)), "{line}");
+            assert!(line.ends_with("(naked single)."), "{line}");
+        }
+    }
+
+    #[test]
+    fn a_solved_board_needs_no_explanation() {
+        let board: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let solution = crate::solve(&board).unwrap();
+        assert_eq!(solution.solve_and_explain().as_deref(), Some(""));
+        let unsolvable: Board = include_str!("../examples/fail3.txt").parse().unwrap();
+        assert_eq!(unsolvable.solve_and_explain(), None);
+    }
+
+    #[test]
+    fn intersection_hint_names_the_blocking_rows_and_columns() {
+        let mut board = Board::new();
+        for (col, row) in [(3, 0), (6, 1), (0, 3), (1, 6)] {
+            board.play_cell(BoardIdx::new(col, row), 5);
+        }
+        let hint = board.intersection_hint().unwrap();
+        assert_eq!(
+            hint,
+            IntersectionHint {
+                idx: BoardIdx::new(2, 2),
+                num: 5,
+                rows: vec![0, 1],
+                cols: vec![0, 1],
+            }
+        );
+        assert_eq!(
+            hint.to_string(),
+            "The 5s in rows 1 and 2 and columns 1 and 2 leave only R3C3 for a 5 in square TL"
+        );
+    }
+
+    #[test]
+    fn intersection_hint_on_the_easy_puzzle() {
+        let board: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let hint = board.intersection_hint().unwrap();
+        assert_eq!((hint.idx, hint.num), (BoardIdx::new(1, 2), 2));
+        assert_eq!((&hint.rows[..], &hint.cols[..]), (&[0][..], &[0][..]));
+        assert_eq!(
+            hint.to_string(),
+            "The 2s in row 1 and column 1 leave only R3C2 for a 2 in square TL"
+        );
+        assert_eq!(crate::solve(&board).unwrap().intersection_hint(), None);
+    }
+
+    #[test]
+    fn next_hint_starts_with_the_first_naked_single() {
+        let board: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        let hint = board.next_hint().unwrap();
+        assert_eq!(
+            hint,
+            Hint::NakedSingle {
+                idx: BoardIdx::new(5, 0),
+                num: 6
+            }
+        );
+        assert_eq!(hint.to_string(), "Place 6 at R1C6 (naked single).");
+    }
+
+    #[test]
+    fn next_hint_falls_back_to_a_hidden_single() {
+        let mut board = Board::new();
+        for (col, row) in [(3, 0), (6, 1), (0, 3), (1, 6)] {
+            board.play_cell(BoardIdx::new(col, row), 5);
+        }
+        assert_eq!(
+            board.next_hint(),
+            Some(Hint::HiddenSingle {
+                idx: BoardIdx::new(2, 2),
+                num: 5,
+                unit: Unit::Column(2)
+            })
+        );
+    }
+
+    #[test]
+    fn next_hint_is_none_when_solved_or_stuck() {
+        let board: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        assert_eq!(crate::solve(&board).unwrap().next_hint(), None);
+        let easter_monster: Board =
+            "1.......2.9.4...5...6...7...5.9.3.......7.......85..4.7.....6...3...9.8...2.....1"
+                .parse()
+                .unwrap();
+        assert_eq!(easter_monster.next_hint(), None);
+    }
+
+    #[test]
+    fn rate_difficulty_goes_by_the_hardest_step() {
+        let easy: Board = include_str!("../examples/easy1.txt").parse().unwrap();
+        assert_eq!(easy.rate_difficulty(), Some(Rating::Easy));
+        assert_eq!(
+            crate::solve(&easy).unwrap().rate_difficulty(),
+            Some(Rating::Easy)
+        );
+
+        let easter_monster: Board =
+            "1.......2.9.4...5...6...7...5.9.3.......7.......85..4.7.....6...3...9.8...2.....1"
+                .parse()
+                .unwrap();
+        assert_eq!(easter_monster.rate_difficulty(), Some(Rating::Expert));
+
+        let unsolvable: Board = include_str!("../examples/fail3.txt").parse().unwrap();
+        assert_eq!(unsolvable.rate_difficulty(), None);
+    }
+}
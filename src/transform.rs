@@ -0,0 +1,661 @@
+//! Validity-preserving transformations that turn a board into an equivalent one
+
+use std::{array, cmp::Ordering, collections::HashSet};
+
+use itertools::{Itertools, iproduct};
+
+use crate::{
+    Board, BoardIdx, Cell,
+    constraint::{Cage, Constraint},
+    rng::Rng,
+};
+
+/// Every ordering of three items
+const PERMUTATIONS_3: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+/// The 1296 orderings of nine lines that keep bands (or stacks) together
+fn line_orders() -> Vec<[usize; 9]> {
+    iproduct!(
+        PERMUTATIONS_3,
+        PERMUTATIONS_3,
+        PERMUTATIONS_3,
+        PERMUTATIONS_3
+    )
+    .map(|(bands, first, second, third)| {
+        let within = [first, second, third];
+        array::from_fn(|line| bands[line / 3] * 3 + within[line / 3][line % 3])
+    })
+    .collect()
+}
+
+/// `line` with `a` and `b` exchanged
+fn swapped(line: usize, a: usize, b: usize) -> usize {
+    match line {
+        line if line == a => b,
+        line if line == b => a,
+        line => line,
+    }
+}
+
+/// A way the clue layout can map onto itself, strongest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Symmetry {
+    /// Unchanged by every rotation and reflection of the square
+    Full,
+    /// Unchanged by a quarter turn
+    QuarterTurn,
+    /// Unchanged by a half turn, the classic newspaper symmetry
+    HalfTurn,
+    /// Unchanged when mirrored left to right
+    Horizontal,
+    /// Unchanged when mirrored top to bottom
+    Vertical,
+    /// Unchanged when mirrored across the diagonal from the top left corner
+    Diagonal,
+    /// Unchanged when mirrored across the diagonal from the top right corner
+    AntiDiagonal,
+}
+
+impl Symmetry {
+    /// Every symmetry, strongest first
+    pub const ALL: [Symmetry; 7] = [
+        Symmetry::Full,
+        Symmetry::QuarterTurn,
+        Symmetry::HalfTurn,
+        Symmetry::Horizontal,
+        Symmetry::Vertical,
+        Symmetry::Diagonal,
+        Symmetry::AntiDiagonal,
+    ];
+
+    /// The cell positions that have to match for this symmetry, as maps from each cell to its partner
+    fn maps(self) -> Vec<fn(BoardIdx) -> BoardIdx> {
+        let quarter_turn: fn(BoardIdx) -> BoardIdx = |idx| BoardIdx::new(8 - idx.row, idx.col);
+        let half_turn: fn(BoardIdx) -> BoardIdx = |idx| BoardIdx::new(8 - idx.col, 8 - idx.row);
+        let horizontal: fn(BoardIdx) -> BoardIdx = |idx| BoardIdx::new(8 - idx.col, idx.row);
+        let vertical: fn(BoardIdx) -> BoardIdx = |idx| BoardIdx::new(idx.col, 8 - idx.row);
+        let diagonal: fn(BoardIdx) -> BoardIdx = |idx| BoardIdx::new(idx.row, idx.col);
+        let anti_diagonal: fn(BoardIdx) -> BoardIdx = |idx| BoardIdx::new(8 - idx.row, 8 - idx.col);
+        match self {
+            // A quarter turn and one mirror generate all eight
+            Symmetry::Full => vec![quarter_turn, horizontal],
+            Symmetry::QuarterTurn => vec![quarter_turn],
+            Symmetry::HalfTurn => vec![half_turn],
+            Symmetry::Horizontal => vec![horizontal],
+            Symmetry::Vertical => vec![vertical],
+            Symmetry::Diagonal => vec![diagonal],
+            Symmetry::AntiDiagonal => vec![anti_diagonal],
+        }
+    }
+}
+
+impl Board {
+    /// Builds a board where each cell is copied from the cell `source` maps it to
+    ///
+    /// `source` must be a bijection. Givens, candidates crossed off through [`Board::eliminate`], and the cells of
+    /// cages and thermometers move along with their cells. Anti-knight, windows, and diagonals stay where they are, so
+    /// `source` has to map each of them onto itself, as every rotation and reflection does. The result doesn't carry
+    /// over provenance, since it refers to the old positions
+    ///
+    /// Panics if `source` moves one of those constraints
+    #[track_caller]
+    fn remap(&self, source: impl Fn(BoardIdx) -> BoardIdx) -> Board {
+        assert!(
+            self.keeps_constraints(&source),
+            "The transformation would move the board's anti-knight, windows, or diagonals constraint"
+        );
+        let mut target = [BoardIdx::new(0, 0); 81];
+        let mut board = Board::new();
+        for idx in (0..9)
+            .cartesian_product(0..9)
+            .map(|(row, col)| BoardIdx::new(col, row))
+        {
+            let from = source(idx);
+            target[from.idx] = idx;
+            board.set_raw(idx, self.get(from));
+            if self.played.contains(&from) {
+                board.played.insert(idx);
+            }
+        }
+
+        let moved = |cells: &[BoardIdx]| cells.iter().map(|idx| target[idx.idx]).collect();
+        board.givens = self.givens.iter().map(|idx| target[idx.idx]).collect();
+        board.user_eliminations = self
+            .user_eliminations
+            .iter()
+            .map(|&(idx, num)| (target[idx.idx], num))
+            .collect();
+        board.constraints = self
+            .constraints
+            .iter()
+            .map(|constraint| match constraint {
+                Constraint::Thermometer(path) => Constraint::Thermometer(moved(path)),
+                Constraint::Cage(cage) => Constraint::Cage(Cage {
+                    cells: moved(&cage.cells),
+                    sum: cage.sum,
+                }),
+                constraint => constraint.clone(),
+            })
+            .collect();
+        board
+    }
+
+    /// Whether `source`, as passed to [`Board::remap`], maps the anti-knight, windows, and diagonals constraints
+    /// onto themselves, so the cells each one relates are still related afterwards
+    fn keeps_constraints(&self, source: &impl Fn(BoardIdx) -> BoardIdx) -> bool {
+        let fixed = self.constraints.iter().filter(|constraint| {
+            matches!(
+                constraint,
+                Constraint::AntiKnight | Constraint::Windows | Constraint::Diagonals
+            )
+        });
+        fixed.into_iter().all(|constraint| {
+            let mut alone = Board::new();
+            alone.constraints.push(constraint.clone());
+            (0..81).map(BoardIdx::from_linear).all(|idx| {
+                let peers: HashSet<BoardIdx> = alone
+                    .constraint_peers(idx)
+                    .into_iter()
+                    .map(&source)
+                    .collect();
+                peers == alone.constraint_peers(source(idx)).into_iter().collect()
+            })
+        })
+    }
+
+    /// This board rotated a quarter turn clockwise
+    pub fn rotate90(&self) -> Board {
+        self.remap(|idx| BoardIdx::new(idx.row, 8 - idx.col))
+    }
+
+    /// This board mirrored left to right
+    pub fn reflect_horizontal(&self) -> Board {
+        self.remap(|idx| BoardIdx::new(8 - idx.col, idx.row))
+    }
+
+    /// This board with rows `a` and `b` swapped, which must be in the same band
+    ///
+    /// Panics if either row is out of range or they're in different bands, since that swap could break the squares,
+    /// or if the swap would move the board's anti-knight, windows, or diagonals constraint
+    #[track_caller]
+    pub fn swap_rows(&self, a: usize, b: usize) -> Board {
+        assert!(
+            a < 9 && b < 9 && a / 3 == b / 3,
+            "Rows {a} and {b} aren't in the same band"
+        );
+        self.remap(|idx| BoardIdx::new(idx.col, swapped(idx.row, a, b)))
+    }
+
+    /// This board with columns `a` and `b` swapped, which must be in the same stack
+    ///
+    /// Panics if either column is out of range or they're in different stacks, since that swap could break the
+    /// squares, or if the swap would move the board's anti-knight, windows, or diagonals constraint
+    #[track_caller]
+    pub fn swap_cols(&self, a: usize, b: usize) -> Board {
+        assert!(
+            a < 9 && b < 9 && a / 3 == b / 3,
+            "Columns {a} and {b} aren't in the same stack"
+        );
+        self.remap(|idx| BoardIdx::new(swapped(idx.col, a, b), idx.row))
+    }
+
+    /// This board with bands `a` and `b` (each three rows of squares, counted from the top) swapped
+    ///
+    /// Panics if either band is out of range, or if the swap would move the board's anti-knight, windows, or
+    /// diagonals constraint
+    #[track_caller]
+    pub fn swap_bands(&self, a: usize, b: usize) -> Board {
+        assert!(a < 3 && b < 3, "Bands {a} and {b} aren't both in 0..3");
+        self.remap(|idx| BoardIdx::new(idx.col, swapped(idx.row / 3, a, b) * 3 + idx.row % 3))
+    }
+
+    /// This board with stacks `a` and `b` (each three columns of squares, counted from the left) swapped
+    ///
+    /// Panics if either stack is out of range, or if the swap would move the board's anti-knight, windows, or
+    /// diagonals constraint
+    #[track_caller]
+    pub fn swap_stacks(&self, a: usize, b: usize) -> Board {
+        assert!(a < 3 && b < 3, "Stacks {a} and {b} aren't both in 0..3");
+        self.remap(|idx| BoardIdx::new(swapped(idx.col / 3, a, b) * 3 + idx.col % 3, idx.row))
+    }
+
+    /// The strongest [`Symmetry`] of where the played cells are, ignoring which numbers they hold
+    ///
+    /// Returns `None` if the layout has no symmetry at all. A blank board counts as [`Symmetry::Full`]
+    pub fn clue_symmetry(&self) -> Option<Symmetry> {
+        Symmetry::ALL.into_iter().find(|symmetry| {
+            symmetry.maps().into_iter().all(|map| {
+                self.played
+                    .iter()
+                    .all(|&idx| self.played.contains(&map(idx)))
+            })
+        })
+    }
+
+    /// This board with every number `n` replaced by `mapping[n - 1]`
+    ///
+    /// Candidates crossed off through [`Board::eliminate`] are relabeled too, and anti-knight, windows, and diagonals
+    /// carry over, since they don't care which numbers are where. Cage sums and thermometers do, so boards with
+    /// those can't be relabeled
+    ///
+    /// Panics if `mapping` isn't a permutation of `1..=9`, or the board has a cage or thermometer
+    #[track_caller]
+    pub fn relabel(&self, mapping: &[u8; 9]) -> Board {
+        assert!(
+            mapping.iter().sorted().eq(&[1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            "Relabel mapping {mapping:?} isn't a permutation of 1..=9"
+        );
+        assert!(
+            self.can_relabel(),
+            "Relabeling would break the board's cage or thermometer constraints"
+        );
+
+        let mut board = self.remap(|idx| idx);
+        let label = |num: u8| mapping[num as usize - 1];
+        for cell in &mut board.cells {
+            let mut relabeled = Cell::none_possible();
+            for num in cell.possibilities() {
+                relabeled.set_possible(label(num), true);
+            }
+            *cell = relabeled;
+        }
+        board.user_eliminations = board
+            .user_eliminations
+            .iter()
+            .map(|&(idx, num)| (idx, label(num)))
+            .collect();
+        board
+    }
+
+    /// Whether [`Board::relabel`] keeps this board's constraints, which it does unless there's a cage or thermometer
+    fn can_relabel(&self) -> bool {
+        !self.constraints.iter().any(|constraint| {
+            matches!(constraint, Constraint::Cage(_) | Constraint::Thermometer(_))
+        })
+    }
+
+    /// A random puzzle equivalent to this one, for augmenting datasets or checking the solver doesn't care about layout
+    ///
+    /// Applies one of the eight rotations and reflections, a random shuffle of bands, stacks, and the lines within them,
+    /// and a random relabeling of the numbers. Solving the result gives the same transformation of this board's solution.
+    /// The shuffle is left out if it would move an anti-knight, windows, or diagonals constraint, and the relabeling
+    /// if the board has a cage or thermometer
+    pub fn randomize_equivalent(&self, rng: &mut Rng) -> Board {
+        let mut board = self.clone();
+        for _ in 0..rng.below(4) {
+            board = board.rotate90();
+        }
+        if rng.below(2) == 1 {
+            board = board.reflect_horizontal();
+        }
+
+        let orders = line_orders();
+        let rows = orders[rng.below(orders.len())];
+        let cols = orders[rng.below(orders.len())];
+        let shuffle = |idx: BoardIdx| BoardIdx::new(cols[idx.col], rows[idx.row]);
+        if board.keeps_constraints(&shuffle) {
+            board = board.remap(shuffle);
+        }
+
+        let mut mapping = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        rng.shuffle(&mut mapping);
+        match board.can_relabel() {
+            true => board.relabel(&mapping),
+            false => board,
+        }
+    }
+
+    /// The representative of this puzzle's equivalence class, so two puzzles are equivalent iff their canonical forms match
+    ///
+    /// Tries every combination of transposition, band and stack swaps, and row and column swaps within them,
+    /// relabeling numbers in order of first appearance each time, and keeps the row-major
+    /// lexicographically smallest grid (empty cells count as 0). Only played cells are kept, and only while they
+    /// still hold their number. That's over three million arrangements, but most are rejected within their first few cells
+    pub fn canonical_form(&self) -> Board {
+        let values: [u8; 81] =
+            array::from_fn(|idx| self.played_num(BoardIdx::from_linear(idx)).unwrap_or(0));
+        let transposed: [u8; 81] = array::from_fn(|idx| values[(idx % 9) * 9 + idx / 9]);
+        let orders = line_orders();
+
+        let mut best = [u8::MAX; 81];
+        for grid in [values, transposed] {
+            for (rows, cols) in iproduct!(&orders, &orders) {
+                let mut labels = [0u8; 10];
+                let mut next_label = 1;
+                let mut candidate = [0; 81];
+                let mut ordering = Ordering::Equal;
+
+                for (idx, slot) in candidate.iter_mut().enumerate() {
+                    let value = grid[rows[idx / 9] * 9 + cols[idx % 9]] as usize;
+                    if value != 0 && labels[value] == 0 {
+                        labels[value] = next_label;
+                        next_label += 1;
+                    }
+                    *slot = labels[value];
+
+                    if ordering == Ordering::Equal {
+                        ordering = (*slot).cmp(&best[idx]);
+                        if ordering == Ordering::Greater {
+                            break;
+                        }
+                    }
+                }
+
+                if ordering == Ordering::Less {
+                    best = candidate;
+                }
+            }
+        }
+
+        let mut board = Board::new();
+        for (idx, &num) in best.iter().enumerate() {
+            if num != 0 {
+                board.play_cell(BoardIdx::from_linear(idx), num);
+            }
+        }
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::Solver;
+
+    fn easy() -> Board {
+        include_str!("../examples/easy1.txt").parse().unwrap()
+    }
+
+    fn solution(board: &Board) -> String {
+        Solver::new().solve(board.clone()).unwrap().to_line()
+    }
+
+    #[test]
+    fn rotated_puzzle_solves_to_rotated_solution() {
+        let puzzle = easy();
+        let solved = Solver::new().solve(puzzle.clone()).unwrap();
+        assert_eq!(solution(&puzzle.rotate90()), solved.rotate90().to_line());
+    }
+
+    #[test]
+    fn four_rotations_are_the_identity() {
+        let puzzle = easy();
+        let turned = puzzle.rotate90().rotate90().rotate90().rotate90();
+        assert_eq!(turned.to_line(), puzzle.to_line());
+        assert_eq!(turned.played, puzzle.played);
+    }
+
+    #[test]
+    fn reflected_puzzle_solves_to_reflected_solution() {
+        let puzzle = easy();
+        let solved = Solver::new().solve(puzzle.clone()).unwrap();
+        assert_eq!(
+            solution(&puzzle.reflect_horizontal()),
+            solved.reflect_horizontal().to_line()
+        );
+    }
+
+    #[test]
+    fn relabeled_puzzle_solves_to_relabeled_solution() {
+        let mapping = [9, 8, 7, 6, 5, 4, 3, 2, 1];
+        let puzzle = easy();
+        let solved = Solver::new().solve(puzzle.clone()).unwrap();
+        assert_eq!(
+            solution(&puzzle.relabel(&mapping)),
+            solved.relabel(&mapping).to_line()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't a permutation")]
+    fn relabel_rejects_a_repeated_number() {
+        easy().relabel(&[1, 1, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn canonical_form_is_shared_by_equivalent_puzzles() {
+        let puzzle = easy();
+        let variant = puzzle
+            .rotate90()
+            .relabel(&[3, 1, 2, 6, 4, 5, 9, 7, 8])
+            .swap_bands(0, 2);
+        assert_eq!(
+            variant.canonical_form().to_line(),
+            puzzle.canonical_form().to_line()
+        );
+    }
+
+    #[test]
+    fn canonical_form_skips_an_emptied_cell() {
+        let mut board = Board::new();
+        board.play_cell(BoardIdx::new(4, 4), 3);
+        board.play_cell(BoardIdx::new(0, 0), 5);
+        board.set_raw(BoardIdx::new(0, 0), Cell::none_possible());
+        assert_eq!(board.canonical_form().clue_count(), 1);
+    }
+
+    #[test]
+    fn randomized_puzzle_solves_to_the_same_transform_of_the_solution() {
+        let puzzle = easy();
+        let solved = Solver::new().solve(puzzle.clone()).unwrap();
+        for seed in 0..8 {
+            // The same seed picks the same transformation for both
+            let variant = puzzle.randomize_equivalent(&mut Rng::new(seed));
+            let variant_solved = solved.randomize_equivalent(&mut Rng::new(seed));
+            assert_eq!(solution(&variant), variant_solved.to_line(), "seed {seed}");
+            assert_eq!(variant.clue_count(), puzzle.clue_count());
+        }
+    }
+
+    #[test]
+    fn randomize_equivalent_changes_the_text() {
+        let puzzle = easy();
+        let variants = (0..8)
+            .map(|seed| puzzle.randomize_equivalent(&mut Rng::new(seed)).to_line())
+            .collect::<HashSet<_>>();
+        assert!(!variants.contains(&puzzle.to_line()));
+        assert!(variants.len() > 1);
+    }
+
+    #[test]
+    fn swapped_puzzle_solves_to_swapped_solution() {
+        let puzzle = easy();
+        let solved = Solver::new().solve(puzzle.clone()).unwrap();
+        let swaps: [fn(&Board) -> Board; 4] = [
+            |board| board.swap_rows(3, 5),
+            |board| board.swap_cols(6, 7),
+            |board| board.swap_bands(0, 1),
+            |board| board.swap_stacks(2, 0),
+        ];
+        for swap in swaps {
+            assert_eq!(solution(&swap(&puzzle)), swap(&solved).to_line());
+        }
+    }
+
+    #[test]
+    fn swap_bands_moves_whole_rows() {
+        let puzzle = easy();
+        let swapped = puzzle.swap_bands(0, 2);
+        for (row, col) in iproduct!(0..9, 0..9) {
+            let from_row = match row / 3 {
+                0 => row + 6,
+                1 => row,
+                _ => row - 6,
+            };
+            let (to, from) = (BoardIdx::new(col, row), BoardIdx::new(col, from_row));
+            assert_eq!(swapped.get(to), puzzle.get(from));
+            assert_eq!(swapped.played.contains(&to), puzzle.played.contains(&from));
+        }
+        assert_eq!(swapped.swap_bands(2, 0).to_line(), puzzle.to_line());
+    }
+
+    #[test]
+    #[should_panic(expected = "aren't in the same band")]
+    fn swap_rows_rejects_rows_in_different_bands() {
+        easy().swap_rows(2, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "aren't in the same stack")]
+    fn swap_cols_rejects_columns_in_different_stacks() {
+        easy().swap_cols(0, 8);
+    }
+
+    #[test]
+    #[should_panic(expected = "aren't both in 0..3")]
+    fn swap_stacks_rejects_a_stack_out_of_range() {
+        easy().swap_stacks(0, 3);
+    }
+
+    /// A puzzle file with constraint lines, read like the command line reads it
+    fn load(file: &str) -> Board {
+        let (grid, constraints) = crate::constraint::split_constraints(file).unwrap();
+        let mut board: Board = grid.parse().unwrap();
+        for constraint in constraints {
+            board.add_constraint(constraint);
+        }
+        board
+    }
+
+    #[test]
+    fn transformed_cages_move_with_their_cells() {
+        let puzzle = load(include_str!("../examples/killer1.txt"));
+        let solved = Solver::new().solve(puzzle.clone()).unwrap();
+        for (turned, expected) in [
+            (puzzle.rotate90(), solved.rotate90()),
+            (puzzle.swap_rows(0, 2), solved.swap_rows(0, 2)),
+        ] {
+            let solution = Solver::new().solve(turned).unwrap();
+            assert!(solution.verify_constraints().is_ok());
+            assert_eq!(solution, expected);
+        }
+
+        let Some(Constraint::Cage(cage)) = puzzle.rotate90().constraints.first().cloned() else {
+            panic!("the killer example starts with a cage");
+        };
+        let Some(Constraint::Cage(original)) = puzzle.constraints.first() else {
+            unreachable!();
+        };
+        let rotated = original
+            .cells
+            .iter()
+            .map(|idx| BoardIdx::new(8 - idx.row, idx.col))
+            .collect::<Vec<_>>();
+        assert_eq!(cage.cells, rotated);
+        assert_eq!(cage.sum, original.sum);
+    }
+
+    #[test]
+    fn transformations_carry_givens_and_eliminations() {
+        let mut puzzle = easy();
+        puzzle.freeze();
+        let cells = (0..81).map(BoardIdx::from_linear);
+        let given = cells.clone().find(|&idx| puzzle.is_given(idx)).unwrap();
+        let marked = cells
+            .clone()
+            .find(|idx| !puzzle.played.contains(idx))
+            .unwrap();
+        let num = puzzle.get(marked).possibilities()[0];
+        puzzle.eliminate(marked, num).unwrap();
+
+        let mut turned = puzzle.rotate90();
+        turned.recompute_candidates();
+        let rotated = |idx: BoardIdx| BoardIdx::new(8 - idx.row, idx.col);
+        assert!(turned.is_given(rotated(given)));
+        assert!(!turned.get(rotated(marked)).is_possible(num));
+        assert_eq!(turned.clue_count(), puzzle.clue_count());
+    }
+
+    #[test]
+    fn rotations_keep_an_anti_knight_constraint() {
+        let mut puzzle = easy();
+        puzzle.add_constraint(Constraint::AntiKnight);
+        assert_eq!(puzzle.rotate90().constraints, [Constraint::AntiKnight]);
+        assert_eq!(
+            puzzle.reflect_horizontal().constraints,
+            [Constraint::AntiKnight]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "would move the board's anti-knight")]
+    fn swap_rows_rejects_moving_an_anti_knight_constraint() {
+        let mut puzzle = Board::new();
+        puzzle.add_constraint(Constraint::AntiKnight);
+        puzzle.swap_rows(0, 1);
+    }
+
+    #[test]
+    fn clue_symmetry_of_a_half_turn_puzzle() {
+        let mut board: Board =
+            "3.....9.8..43...1...89.2......1..5...5..7..4...9..3......6.78...3...41..7.2.....5"
+                .parse()
+                .unwrap();
+        assert_eq!(board.clue_symmetry(), Some(Symmetry::HalfTurn));
+
+        assert!(board.unplay_cell(BoardIdx::new(0, 0)));
+        assert_eq!(board.clue_symmetry(), None);
+        assert!(board.unplay_cell(BoardIdx::new(8, 8)));
+        assert_eq!(board.clue_symmetry(), Some(Symmetry::HalfTurn));
+    }
+
+    #[test]
+    fn clue_symmetry_picks_the_strongest() {
+        assert_eq!(Board::new().clue_symmetry(), Some(Symmetry::Full));
+
+        let mut board = Board::new();
+        board.play_cell(BoardIdx::new(4, 4), 5);
+        assert_eq!(board.clue_symmetry(), Some(Symmetry::Full));
+        board.play_cell(BoardIdx::new(1, 0), 1);
+        board.play_cell(BoardIdx::new(7, 0), 2);
+        assert_eq!(board.clue_symmetry(), Some(Symmetry::Horizontal));
+        board.play_cell(BoardIdx::new(1, 8), 3);
+        board.play_cell(BoardIdx::new(7, 8), 4);
+        assert_eq!(board.clue_symmetry(), Some(Symmetry::HalfTurn));
+    }
+
+    #[test]
+    #[should_panic(expected = "cage or thermometer")]
+    fn relabel_rejects_a_cage() {
+        load(include_str!("../examples/killer1.txt")).relabel(&[2, 1, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn relabel_carries_eliminations_and_an_anti_knight_constraint() {
+        let mut puzzle = easy();
+        puzzle.add_constraint(Constraint::AntiKnight);
+        let open = BoardIdx::new(0, 8);
+        let num = puzzle.candidates(open)[0];
+        puzzle.eliminate(open, num).unwrap();
+
+        let mapping = [9, 8, 7, 6, 5, 4, 3, 2, 1];
+        let relabeled = puzzle.relabel(&mapping);
+        assert_eq!(relabeled.constraints, puzzle.constraints);
+        assert_eq!(relabeled.user_eliminations, [(open, 10 - num)]);
+        assert!(!relabeled.get(open).is_possible(10 - num));
+        let mut recomputed = relabeled.clone();
+        recomputed.recompute_candidates();
+        assert!(!recomputed.get(open).is_possible(10 - num));
+    }
+
+    #[test]
+    fn randomized_killer_keeps_its_cage_sums() {
+        let puzzle = load(include_str!("../examples/killer1.txt"));
+        let solved = Solver::new().solve(puzzle.clone()).unwrap();
+        for seed in 0..4 {
+            let variant = puzzle.randomize_equivalent(&mut Rng::new(seed));
+            let solution = Solver::new().solve(variant).unwrap();
+            assert!(solution.verify_constraints().is_ok(), "seed {seed}");
+            assert_eq!(solution, solved.randomize_equivalent(&mut Rng::new(seed)));
+        }
+    }
+}